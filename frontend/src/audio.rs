@@ -0,0 +1,19 @@
+use leptos::logging::error;
+use wasm_bindgen::prelude::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlAudioElement;
+
+use crate::editor::encode;
+
+// 连击模式下是几张卡拼起来的虚拟卡，按假名取发音。
+// 取不到（有字合成不了）时调 on_error；页面还没被点过时浏览器会拒绝自动播放，那种不算
+pub fn play(kana: &str, on_error: impl FnOnce() + 'static) {
+    let url = format!("/api/v1/audio/kana/{}", encode(kana));
+    match HtmlAudioElement::new_with_src(&url) {
+        Ok(audio) => {
+            audio.set_onerror(Some(Closure::once_into_js(on_error).unchecked_ref()));
+            let _ = audio.play();
+        }
+        Err(e) => error!("Failed to create audio element: {:?}", e),
+    }
+}
@@ -0,0 +1,426 @@
+use std::collections::HashSet;
+
+use gloo_net::http::Request;
+use kana_types::{Card, CardRequest, CardUpdate, Deck, DeckCards, DeckRequest, ErrorBody, SessionDeck};
+use leptos::*;
+use serde::de::DeserializeOwned;
+
+// 发请求并解析 JSON；失败时尽量拿服务端 ErrorBody 里的 message
+pub(crate) async fn call<T: DeserializeOwned>(req: Result<Request, gloo_net::Error>) -> Result<T, String> {
+    let resp = req.map_err(|e| e.to_string())?.send().await.map_err(|e| e.to_string())?;
+    if !resp.ok() {
+        return Err(match resp.json::<ErrorBody>().await {
+            Ok(body) => body.message,
+            Err(_) => format!("HTTP {}", resp.status()),
+        });
+    }
+    resp.json::<T>().await.map_err(|e| e.to_string())
+}
+
+pub(crate) fn encode(value: &str) -> String {
+    String::from(js_sys::encode_uri_component(value))
+}
+
+fn split_tags(tags: &str) -> Vec<String> {
+    tags.split(|c: char| c.is_whitespace() || c == ',').filter(|t| !t.is_empty()).map(str::to_string).collect()
+}
+
+// buried_until 是 UTC 的 "YYYY-MM-DD HH:MM:SS"，过期的不算
+fn is_buried(card: &Card) -> bool {
+    let now: String = js_sys::Date::new_0().to_iso_string().into();
+    let now = now.replace('T', " ");
+    card.buried_until.as_deref().is_some_and(|until| until > &now[..19])
+}
+
+// 和服务端 mnemonic::image_url 一致，文件名变了浏览器就会重新取
+fn image_url(card_id: &str, file: &str) -> String {
+    format!("/api/v1/cards/{}/mnemonic/image?v={}", encode(card_id), encode(file))
+}
+
+const BUTTON: &str = "font-size: 12px; color: #777; border: 1px solid #eee; background: #fff; border-radius: 4px; padding: 2px 8px; cursor: pointer;";
+const FIELD: &str = "border: none; border-bottom: 1px solid #eee; outline: none; font-size: 13px; padding: 4px; color: #555; background: transparent;";
+
+#[component]
+pub fn Editor(on_back: Callback<()>) -> impl IntoView {
+    let (decks, set_decks) = create_signal(Vec::<Deck>::new());
+    let (cards, set_cards) = create_signal(Vec::<Card>::new());
+    // 选中的卡组；勾选框改的就是它的成员
+    let (selected, set_selected) = create_signal(Option::<String>::None);
+    let (members, set_members) = create_signal(HashSet::<String>::new());
+    let (session_deck, set_session_deck) = create_signal(Option::<String>::None);
+    let (search, set_search) = create_signal(String::new());
+    let (new_deck, set_new_deck) = create_signal(String::new());
+    let (message, set_message) = create_signal(Option::<String>::None);
+
+    // 表单：editing 为 None 时是新建
+    let (editing, set_editing) = create_signal(Option::<String>::None);
+    let (kana, set_kana) = create_signal(String::new());
+    let (romaji, set_romaji) = create_signal(String::new());
+    let (meaning, set_meaning) = create_signal(String::new());
+    let (tags, set_tags) = create_signal(String::new());
+    let (mnemonic, set_mnemonic) = create_signal(String::new());
+    // 正在编辑的卡片当前的助记图片；新建时还没有卡片 id，传不了图
+    let (image, set_image) = create_signal(Option::<String>::None);
+
+    let report = move |result: Result<(), String>| {
+        set_message.set(result.err());
+    };
+
+    let reload = move || {
+        spawn_local(async move {
+            let result = async {
+                let q = search.get_untracked();
+                let url = if q.trim().is_empty() { "/api/v1/cards".to_string() } else { format!("/api/v1/cards?q={}", encode(q.trim())) };
+                set_cards.set(call::<Vec<Card>>(Request::get(&url).build()).await?);
+                set_decks.set(call::<Vec<Deck>>(Request::get("/api/v1/decks").build()).await?);
+                set_session_deck.set(call::<SessionDeck>(Request::get("/api/v1/session/deck").build()).await?.deck_id);
+
+                let ids = match selected.get_untracked() {
+                    Some(id) => {
+                        let url = format!("/api/v1/cards?deck={}", encode(&id));
+                        call::<Vec<Card>>(Request::get(&url).build()).await?.into_iter().map(|c| c.id).collect()
+                    }
+                    None => HashSet::new(),
+                };
+                set_members.set(ids);
+                Ok(())
+            }.await;
+            report(result);
+        });
+    };
+    reload();
+
+    let clear_form = move || {
+        batch(move || {
+            set_editing.set(None);
+            set_kana.set(String::new());
+            set_romaji.set(String::new());
+            set_meaning.set(String::new());
+            set_tags.set(String::new());
+            set_mnemonic.set(String::new());
+            set_image.set(None);
+        });
+    };
+
+    let edit = move |card: Card| {
+        batch(move || {
+            set_editing.set(Some(card.id));
+            set_kana.set(card.kana_char);
+            set_romaji.set(card.romaji);
+            set_meaning.set(card.meaning.unwrap_or_default());
+            set_tags.set(card.tags);
+            set_mnemonic.set(card.mnemonic.unwrap_or_default());
+            set_image.set(card.mnemonic_image);
+        });
+    };
+
+    let save_card = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        spawn_local(async move {
+            let result = match editing.get_untracked() {
+                Some(id) => {
+                    let body = CardUpdate {
+                        kana_char: Some(kana.get_untracked()),
+                        romaji: Some(romaji.get_untracked()),
+                        meaning: Some(meaning.get_untracked()),
+                        tags: Some(split_tags(&tags.get_untracked())),
+                        suspended: None,
+                        mnemonic: Some(mnemonic.get_untracked()),
+                    };
+                    call::<Card>(Request::put(&format!("/api/v1/cards/{}", encode(&id))).json(&body)).await
+                }
+                None => {
+                    let meaning = meaning.get_untracked();
+                    let mnemonic = mnemonic.get_untracked();
+                    let body = CardRequest {
+                        kana_char: kana.get_untracked(),
+                        romaji: romaji.get_untracked(),
+                        meaning: (!meaning.trim().is_empty()).then_some(meaning),
+                        tags: split_tags(&tags.get_untracked()),
+                        // 留空让服务端套用内置助记
+                        mnemonic: (!mnemonic.trim().is_empty()).then_some(mnemonic),
+                    };
+                    call::<Card>(Request::post("/api/v1/cards").json(&body)).await
+                }
+            };
+            match result {
+                Ok(_) => {
+                    clear_form();
+                    reload();
+                }
+                Err(e) => set_message.set(Some(e)),
+            }
+        });
+    };
+
+    // 选了文件就直接传，不等点保存
+    let upload_image = move |ev: ev::Event| {
+        let Some(id) = editing.get_untracked() else { return; };
+        let input: web_sys::HtmlInputElement = event_target(&ev);
+        let Some(file) = input.files().and_then(|files| files.get(0)) else { return; };
+        input.set_value("");
+        spawn_local(async move {
+            let result = async {
+                let form = web_sys::FormData::new().map_err(|_| "FormData unavailable".to_string())?;
+                form.append_with_blob("image", &file).map_err(|_| "failed to read file".to_string())?;
+                let url = format!("/api/v1/cards/{}/mnemonic/image", encode(&id));
+                call::<Card>(Request::put(&url).body(form)).await
+            }.await;
+            match result {
+                Ok(card) => {
+                    set_image.set(card.mnemonic_image);
+                    set_message.set(None);
+                    reload();
+                }
+                Err(e) => set_message.set(Some(e)),
+            }
+        });
+    };
+
+    let remove_image = move || {
+        let Some(id) = editing.get_untracked() else { return; };
+        spawn_local(async move {
+            let url = format!("/api/v1/cards/{}/mnemonic/image", encode(&id));
+            match call::<Card>(Request::delete(&url).build()).await {
+                Ok(_) => {
+                    set_image.set(None);
+                    set_message.set(None);
+                    reload();
+                }
+                Err(e) => set_message.set(Some(e)),
+            }
+        });
+    };
+
+    let toggle_suspended = move |card: Card| {
+        spawn_local(async move {
+            let body = CardUpdate { suspended: Some(!card.suspended), ..Default::default() };
+            let result = call::<Card>(Request::put(&format!("/api/v1/cards/{}", encode(&card.id))).json(&body)).await;
+            report(result.map(|_| ()));
+            reload();
+        });
+    };
+
+    // 埋到明天；再点一次取消
+    let toggle_buried = move |card: Card| {
+        spawn_local(async move {
+            let url = format!("/api/v1/cards/{}/bury", encode(&card.id));
+            let req = if is_buried(&card) { Request::delete(&url) } else { Request::post(&url) };
+            let result = call::<Card>(req.build()).await;
+            report(result.map(|_| ()));
+            reload();
+        });
+    };
+
+    let delete_card = move |card: Card| {
+        let confirmed = window().confirm_with_message(&format!("删除 {} 和它的复习记录？", card.kana_char)).unwrap_or(false);
+        if !confirmed { return; }
+        spawn_local(async move {
+            let result = call::<Card>(Request::delete(&format!("/api/v1/cards/{}", encode(&card.id))).build()).await;
+            report(result.map(|_| ()));
+            reload();
+        });
+    };
+
+    let toggle_member = move |card_id: String, checked: bool| {
+        let Some(deck_id) = selected.get_untracked() else { return; };
+        spawn_local(async move {
+            let result = if checked {
+                let body = DeckCards { card_ids: vec![card_id] };
+                call::<Deck>(Request::post(&format!("/api/v1/decks/{}/cards", encode(&deck_id))).json(&body)).await
+            } else {
+                let url = format!("/api/v1/decks/{}/cards/{}", encode(&deck_id), encode(&card_id));
+                call::<Deck>(Request::delete(&url).build()).await
+            };
+            report(result.map(|_| ()));
+            reload();
+        });
+    };
+
+    let create_deck = move || {
+        let name = new_deck.get_untracked();
+        if name.trim().is_empty() { return; }
+        spawn_local(async move {
+            let body = DeckRequest { name, card_ids: Vec::new() };
+            match call::<Deck>(Request::post("/api/v1/decks").json(&body)).await {
+                Ok(deck) => {
+                    set_new_deck.set(String::new());
+                    set_selected.set(Some(deck.id));
+                    set_message.set(None);
+                    reload();
+                }
+                Err(e) => set_message.set(Some(e)),
+            }
+        });
+    };
+
+    let delete_deck = move || {
+        let Some(deck_id) = selected.get_untracked() else { return; };
+        if !window().confirm_with_message("删除这个卡组？卡片本身会保留。").unwrap_or(false) { return; }
+        spawn_local(async move {
+            let result = call::<Vec<Deck>>(Request::delete(&format!("/api/v1/decks/{}", encode(&deck_id))).build()).await;
+            set_selected.set(None);
+            report(result.map(|_| ()));
+            reload();
+        });
+    };
+
+    // 切到选中的卡组（没选就是全部卡片）然后回去练
+    let practice = move || {
+        spawn_local(async move {
+            let body = SessionDeck { deck_id: selected.get_untracked() };
+            match call::<SessionDeck>(Request::put("/api/v1/session/deck").json(&body)).await {
+                Ok(_) => on_back.call(()),
+                Err(e) => set_message.set(Some(e)),
+            }
+        });
+    };
+
+    view! {
+        <div style="
+            width: 520px;
+            max-height: 90vh;
+            overflow-y: auto;
+            padding: 15px;
+            background: #ffffff;
+            border-radius: 12px;
+            box-shadow: 0 4px 15px rgba(0,0,0,0.05);
+            display: flex;
+            flex-direction: column;
+            gap: 10px;
+            font-family: 'Segoe UI', sans-serif;
+            font-size: 13px;
+            color: #555;
+            border: 1px solid #f0f0f0;
+        ">
+            <div style="display: flex; justify-content: space-between; align-items: center;">
+                <span style="font-weight: bold;">"卡片编辑"</span>
+                <span style="cursor: pointer; font-size: 12px; color: #999;" on:click=move |_| on_back.call(())>"返回"</span>
+            </div>
+
+            // 卡组
+            <div style="display: flex; gap: 6px; align-items: center; flex-wrap: wrap;">
+                <select
+                    on:change=move |ev| {
+                        let value = event_target_value(&ev);
+                        set_selected.set((!value.is_empty()).then_some(value));
+                        reload();
+                    }
+                    style=FIELD
+                >
+                    <option value="" selected=move || selected.get().is_none()>"全部卡片"</option>
+                    {move || decks.get().into_iter().map(|deck| {
+                        let id = deck.id.clone();
+                        let active = session_deck.get().as_deref() == Some(deck.id.as_str());
+                        view! {
+                            <option value=deck.id.clone() selected=move || selected.get().as_deref() == Some(id.as_str())>
+                                {format!("{} ({}){}", deck.name, deck.card_count, if active { " · 练习中" } else { "" })}
+                            </option>
+                        }
+                    }).collect_view()}
+                </select>
+                <button style=BUTTON on:click=move |_| practice()>"练习此卡组"</button>
+                {move || selected.get().map(|_| view! {
+                    <button style=BUTTON on:click=move |_| delete_deck()>"删除卡组"</button>
+                })}
+                <span style="flex: 1;"></span>
+                <input type="text" placeholder="新卡组"
+                    prop:value=new_deck
+                    on:input=move |ev| set_new_deck.set(event_target_value(&ev))
+                    style=format!("{} width: 90px;", FIELD)
+                />
+                <button style=BUTTON on:click=move |_| create_deck()>"新建"</button>
+            </div>
+
+            // 新建/编辑卡片
+            <form on:submit=save_card style="display: flex; gap: 6px; align-items: center; flex-wrap: wrap;">
+                <input type="text" placeholder="假名" prop:value=kana
+                    on:input=move |ev| set_kana.set(event_target_value(&ev))
+                    style=format!("{} width: 60px;", FIELD) />
+                <input type="text" placeholder="罗马音" prop:value=romaji
+                    on:input=move |ev| set_romaji.set(event_target_value(&ev))
+                    style=format!("{} width: 70px;", FIELD) />
+                <input type="text" placeholder="释义" prop:value=meaning
+                    on:input=move |ev| set_meaning.set(event_target_value(&ev))
+                    style=format!("{} width: 100px;", FIELD) />
+                <input type="text" placeholder="标签" prop:value=tags
+                    on:input=move |ev| set_tags.set(event_target_value(&ev))
+                    style=format!("{} flex: 1;", FIELD) />
+                <button type="submit" style=BUTTON>{move || if editing.get().is_some() { "保存" } else { "添加" }}</button>
+                {move || editing.get().map(|_| view! {
+                    <button type="button" style=BUTTON on:click=move |_| clear_form()>"取消"</button>
+                })}
+                <textarea placeholder="助记（留空使用内置的）" prop:value=mnemonic
+                    on:input=move |ev| set_mnemonic.set(event_target_value(&ev))
+                    rows="2"
+                    style=format!("{} width: 100%; resize: vertical; font-family: inherit;", FIELD)></textarea>
+            </form>
+
+            // 助记图片，只有编辑已有卡片时能传
+            {move || editing.get().map(|id| view! {
+                <div style="display: flex; gap: 8px; align-items: center; font-size: 12px; color: #999;">
+                    {move || image.get().map(|file| view! {
+                        <img src=image_url(&id, &file) style="max-height: 60px; max-width: 120px; border-radius: 4px;" />
+                        <button type="button" style=BUTTON on:click=move |_| remove_image()>"移除图片"</button>
+                    })}
+                    <label style="cursor: pointer;">
+                        {move || if image.get().is_some() { "换一张图片" } else { "添加助记图片" }}
+                        <input type="file" accept="image/png,image/jpeg,image/gif,image/webp"
+                            on:change=upload_image style="display: none;" />
+                    </label>
+                </div>
+            })}
+
+            <div style="height: 16px; font-size: 12px; color: #e57373;">
+                {move || message.get().unwrap_or_default()}
+            </div>
+
+            <input type="search" placeholder="搜索假名、罗马音或释义"
+                prop:value=search
+                on:input=move |ev| { set_search.set(event_target_value(&ev)); reload(); }
+                style=FIELD
+            />
+
+            // 卡片列表
+            <table style="width: 100%; border-collapse: collapse; font-size: 13px;">
+                <tbody>
+                    {move || {
+                        let has_deck = selected.get().is_some();
+                        let in_deck = members.get();
+                        cards.get().into_iter().map(|card| {
+                            let checked = in_deck.contains(&card.id);
+                            let card_id = card.id.clone();
+                            let (for_edit, for_suspend, for_bury, for_delete) = (card.clone(), card.clone(), card.clone(), card.clone());
+                            let buried = is_buried(&card);
+                            let row_color = if card.suspended || buried { "#bbb" } else { "#555" };
+                            view! {
+                                <tr style=format!("border-bottom: 1px solid #f5f5f5; color: {};", row_color)>
+                                    <td style="width: 20px;">
+                                        {has_deck.then(|| view! {
+                                            <input type="checkbox" prop:checked=checked
+                                                on:change=move |ev| toggle_member(card_id.clone(), event_target_checked(&ev)) />
+                                        })}
+                                    </td>
+                                    <td style="font-size: 18px; font-family: 'Meiryo', 'Hiragino Kaku Gothic ProN', 'Yu Gothic', sans-serif;">{card.kana_char}</td>
+                                    <td>{card.romaji}</td>
+                                    <td>{card.meaning.unwrap_or_default()}</td>
+                                    <td style="font-size: 11px; color: #999;">{card.tags}</td>
+                                    <td style="text-align: right; white-space: nowrap; font-size: 11px;">
+                                        <span style="cursor: pointer; margin-left: 6px;" on:click=move |_| edit(for_edit.clone())>"编辑"</span>
+                                        <span style="cursor: pointer; margin-left: 6px;" on:click=move |_| toggle_buried(for_bury.clone())>
+                                            {if buried { "取消埋" } else { "明天再练" }}
+                                        </span>
+                                        <span style="cursor: pointer; margin-left: 6px;" on:click=move |_| toggle_suspended(for_suspend.clone())>
+                                            {if card.suspended { "启用" } else { "停用" }}
+                                        </span>
+                                        <span style="cursor: pointer; margin-left: 6px; color: #e57373;" on:click=move |_| delete_card(for_delete.clone())>"删除"</span>
+                                    </td>
+                                </tr>
+                            }
+                        }).collect_view()
+                    }}
+                </tbody>
+            </table>
+        </div>
+    }
+}
@@ -0,0 +1,123 @@
+use kana_types::DrawnStroke;
+use leptos::html::Canvas;
+use leptos::*;
+use wasm_bindgen::JsCast;
+use web_sys::CanvasRenderingContext2d;
+
+// 一格在画布上的边长（像素）；发给服务端的坐标换算成笔顺数据的 109
+const CELL_PX: f64 = 120.0;
+const CELL: f64 = 109.0;
+
+fn context(canvas: &web_sys::HtmlCanvasElement) -> Option<CanvasRenderingContext2d> {
+    canvas.get_context("2d").ok()??.dyn_into().ok()
+}
+
+fn line(ctx: &CanvasRenderingContext2d, from: (f64, f64), to: (f64, f64)) {
+    ctx.begin_path();
+    ctx.move_to(from.0, from.1);
+    ctx.line_to(to.0, to.1);
+    ctx.stroke();
+}
+
+// 每格画十字辅助线，再把已经写的笔画画上去
+fn redraw(canvas: &web_sys::HtmlCanvasElement, cells: usize, strokes: &[DrawnStroke]) {
+    let Some(ctx) = context(canvas) else { return };
+    let (width, height) = (CELL_PX * cells as f64, CELL_PX);
+    ctx.clear_rect(0.0, 0.0, width, height);
+
+    ctx.set_stroke_style_str("#f3f3f3");
+    ctx.set_line_width(1.0);
+    for i in 0..cells {
+        let left = CELL_PX * i as f64;
+        line(&ctx, (left + CELL_PX / 2.0, 0.0), (left + CELL_PX / 2.0, height));
+        line(&ctx, (left, height / 2.0), (left + CELL_PX, height / 2.0));
+        if i > 0 {
+            ctx.set_stroke_style_str("#e0e0e0");
+            line(&ctx, (left, 0.0), (left, height));
+            ctx.set_stroke_style_str("#f3f3f3");
+        }
+    }
+
+    ctx.set_stroke_style_str("#333");
+    ctx.set_line_width(5.0);
+    ctx.set_line_cap("round");
+    ctx.set_line_join("round");
+    let scale = CELL_PX / CELL;
+    for stroke in strokes {
+        let Some(first) = stroke.first() else { continue };
+        ctx.begin_path();
+        ctx.move_to(first[0] as f64 * scale, first[1] as f64 * scale);
+        // 只有一个点时也留个印子
+        if stroke.len() == 1 {
+            ctx.line_to(first[0] as f64 * scale + 0.1, first[1] as f64 * scale);
+        }
+        for point in &stroke[1..] {
+            ctx.line_to(point[0] as f64 * scale, point[1] as f64 * scale);
+        }
+        ctx.stroke();
+    }
+}
+
+// 多字卡片一个字一格横着排；画布被 CSS 缩小时按实际显示尺寸换算坐标
+#[component]
+pub fn DrawPad(cells: usize, strokes: RwSignal<Vec<DrawnStroke>>, #[prop(into)] disabled: Signal<bool>) -> impl IntoView {
+    let canvas_ref = create_node_ref::<Canvas>();
+    let drawing = store_value(false);
+    let cells = cells.max(1);
+
+    create_effect(move |_| {
+        let Some(canvas) = canvas_ref.get() else { return };
+        strokes.with(|strokes| redraw(&canvas, cells, strokes));
+    });
+
+    let point = move |ev: &ev::PointerEvent| -> Option<[f32; 2]> {
+        let canvas = canvas_ref.get_untracked()?;
+        let rect = canvas.get_bounding_client_rect();
+        if rect.width() <= 0.0 || rect.height() <= 0.0 {
+            return None;
+        }
+        let x = (ev.client_x() as f64 - rect.left()) * CELL * cells as f64 / rect.width();
+        let y = (ev.client_y() as f64 - rect.top()) * CELL / rect.height();
+        Some([x as f32, y as f32])
+    };
+
+    let on_down = move |ev: ev::PointerEvent| {
+        if disabled.get_untracked() { return; }
+        let Some(p) = point(&ev) else { return };
+        ev.prevent_default();
+        if let Some(canvas) = canvas_ref.get_untracked() {
+            let _ = canvas.set_pointer_capture(ev.pointer_id());
+        }
+        drawing.set_value(true);
+        strokes.update(|s| s.push(vec![p]));
+    };
+
+    let on_move = move |ev: ev::PointerEvent| {
+        if !drawing.get_value() { return; }
+        let Some(p) = point(&ev) else { return };
+        strokes.update(|s| {
+            if let Some(last) = s.last_mut() {
+                last.push(p);
+            }
+        });
+    };
+
+    let on_up = move |_: ev::PointerEvent| drawing.set_value(false);
+
+    view! {
+        <canvas
+            node_ref=canvas_ref
+            width=CELL_PX * cells as f64
+            height=CELL_PX
+            on:pointerdown=on_down
+            on:pointermove=on_move
+            on:pointerup=on_up
+            on:pointercancel=on_up
+            style=format!(
+                "width: 100%; max-width: {}px; aspect-ratio: {} / 1; border: 1px solid #eee; border-radius: 6px; touch-action: none; cursor: crosshair;",
+                CELL_PX * cells as f64,
+                cells
+            )
+        ></canvas>
+    }
+}
@@ -1,85 +1,208 @@
+mod audio;
+mod editor;
+mod handwriting;
+mod strokes;
+
 use leptos::*;
 use leptos::logging::error;
-use serde::{Deserialize, Serialize};
-use gloo_net::http::Request;
+use futures::channel::mpsc::{self, UnboundedSender};
+use futures::{SinkExt, StreamExt};
+use gloo_net::websocket::{futures::WebSocket, Message};
 use leptos::html::Input;
 use wasm_bindgen::JsCast;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Card {
-    pub id: String,
-    pub kana_char: String,
-    pub romaji: String,
-    pub stability: f64,
-    pub difficulty: f64,
-    pub last_review: Option<String>,
+use gloo_net::http::Request;
+use editor::Editor;
+use handwriting::DrawPad;
+use strokes::StrokeOrder;
+use kana_types::{AuthStatus, Card, ClientMessage, DictationSegment, DrawnStroke, Grade, LoginRequest, MnemonicNote, Prompt, ServerMessage, SessionMode, SessionSummary};
+
+// 下拉框的值 -> 会话模式
+fn parse_mode(value: &str) -> SessionMode {
+    match value {
+        "sprint60" => SessionMode::Sprint { seconds: 60 },
+        "sprint120" => SessionMode::Sprint { seconds: 120 },
+        "sprint300" => SessionMode::Sprint { seconds: 300 },
+        "count50" => SessionMode::Count { cards: 50 },
+        "due" => SessionMode::Due,
+        _ => SessionMode::Open,
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BatchResponse {
-    pub batch_current: usize,
-    pub batch_total: usize,
-    pub remaining_in_deck: usize,
-    pub is_review: bool,
-    pub cycle_mistakes_count: usize,
-    pub cards: Vec<Card>,
-    pub current_card_index: usize,
+// 看反馈的时候服务端已经把下一张发过来了，先存着，按回车再显示
+#[derive(Clone)]
+enum Upcoming {
+    // (票据, 卡片)
+    Card(String, Card),
+    End(SessionSummary),
 }
 
-#[derive(Serialize)]
-struct SubmitRequest {
-    card_id: String,
-    correct: bool,
+fn ws_url() -> String {
+    let location = window().location();
+    let scheme = if location.protocol().unwrap_or_default() == "https:" { "wss" } else { "ws" };
+    format!("{}://{}/api/v1/ws", scheme, location.host().unwrap_or_default())
 }
 
-#[derive(Deserialize)]
-#[allow(unused)]
-struct SubmitResponse {
-    new_interval: i64,
+async fn fetch_auth_status() -> Option<AuthStatus> {
+    let resp = Request::get("/api/v1/auth/status").send().await.ok()?;
+    resp.json::<AuthStatus>().await.ok()
 }
 
 #[component]
 fn App() -> impl IntoView {
+    // None：还在查登录状态
+    let (status, set_status) = create_signal(Option::<AuthStatus>::None);
+    let (editing, set_editing) = create_signal(false);
+
+    let recheck = move || {
+        spawn_local(async move {
+            match fetch_auth_status().await {
+                Some(s) => set_status.set(Some(s)),
+                None => error!("Failed to fetch auth status"),
+            }
+        });
+    };
+    recheck();
+
     view! {
         <main style="display: flex; justify-content: center; align-items: center; height: 100vh; background-color: transparent;">
-            <Quiz />
+            {move || match status.get() {
+                None => view! { <div style="font-size: 14px; color: #999;">"..."</div> }.into_view(),
+                Some(s) if s.authenticated && editing.get() => view! { <Editor on_back=Callback::new(move |_| set_editing.set(false)) /> }.into_view(),
+                Some(s) if s.authenticated => view! {
+                    <Quiz on_auth_lost=Callback::new(move |_| recheck()) on_edit=Callback::new(move |_| set_editing.set(true)) />
+                }.into_view(),
+                Some(s) => view! { <Login password_set=s.password_set on_login=Callback::new(move |s| set_status.set(Some(s))) /> }.into_view(),
+            }}
         </main>
     }
 }
 
-fn normalize_input(input: &str) -> String {
-    let mut s = input.trim().to_lowercase().replace(" ", "");
-    let replacements = [
-        ("tsu", "tsu"), ("tu", "tsu"),
-        ("shi", "shi"), ("si", "shi"),
-        ("chi", "chi"), ("ti", "chi"),
-        ("fu", "fu"),   ("hu", "fu"),
-        ("ji", "ji"),   ("zi", "ji"), ("di", "ji"),
-        ("zu", "zu"),   ("du", "zu"),
-        ("sha", "sha"), ("sya", "sha"),
-        ("shu", "shu"), ("syu", "shu"),
-        ("sho", "sho"), ("syo", "sho"),
-        ("cha", "cha"), ("tya", "cha"),
-        ("chu", "chu"), ("tyu", "chu"),
-        ("cho", "cho"), ("tyo", "cho"),
-        ("ja", "ja"),   ("zya", "ja"), ("jya", "ja"),
-        ("ju", "ju"),   ("zyu", "ju"), ("jyu", "ju"),
-        ("jo", "jo"),   ("zyo", "jo"), ("jyo", "jo"),
-    ];
-    for (target, replacement) in replacements.iter() {
-        if target != replacement { s = s.replace(target, replacement); }
+#[component]
+fn Login(password_set: bool, on_login: Callback<AuthStatus>) -> impl IntoView {
+    let (password, set_password) = create_signal(String::new());
+    let (error_msg, set_error_msg) = create_signal(Option::<String>::None);
+    let (busy, set_busy) = create_signal(false);
+    let input_ref = create_node_ref::<Input>();
+
+    create_effect(move |_| {
+        if let Some(input) = input_ref.get() { let _ = input.focus(); }
+    });
+
+    let submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        if busy.get() { return; }
+        set_busy.set(true);
+        set_error_msg.set(None);
+
+        let body = LoginRequest { password: password.get() };
+        spawn_local(async move {
+            let resp = match Request::post("/api/v1/auth/login").json(&body) {
+                Ok(req) => req.send().await,
+                Err(e) => Err(e),
+            };
+            match resp {
+                Ok(resp) if resp.ok() => match resp.json::<AuthStatus>().await {
+                    Ok(s) => on_login.call(s),
+                    Err(e) => {
+                        error!("Failed to parse login response: {:?}", e);
+                        set_error_msg.set(Some("NetErr".to_string()));
+                    }
+                },
+                Ok(_) => {
+                    set_password.set(String::new());
+                    set_error_msg.set(Some("密码错误".to_string()));
+                }
+                Err(e) => {
+                    error!("Network error: {:?}", e);
+                    set_error_msg.set(Some("NetErr".to_string()));
+                }
+            }
+            set_busy.set(false);
+        });
+    };
+
+    view! {
+        <form on:submit=submit style="
+            width: 160px;
+            padding: 15px;
+            background: #ffffff;
+            border-radius: 12px;
+            box-shadow: 0 4px 15px rgba(0,0,0,0.05);
+            display: flex;
+            flex-direction: column;
+            align-items: center;
+            gap: 8px;
+            font-family: 'Segoe UI', sans-serif;
+            border: 1px solid #f0f0f0;
+        ">
+            {if password_set {
+                view! {
+                    <input type="password"
+                        placeholder="密码"
+                        prop:value=password
+                        prop:readonly=busy
+                        node_ref=input_ref
+                        on:input=move |ev| set_password.set(event_target_value(&ev))
+                        style="width: 100%; text-align: center; border: none; border-bottom: 1px solid #eee; outline: none; font-size: 14px; padding: 4px; color: #555; background: transparent;"
+                    />
+                }.into_view()
+            } else {
+                // 没设密码又不是本机访问，只能去服务器上设
+                view! {
+                    <div style="font-size: 12px; color: #999; text-align: center; line-height: 1.6;">
+                        "请先在服务器上运行"<br/><code>"kana-tutor passwd"</code>
+                    </div>
+                }.into_view()
+            }}
+            <div style="height: 16px; font-size: 12px; color: #e57373;">
+                {move || error_msg.get().unwrap_or_default()}
+            </div>
+        </form>
     }
-    s
 }
 
 #[component]
-fn Quiz() -> impl IntoView {
+fn Quiz(on_auth_lost: Callback<()>, on_edit: Callback<()>) -> impl IntoView {
     let (cards, set_cards) = create_signal(Vec::<Card>::new());
     let (current_index, set_current_index) = create_signal(0);
     let (user_input, set_user_input) = create_signal(String::new());
     let (feedback, set_feedback) = create_signal(Option::<(bool, String)>::None);
+    // 答错时的讲解和助记，翻到下一张就清掉
+    let (explanation, set_explanation) = create_signal(Option::<String>::None);
+    let (mnemonics, set_mnemonics) = create_signal(Vec::<MnemonicNote>::new());
+    // 要演示笔顺的假名：答错时自动打开，也可以在控制栏手动开关
+    let (strokes_for, set_strokes_for) = create_signal(Option::<String>::None);
+    // 手写模式：提示罗马音，在画布上写假名，由服务端比对笔顺判分
+    let (writing, set_writing) = create_signal(false);
+    // 这张卡没有笔顺数据时退回键入作答，换卡就恢复
+    let (typing_fallback, set_typing_fallback) = create_signal(false);
+    let drawn = create_rw_signal(Vec::<DrawnStroke>::new());
+    let sent_strokes = store_value(false);
+    // 听音 / 听写：题目只放发音，键入罗马音或假名作答（听不出平片假名，不能手写）
+    let (prompt, set_prompt) = create_signal(Prompt::Text);
+    // 这张卡合成不了发音时退回看假名，换卡就恢复
+    let (no_audio, set_no_audio) = create_signal(false);
+    let listening = Signal::derive(move || prompt.get() != Prompt::Text && !no_audio.get());
+    // 听写时逐拍的结果
+    let (segments, set_segments) = create_signal(Vec::<DictationSegment>::new());
+    // 翻出答案时自动念一遍
+    let (auto_play, set_auto_play) = create_signal(false);
+    let handwriting_active = Signal::derive(move || writing.get() && !typing_fallback.get() && !listening.get());
     let (loading, set_loading) = create_signal(true);
     let (error_msg, set_error_msg) = create_signal(Option::<String>::None);
+    let (summary, set_summary) = create_signal(Option::<SessionSummary>::None);
+    let (mode, set_mode) = create_signal(SessionMode::Open);
+    // 卡片出现的时间，用来算反应时间
+    let (shown_at, set_shown_at) = create_signal(0.0_f64);
+    let (last_response_ms, set_last_response_ms) = create_signal(0_u64);
+    // 已提交、还没收到 verdict
+    let (awaiting_verdict, set_awaiting_verdict) = create_signal(false);
+    let (upcoming, set_upcoming) = create_signal(Option::<Upcoming>::None);
+    // 当前这张卡的票据，提交时带上
+    let (ticket, set_ticket) = create_signal(String::new());
+    let (answered, set_answered) = create_signal(0_usize);
+    let (correct_count, set_correct_count) = create_signal(0_usize);
+    let sender = store_value(Option::<UnboundedSender<ClientMessage>>::None);
 
     let (font_size, set_font_size) = create_signal(2.0);
     let (card_width, set_card_width) = create_signal(160);
@@ -87,40 +210,207 @@ fn Quiz() -> impl IntoView {
     let is_submitted = create_memo(move |_| feedback.get().is_some());
     let input_ref = create_node_ref::<Input>();
 
-    let fetch_next_batch = move || {
+    let send = move |msg: ClientMessage| {
+        let sent = sender.with_value(|tx| tx.as_ref().map(|tx| tx.unbounded_send(msg).is_ok()));
+        if sent != Some(true) {
+            set_error_msg.set(Some("NetErr".to_string()));
+            set_loading.set(false);
+        }
+    };
+
+    // 放题目的发音；取不到时这张卡改成看假名。换了卡才报错的就不管了
+    let play_prompt = move |kana: String| {
+        let current = ticket.get_untracked();
+        audio::play(&kana, move || {
+            if ticket.get_untracked() == current {
+                set_no_audio.set(true);
+            }
+        });
+    };
+
+    let show = move |next: Upcoming| {
+        batch(move || {
+            match next {
+                Upcoming::Card(t, card) => {
+                    set_no_audio.set(false);
+                    set_ticket.set(t);
+                    if listening.get_untracked() {
+                        play_prompt(card.kana_char.clone());
+                    }
+                    set_cards.set(vec![card]);
+                    set_shown_at.set(js_sys::Date::now());
+                }
+                Upcoming::End(sum) => {
+                    set_cards.set(Vec::new());
+                    set_summary.set(Some(sum));
+                }
+            }
+            set_feedback.set(None);
+            set_explanation.set(None);
+            set_mnemonics.set(Vec::new());
+            set_strokes_for.set(None);
+            set_typing_fallback.set(false);
+            set_segments.set(Vec::new());
+            drawn.set(Vec::new());
+            set_error_msg.set(None);
+            set_user_input.set(String::new());
+            set_current_index.set(0);
+            set_loading.set(false);
+        });
+    };
+
+    let on_message = move |msg: ServerMessage| match msg {
+        ServerMessage::Card { ticket: t, card, .. } => {
+            // 重复提交时服务端会再发一次当前卡，票据没变就不用刷新
+            if t == ticket.get_untracked() && feedback.get_untracked().is_none() {
+                set_loading.set(false);
+            } else if feedback.get_untracked().is_some() {
+                set_upcoming.set(Some(Upcoming::Card(t, card)));
+            } else {
+                show(Upcoming::Card(t, card));
+            }
+        }
+        ServerMessage::SessionEnd { summary: sum } => {
+            if feedback.get_untracked().is_some() {
+                set_upcoming.set(Some(Upcoming::End(sum)));
+            } else {
+                show(Upcoming::End(sum));
+            }
+        }
+        ServerMessage::Verdict { correct, expected, grade, leech, explanation: detail, mnemonics: notes, segments: parts, .. } => {
+            set_awaiting_verdict.set(false);
+            set_segments.set(parts);
+            if auto_play.get_untracked() {
+                if let Some(card) = cards.get_untracked().first() {
+                    audio::play(&card.kana_char, || {});
+                }
+            }
+            if correct {
+                // 答对但太慢（Hard）时显示用时
+                let note = if grade == Grade::Hard { format!("{:.1}s", last_response_ms.get_untracked() as f64 / 1000.0) } else { String::new() };
+                set_feedback.set(Some((true, note)));
+            } else {
+                // 刚达到 leech 阈值时顺带提示一下，具体怎么处理看服务端配置
+                // 手写时要看的是假名本身
+                let expected = if handwriting_active.get_untracked() {
+                    cards.get_untracked().first().map(|c| c.kana_char.clone()).unwrap_or(expected)
+                } else {
+                    expected
+                };
+                let expected = if leech { format!("{} · leech", expected) } else { expected };
+                batch(move || {
+                    set_feedback.set(Some((false, expected)));
+                    set_explanation.set(detail);
+                    set_mnemonics.set(notes);
+                    set_strokes_for.set(cards.get_untracked().first().map(|c| c.kana_char.clone()));
+                });
+            }
+        }
+        ServerMessage::Stats { answered: a, correct: c, .. } => {
+            set_answered.update(|n| *n += a);
+            set_correct_count.update(|n| *n += c);
+        }
+        ServerMessage::Error { code, message } => {
+            error!("Server error ({}): {}", code, message);
+            set_awaiting_verdict.set(false);
+            // 票据过期不算错误，重新要当前卡就行
+            if code == "stale_session" {
+                set_feedback.set(None);
+                set_loading.set(true);
+                send(ClientMessage::Next);
+                return;
+            }
+            // 手写提交被拒：这张卡没有笔顺数据，改成键入作答
+            if code == "bad_request" && sent_strokes.get_value() {
+                sent_strokes.set_value(false);
+                set_typing_fallback.set(true);
+                return;
+            }
+            // 卡片已被删除时服务端会紧接着发下一张，这里只提示
+            batch(move || {
+                set_error_msg.set(Some(format!("Error: {}", code)));
+                set_loading.set(false);
+            });
+        }
+    };
+
+    let connect = move || {
         set_loading.set(true);
         set_error_msg.set(None);
 
+        let ws = match WebSocket::open(&ws_url()) {
+            Ok(ws) => ws,
+            Err(e) => {
+                error!("Network error: {:?}", e);
+                set_error_msg.set(Some("NetErr".to_string()));
+                set_loading.set(false);
+                return;
+            }
+        };
+        let (mut write, mut read) = ws.split();
+        let (tx, mut rx) = mpsc::unbounded::<ClientMessage>();
+        let _ = tx.unbounded_send(ClientMessage::Next);
+        sender.set_value(Some(tx));
+
         spawn_local(async move {
-            let url = format!("/api/next_batch?t={}", js_sys::Date::now());
-            let resp_res = Request::get(&url).send().await;
-
-            match resp_res {
-                Ok(resp) => {
-                    if let Ok(batch_data) = resp.json::<BatchResponse>().await {
-                        batch(move || {
-                            set_cards.set(batch_data.cards);
-                            set_feedback.set(None);
-                            set_user_input.set(String::new());
-                            set_current_index.set(0);
-                            set_loading.set(false);
-                        });
-                    } else {
-                        error!("Failed to parse batch response");
-                        set_error_msg.set(Some("Error".to_string()));
-                        set_loading.set(false);
+            while let Some(msg) = rx.next().await {
+                let Ok(text) = serde_json::to_string(&msg) else { continue };
+                if write.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        spawn_local(async move {
+            while let Some(msg) = read.next().await {
+                match msg {
+                    Ok(Message::Text(text)) => match serde_json::from_str::<ServerMessage>(&text) {
+                        Ok(msg) => on_message(msg),
+                        Err(e) => error!("Failed to parse server message: {:?}", e),
+                    },
+                    Ok(Message::Bytes(_)) => {}
+                    Err(e) => {
+                        error!("Network error: {:?}", e);
+                        break;
                     }
-                },
-                Err(e) => {
-                    error!("Network error: {:?}", e);
-                    set_error_msg.set(Some("NetErr".to_string()));
-                    set_loading.set(false);
                 }
             }
+            sender.set_value(None);
+            set_awaiting_verdict.set(false);
+            // 登录过期时 WebSocket 握手会被拒，回到登录框
+            if fetch_auth_status().await.is_some_and(|s| !s.authenticated) {
+                on_auth_lost.call(());
+                return;
+            }
+            set_error_msg.set(Some("NetErr".to_string()));
+            set_loading.set(false);
         });
     };
 
-    create_effect(move |_| { fetch_next_batch(); });
+    create_effect(move |_| { connect(); });
+
+    // 断线就重连，否则只是重新要当前这张卡
+    let retry = move || {
+        if sender.with_value(|tx| tx.is_none()) {
+            connect();
+        } else {
+            set_error_msg.set(None);
+            set_loading.set(true);
+            send(ClientMessage::Next);
+        }
+    };
+
+    // 切换模式或“再来一轮”都重新开一轮会话
+    let start_session = move |next: SessionMode| {
+        batch(|| {
+            set_summary.set(None);
+            set_upcoming.set(None);
+            set_feedback.set(None);
+            set_mode.set(next.clone());
+            set_loading.set(true);
+        });
+        send(ClientMessage::StartSession { mode: next });
+    };
 
     create_effect(move |_| {
         let _ = loading.get();
@@ -141,39 +431,57 @@ fn Quiz() -> impl IntoView {
         }
     });
 
+    let logout = move || {
+        spawn_local(async move {
+            if let Err(e) = Request::post("/api/v1/auth/logout").send().await {
+                error!("Network error: {:?}", e);
+            }
+            on_auth_lost.call(());
+        });
+    };
+
     let submit_answer = move || {
         let current_cards = cards.get();
-        if current_cards.is_empty() { return; }
-
-        let card = &current_cards[0];
-        let input_val = user_input.get();
-
-        let normalized_user_input = normalize_input(&input_val);
-        let normalized_correct_romaji = normalize_input(&card.romaji);
-
-        let is_correct = normalized_user_input == normalized_correct_romaji;
-        let card_id = card.id.clone();
-        let romaji = card.romaji.clone();
+        if current_cards.is_empty() || awaiting_verdict.get() { return; }
+
+        let response_ms = (js_sys::Date::now() - shown_at.get_untracked()).max(0.0) as u64;
+
+        if handwriting_active.get_untracked() {
+            let strokes = drawn.get_untracked();
+            if strokes.is_empty() { return; }
+            set_last_response_ms.set(response_ms);
+            set_awaiting_verdict.set(true);
+            sent_strokes.set_value(true);
+            send(ClientMessage::SubmitStrokes { ticket: ticket.get(), strokes, response_ms: Some(response_ms) });
+            return;
+        }
 
-        spawn_local(async move {
-            let _ = Request::post("/api/submit")
-                .json(&SubmitRequest { card_id, correct: is_correct })
-                .unwrap().send().await;
+        set_last_response_ms.set(response_ms);
+        set_awaiting_verdict.set(true);
+        sent_strokes.set_value(false);
+        let asked = if listening.get_untracked() { prompt.get_untracked() } else { Prompt::Text };
+        send(ClientMessage::Submit { ticket: ticket.get(), answer: user_input.get(), response_ms: Some(response_ms), prompt: asked });
+    };
 
-            if is_correct {
-                set_feedback.set(Some((true, "".to_string())));
-            } else {
-                set_feedback.set(Some((false, format!("{}", romaji))));
+    let next_card = move || {
+        match upcoming.get() {
+            Some(next) => {
+                set_upcoming.set(None);
+                show(next);
             }
-        });
+            // 下一张还在路上，到了会直接显示
+            None => {
+                set_feedback.set(None);
+                set_loading.set(true);
+            }
+        }
     };
 
-    let next_card = move || { fetch_next_batch(); };
-
     let handle_global_enter = window_event_listener(ev::keydown, move |ev| {
         if ev.key() == "Enter" {
             ev.prevent_default();
-            if error_msg.get().is_some() { fetch_next_batch(); return; }
+            if error_msg.get().is_some() { retry(); return; }
+            if summary.get().is_some() { start_session(mode.get()); return; }
             if loading.get() { return; }
             if is_submitted.get() { next_card(); } else { submit_answer(); }
         }
@@ -205,7 +513,21 @@ fn Quiz() -> impl IntoView {
 
             // Error
             {move || if let Some(msg) = error_msg.get() {
-                view! { <div style="color: red; font-size: 12px; cursor: pointer;" on:click=move |_| fetch_next_batch()>{msg} " ↻"</div> }.into_view()
+                view! { <div style="color: red; font-size: 12px; cursor: pointer;" on:click=move |_| retry()>{msg} " ↻"</div> }.into_view()
+            } else {
+                view! { <span style="display: none"></span> }.into_view()
+            }}
+
+            // Summary
+            {move || if let Some(sum) = summary.get() {
+                view! {
+                    <div style="font-size: 12px; color: #555; text-align: center; line-height: 1.6; cursor: pointer;" on:click=move |_| start_session(mode.get())>
+                        <div style="font-weight: bold; color: #4caf50;">"完成"</div>
+                        <div>{format!("{} 张 · {:.0}%", sum.answered, sum.accuracy * 100.0)}</div>
+                        <div>{format!("{:.1} 张/分 · {:.0} 秒", sum.cards_per_minute, sum.elapsed_secs)}</div>
+                        <div style="color: #ccc;">"↻"</div>
+                    </div>
+                }.into_view()
             } else {
                 view! { <span style="display: none"></span> }.into_view()
             }}
@@ -216,7 +538,7 @@ fn Quiz() -> impl IntoView {
                 if current_cards.is_empty() {
                      view! { <div style="height: 40px;"></div> }.into_view()
                 } else {
-                    let card = current_cards.get(0).cloned().unwrap_or_else(|| current_cards[0].clone());
+                    let card = current_cards[0].clone();
                     let is_sub = is_submitted.get();
                     let is_readonly = is_sub || loading.get() || error_msg.get().is_some();
 
@@ -239,10 +561,28 @@ fn Quiz() -> impl IntoView {
                                 margin-bottom: 5px;
                                 transition: color 0.2s;
                             ", font_size.get(), kana_color)>
-                                {card.kana_char}
+                                // 手写时先只给罗马音，听音时只给喇叭，交了以后再显示假名
+                                {if listening.get() && !is_sub {
+                                    let kana = card.kana_char.clone();
+                                    view! { <span title="再听一遍" style="cursor: pointer;" on:click=move |_| audio::play(&kana, || {})>"🔊"</span> }.into_view()
+                                } else if handwriting_active.get() && !is_sub {
+                                    card.romaji.clone().into_view()
+                                } else {
+                                    card.kana_char.clone().into_view()
+                                }}
                             </div>
 
+                            {handwriting_active.get().then(|| view! {
+                                <DrawPad cells=card.kana_char.chars().count() strokes=drawn disabled=is_submitted />
+                                <div style="display: flex; gap: 12px; font-size: 11px; color: #bbb; margin-top: 4px;">
+                                    <span style="cursor: pointer;" on:click=move |_| drawn.update(|s| { s.pop(); })>"撤销"</span>
+                                    <span style="cursor: pointer;" on:click=move |_| drawn.set(Vec::new())>"清除"</span>
+                                    <span style="cursor: pointer;" on:click=move |_| submit_answer()>"提交 ⏎"</span>
+                                </div>
+                            })}
+
                             <input type="text"
+                                prop:hidden=handwriting_active.get()
                                 prop:value=user_input
                                 prop:readonly=is_readonly
                                 node_ref=input_ref
@@ -265,12 +605,57 @@ fn Quiz() -> impl IntoView {
                                     Some((false, ans)) => view! {
                                         <span style="color: #e57373;">{"❌ "}{ans}</span>
                                     }.into_view(),
+                                    Some((true, note)) if !note.is_empty() => view! {
+                                        <span style="color: #ffb74d;">{"✓ "}{note}</span>
+                                    }.into_view(),
                                     Some((true, _)) => view! {
                                         <span style="color: #4caf50;">"✓"</span>
                                     }.into_view(),
                                     _ => view! { <span></span> }.into_view()
                                 }}
                             </div>
+
+                            // 听写答错时逐拍标出来，写错的那拍下面是自己写的
+                            {move || (matches!(feedback.get(), Some((false, _))) && !segments.get().is_empty()).then(|| view! {
+                                <div style="display: flex; flex-wrap: wrap; gap: 6px; justify-content: center; margin-top: 4px;">
+                                    {segments.get().into_iter().map(|seg| view! {
+                                        <div style="display: flex; flex-direction: column; align-items: center; font-size: 11px;">
+                                            <span style=format!(
+                                                "font-family: 'Meiryo', 'Hiragino Kaku Gothic ProN', 'Yu Gothic', sans-serif; font-size: 16px; color: {};",
+                                                if seg.correct { "#4caf50" } else { "#e57373" }
+                                            )>{seg.kana}</span>
+                                            <span style="color: #bbb;">{seg.expected}</span>
+                                            {(!seg.correct).then(|| view! {
+                                                <span style="color: #e57373; text-decoration: line-through;">
+                                                    {if seg.answer.is_empty() { "—".to_string() } else { seg.answer }}
+                                                </span>
+                                            })}
+                                        </div>
+                                    }).collect_view()}
+                                </div>
+                            })}
+
+                            // 答错时：讲解 + 组成这张卡的每个假名的助记
+                            {move || matches!(feedback.get(), Some((false, _))).then(|| view! {
+                                <div style="width: 100%; margin-top: 6px; font-size: 11px; color: #999; text-align: left; display: flex; flex-direction: column; gap: 4px;">
+                                    {explanation.get().map(|text| view! { <div>{text}</div> })}
+                                    {mnemonics.get().into_iter().map(|note| view! {
+                                        <div style="color: #777;">
+                                            {note.text.map(|text| view! {
+                                                <div>
+                                                    <span style="font-family: 'Meiryo', 'Hiragino Kaku Gothic ProN', 'Yu Gothic', sans-serif; margin-right: 4px;">{note.kana_char.clone()}</span>
+                                                    {text}
+                                                </div>
+                                            })}
+                                            {note.image_url.map(|url| view! {
+                                                <img src=url style="max-width: 100%; border-radius: 4px; margin-top: 2px;" />
+                                            })}
+                                        </div>
+                                    }).collect_view()}
+                                </div>
+                            })}
+
+                            {move || strokes_for.get().map(|kana| view! { <StrokeOrder kana=kana /> })}
                         </div>
                     }.into_view()
                 }
@@ -291,6 +676,62 @@ fn Quiz() -> impl IntoView {
             on:mouseenter=|el| { let _ = el.target().expect("el").unchecked_into::<web_sys::HtmlElement>().style().set_property("opacity", "1"); }
             on:mouseleave=|el| { let _ = el.target().expect("el").unchecked_into::<web_sys::HtmlElement>().style().set_property("opacity", "0.1"); }
             >
+                <div style="display: flex; align-items: center; justify-content: space-between; font-size: 10px; color: #ccc;">
+                    <span>"✓"</span>
+                    <span>{move || format!("{}/{}", correct_count.get(), answered.get())}</span>
+                </div>
+                <div style="display: flex; align-items: center; justify-content: space-between; font-size: 10px; color: #ccc;">
+                    <span>"题目"</span>
+                    <select
+                        on:change=move |ev| {
+                            let next = match event_target_value(&ev).as_str() {
+                                "listen" => Prompt::Audio,
+                                "dictate" => Prompt::Dictation,
+                                _ => Prompt::Text,
+                            };
+                            let was_listening = listening.get_untracked();
+                            set_prompt.set(next);
+                            // 切过去时当前这张还没答，先念一遍
+                            if !was_listening && listening.get_untracked() && feedback.get_untracked().is_none() {
+                                if let Some(card) = cards.get_untracked().first() {
+                                    play_prompt(card.kana_char.clone());
+                                }
+                            }
+                        }
+                        style="width: 70%; font-size: 10px; color: #999; border: none; background: transparent; cursor: pointer;"
+                    >
+                        <option value="read">"看假名"</option>
+                        <option value="listen">"听发音"</option>
+                        <option value="dictate">"听写（按拍判分）"</option>
+                    </select>
+                </div>
+                <div style="display: flex; align-items: center; justify-content: space-between; font-size: 10px; color: #ccc;">
+                    <span>"作答"</span>
+                    <select
+                        on:change=move |ev| {
+                            set_writing.set(event_target_value(&ev) == "write");
+                            drawn.set(Vec::new());
+                        }
+                        style="width: 70%; font-size: 10px; color: #999; border: none; background: transparent; cursor: pointer;"
+                    >
+                        <option value="type">"键入罗马音"</option>
+                        <option value="write">"手写假名"</option>
+                    </select>
+                </div>
+                <div style="display: flex; align-items: center; justify-content: space-between; font-size: 10px; color: #ccc;">
+                    <span>"模式"</span>
+                    <select
+                        on:change=move |ev| start_session(parse_mode(&event_target_value(&ev)))
+                        style="width: 70%; font-size: 10px; color: #999; border: none; background: transparent; cursor: pointer;"
+                    >
+                        <option value="open">"自由"</option>
+                        <option value="sprint60">"冲刺 60 秒"</option>
+                        <option value="sprint120">"冲刺 120 秒"</option>
+                        <option value="sprint300">"冲刺 300 秒"</option>
+                        <option value="count50">"50 张"</option>
+                        <option value="due">"到期卡"</option>
+                    </select>
+                </div>
                 <div style="display: flex; align-items: center; justify-content: space-between; font-size: 10px; color: #ccc;">
                     <span>"字"</span>
                     <input type="range" min="1.0" max="4.0" step="0.1"
@@ -307,6 +748,19 @@ fn Quiz() -> impl IntoView {
                         style="width: 70%; cursor: pointer;"
                     />
                 </div>
+                <div style="display: flex; justify-content: space-between; font-size: 10px; color: #ccc;">
+                    <span style="cursor: pointer;" on:click=move |_| {
+                        let current = cards.get_untracked().first().map(|c| c.kana_char.clone());
+                        set_strokes_for.update(|shown| *shown = if shown.is_some() { None } else { current });
+                    }>"笔顺"</span>
+                    <span
+                        title="翻出答案时自动朗读"
+                        style=move || format!("cursor: pointer; color: {};", if auto_play.get() { "#999" } else { "#ccc" })
+                        on:click=move |_| set_auto_play.update(|on| *on = !*on)
+                    >"朗读"</span>
+                    <span style="cursor: pointer;" on:click=move |_| on_edit.call(())>"编辑"</span>
+                    <span style="cursor: pointer;" on:click=move |_| logout()>"退出"</span>
+                </div>
             </div>
         </div>
     }
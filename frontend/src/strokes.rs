@@ -0,0 +1,72 @@
+use gloo_net::http::Request;
+use kana_types::StrokeData;
+use leptos::*;
+
+use crate::editor::{call, encode};
+
+// 每一笔画多久，下一笔紧接着开始
+const STROKE_SECONDS: f64 = 0.5;
+
+// 起笔坐标，用来标笔画序号；路径都以 "Mx,y" 开头
+fn start_point(path: &str) -> Option<(f32, f32)> {
+    let rest = path.trim_start().strip_prefix('M')?;
+    let end = rest.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(rest.len());
+    let mut numbers = rest[..end].split([',', ' ']).filter(|n| !n.is_empty()).map(|n| n.parse::<f32>().ok());
+    Some((numbers.next()??, numbers.next()??))
+}
+
+fn animated(data: StrokeData) -> impl IntoView {
+    let mut delay = 0.0;
+    data.glyphs.into_iter().map(|glyph| {
+        let guides = glyph.strokes.clone();
+        let strokes = glyph.strokes.into_iter().enumerate().map(|(i, d)| {
+            let (x, y) = start_point(&d).unwrap_or((0.0, 0.0));
+            let style = format!(
+                "stroke-dasharray: 1; stroke-dashoffset: 1; animation: kana-stroke {}s linear {}s forwards;",
+                STROKE_SECONDS, delay
+            );
+            delay += STROKE_SECONDS;
+            view! {
+                <path d=d pathLength="1" style=style fill="none" stroke="#555" stroke-width="4" stroke-linecap="round" stroke-linejoin="round" />
+                <text x=x - 6.0 y=y - 3.0 font-size="9" fill="#e57373">{i + 1}</text>
+            }
+        }).collect_view();
+        view! {
+            <svg viewBox=data.view_box.clone() width="64" height="64" style="border: 1px dashed #f0f0f0; border-radius: 4px;">
+                {guides.into_iter().map(|d| view! {
+                    <path d=d fill="none" stroke="#eee" stroke-width="4" stroke-linecap="round" stroke-linejoin="round" />
+                }).collect_view()}
+                {strokes}
+            </svg>
+        }
+    }).collect_view()
+}
+
+// 按书写顺序一笔一笔画出来，点一下重放
+#[component]
+pub fn StrokeOrder(kana: String) -> impl IntoView {
+    let data = create_local_resource(|| (), move |_| {
+        let url = format!("/api/v1/strokes/{}", encode(&kana));
+        async move { call::<StrokeData>(Request::get(&url).build()).await }
+    });
+    let (replay, set_replay) = create_signal(0_u32);
+
+    view! {
+        <style>"@keyframes kana-stroke { to { stroke-dashoffset: 0; } }"</style>
+        <div
+            title="点击重放"
+            on:click=move |_| set_replay.update(|n| *n += 1)
+            style="display: flex; flex-wrap: wrap; gap: 4px; justify-content: center; cursor: pointer; margin-top: 6px;"
+        >
+            {move || {
+                // 重新生成节点，动画就会从头播
+                replay.track();
+                match data.get() {
+                    None => view! { <span style="font-size: 11px; color: #ccc;">"…"</span> }.into_view(),
+                    Some(Err(e)) => view! { <span style="font-size: 11px; color: #ccc;">{e}</span> }.into_view(),
+                    Some(Ok(data)) => animated(data).into_view(),
+                }
+            }}
+        </div>
+    }
+}
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Card {
+    pub id: String,
+    pub kana_char: String,
+    pub romaji: String,
+    pub stability: f64,
+    pub difficulty: f64,
+    pub last_review: Option<String>,
+    pub meaning: Option<String>,
+    // 空格分隔，和 Anki 的 tags 字段保持一致
+    pub tags: String,
+    // 停用的卡不会被抽到，复习记录保留
+    #[serde(default)]
+    pub suspended: bool,
+    // 复习过的卡答错（遗忘）的次数，leech 判定用
+    #[serde(default)]
+    pub lapses: i64,
+    // 埋到这个时间（UTC）之前不会被抽到
+    #[serde(default)]
+    pub buried_until: Option<String>,
+    // 助记文字；内置假名默认就有一份
+    #[serde(default)]
+    pub mnemonic: Option<String>,
+    // 上传的助记图片在媒体目录里的文件名，通过 /api/v1/cards/{id}/mnemonic/image 取
+    #[serde(default)]
+    pub mnemonic_image: Option<String>,
+}
+
+// 答错后给看的助记，缝合卡里每个有助记的假名各一条
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MnemonicNote {
+    pub card_id: String,
+    pub kana_char: String,
+    pub text: Option<String>,
+    pub image_url: Option<String>,
+}
+
+// 答对但太慢记为 Hard：stability 涨得少，difficulty 不变
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Grade {
+    Again,
+    Hard,
+    Good,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BatchResponse {
+    pub batch_current: usize,
+    pub batch_total: usize,
+    pub remaining_in_deck: usize,
+    pub is_review: bool,
+    pub cycle_mistakes_count: usize,
+    pub cards: Vec<Card>,
+    // 【新增】后端告诉前端：现在到底该做第几张了！
+    pub current_card_index: usize,
+    // 会话目标已达成，cards 为空，去 /api/v1/session/summary 取总结
+    pub session_finished: bool,
+    // 当前这张卡的票据，提交时带上；会话结束时为空
+    pub ticket: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SubmitRequest {
+    // next_batch 返回的票据
+    pub ticket: String,
+    pub correct: bool,
+    // 从出卡到提交的毫秒数，老客户端不传
+    #[serde(default)]
+    pub response_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SubmitResponse {
+    // 几天后到期；缝合卡取各假名里最早到期的
+    pub new_interval: i64,
+    pub grade: Grade,
+    // true 表示这张票之前已经交过，本次没有计分
+    pub duplicate: bool,
+    // 这次答错让它成了 leech（开启了 scheduler.leech_action 时）
+    #[serde(default)]
+    pub leech: bool,
+    // 答错时附带的助记，答对时为空
+    #[serde(default)]
+    pub mnemonics: Vec<MnemonicNote>,
+}
+
+// 所有接口出错时的响应体，code 见 README 的错误码列表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ErrorBody {
+    pub code: String,
+    pub message: String,
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LoginRequest {
+    pub password: String,
+}
+
+// 这次请求是怎么通过认证的
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AuthMethod {
+    // 浏览器登录后的会话 cookie
+    Session,
+    // Authorization: Bearer <API token>
+    Token,
+    // 还没设密码，来自本机的请求直接放行
+    Local,
+    // 配置里关掉了认证
+    Disabled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AuthStatus {
+    pub authenticated: bool,
+    // false 时只能在服务器上用 `kana-tutor passwd` 设置密码
+    pub password_set: bool,
+    pub method: Option<AuthMethod>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateTokenRequest {
+    // 随便起个名字，方便以后认出来是哪个脚本在用
+    pub name: String,
+}
+
+// 列表里不含 token 本身，数据库里只存了哈希
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ApiTokenInfo {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+    pub last_used: Option<String>,
+}
+
+// 只在创建时返回一次
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NewApiToken {
+    pub id: String,
+    pub name: String,
+    pub token: String,
+}
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+// 新建卡片；调度状态从零开始
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CardRequest {
+    pub kana_char: String,
+    pub romaji: String,
+    #[serde(default)]
+    pub meaning: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // 不传时内置假名用默认助记
+    #[serde(default)]
+    pub mnemonic: Option<String>,
+}
+
+// 只改传了的字段；meaning、mnemonic 传空字符串表示清空
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CardUpdate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kana_char: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub romaji: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meaning: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suspended: Option<bool>,
+    // 空字符串表示清空；图片走单独的上传接口
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mnemonic: Option<String>,
+}
+
+// GET /api/v1/cards 的筛选条件，都不填就是全部卡片
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+pub struct CardQuery {
+    // 只列这个卡组里的卡
+    pub deck: Option<String>,
+    pub tag: Option<String>,
+    // 按假名、罗马音或释义模糊搜索
+    pub q: Option<String>,
+    pub suspended: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Deck {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+    pub card_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DeckRequest {
+    pub name: String,
+    // 新建时可以顺便放进去的卡
+    #[serde(default)]
+    pub card_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DeckCards {
+    pub card_ids: Vec<String>,
+}
+
+// 当前会话只练哪个卡组，None 为全部卡片
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SessionDeck {
+    pub deck_id: Option<String>,
+}
@@ -0,0 +1,16 @@
+// 前后端之间传的所有类型都放这里，字段对不上会直接编译失败，而不是运行时才发现
+pub mod api;
+pub mod auth;
+pub mod cards;
+pub mod romaji;
+pub mod session;
+pub mod strokes;
+pub mod ws;
+
+pub use api::{BatchResponse, Card, ErrorBody, Grade, MnemonicNote, SubmitRequest, SubmitResponse};
+pub use auth::{ApiTokenInfo, AuthMethod, AuthStatus, CreateTokenRequest, LoginRequest, NewApiToken};
+pub use cards::{CardQuery, CardRequest, CardUpdate, Deck, DeckCards, DeckRequest, SessionDeck};
+pub use romaji::{romaji_to_kana, same_romaji, to_hiragana};
+pub use session::{SessionMode, SessionSummary};
+pub use strokes::{DrawnStroke, GlyphStrokes, HandwritingCheck, HandwritingScore, StrokeData, StrokeProblem, STROKE_VIEW_BOX};
+pub use ws::{ClientMessage, DictationSegment, Prompt, ServerMessage};
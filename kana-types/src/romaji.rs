@@ -0,0 +1,109 @@
+// 罗马音按最长匹配切成假名再比，平文式、训令式、日本式和输入法的写法（jya、cya 之类）都认。
+// 服务端判分用它，前端要做本地提示时也用同一份
+const SYLLABLES: &[(&str, &str)] = &[
+    ("a", "あ"), ("i", "い"), ("u", "う"), ("e", "え"), ("o", "お"),
+    ("ka", "か"), ("ki", "き"), ("ku", "く"), ("ke", "け"), ("ko", "こ"),
+    ("kya", "きゃ"), ("kyu", "きゅ"), ("kyo", "きょ"),
+    ("ga", "が"), ("gi", "ぎ"), ("gu", "ぐ"), ("ge", "げ"), ("go", "ご"),
+    ("gya", "ぎゃ"), ("gyu", "ぎゅ"), ("gyo", "ぎょ"),
+    ("sa", "さ"), ("shi", "し"), ("si", "し"), ("su", "す"), ("se", "せ"), ("so", "そ"),
+    ("sha", "しゃ"), ("shu", "しゅ"), ("sho", "しょ"),
+    ("sya", "しゃ"), ("syu", "しゅ"), ("syo", "しょ"),
+    ("za", "ざ"), ("ji", "じ"), ("zi", "じ"), ("zu", "ず"), ("ze", "ぜ"), ("zo", "ぞ"),
+    ("ja", "じゃ"), ("ju", "じゅ"), ("jo", "じょ"),
+    ("zya", "じゃ"), ("zyu", "じゅ"), ("zyo", "じょ"),
+    ("jya", "じゃ"), ("jyu", "じゅ"), ("jyo", "じょ"),
+    ("ta", "た"), ("chi", "ち"), ("ti", "ち"), ("tsu", "つ"), ("tu", "つ"), ("te", "て"), ("to", "と"),
+    ("cha", "ちゃ"), ("chu", "ちゅ"), ("cho", "ちょ"),
+    ("tya", "ちゃ"), ("tyu", "ちゅ"), ("tyo", "ちょ"),
+    ("cya", "ちゃ"), ("cyu", "ちゅ"), ("cyo", "ちょ"),
+    ("da", "だ"), ("di", "ぢ"), ("du", "づ"), ("de", "で"), ("do", "ど"),
+    ("dya", "ぢゃ"), ("dyu", "ぢゅ"), ("dyo", "ぢょ"),
+    ("na", "な"), ("ni", "に"), ("nu", "ぬ"), ("ne", "ね"), ("no", "の"),
+    ("nya", "にゃ"), ("nyu", "にゅ"), ("nyo", "にょ"),
+    ("ha", "は"), ("hi", "ひ"), ("fu", "ふ"), ("hu", "ふ"), ("he", "へ"), ("ho", "ほ"),
+    ("hya", "ひゃ"), ("hyu", "ひゅ"), ("hyo", "ひょ"),
+    ("ba", "ば"), ("bi", "び"), ("bu", "ぶ"), ("be", "べ"), ("bo", "ぼ"),
+    ("bya", "びゃ"), ("byu", "びゅ"), ("byo", "びょ"),
+    ("pa", "ぱ"), ("pi", "ぴ"), ("pu", "ぷ"), ("pe", "ぺ"), ("po", "ぽ"),
+    ("pya", "ぴゃ"), ("pyu", "ぴゅ"), ("pyo", "ぴょ"),
+    ("ma", "ま"), ("mi", "み"), ("mu", "む"), ("me", "め"), ("mo", "も"),
+    ("mya", "みゃ"), ("myu", "みゅ"), ("myo", "みょ"),
+    ("ya", "や"), ("yu", "ゆ"), ("yo", "よ"),
+    ("ra", "ら"), ("ri", "り"), ("ru", "る"), ("re", "れ"), ("ro", "ろ"),
+    ("rya", "りゃ"), ("ryu", "りゅ"), ("ryo", "りょ"),
+    ("wa", "わ"), ("wo", "を"),
+];
+
+// 一段最长三个字母（kya、tsu、jyo）
+const LONGEST: usize = 3;
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'i' | 'u' | 'e' | 'o')
+}
+
+// 罗马音 → 平假名。大小写、空格不计；切不下去（有不认识的字母组合）时返回 None。
+// ん：n' 和 n 后面不是元音 / y 时都是 ん，nn 后面不是元音 / y 时算一个 ん（onna 是 おんな），
+// 平文式旧写法 b / m / p 前的 m 也算（shimbun）；っ：双写辅音（kk、cchi）和 tch（matcha）
+pub fn romaji_to_kana(input: &str) -> Option<String> {
+    let chars: Vec<char> = input.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).collect();
+    let at = |i: usize| chars.get(i).copied();
+    let mut kana = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = at(i + 1);
+        let starts_syllable = |c: Option<char>| c.is_some_and(|c| is_vowel(c) || c == 'y');
+
+        if c == 'n' && next == Some('\'') {
+            kana.push('ん');
+            i += 2;
+            continue;
+        }
+        if c == 'n' && !starts_syllable(next) {
+            kana.push('ん');
+            i += if next == Some('n') && !starts_syllable(at(i + 2)) && at(i + 2) != Some('\'') { 2 } else { 1 };
+            continue;
+        }
+        if c == 'm' && matches!(next, Some('b' | 'm' | 'p')) {
+            kana.push('ん');
+            i += 1;
+            continue;
+        }
+        if c.is_ascii_alphabetic() && !is_vowel(c) && (next == Some(c) || (c == 't' && next == Some('c'))) {
+            kana.push('っ');
+            i += 1;
+            continue;
+        }
+
+        let (len, syllable) = (1..=LONGEST.min(chars.len() - i)).rev().find_map(|len| {
+            let piece: String = chars[i..i + len].iter().collect();
+            SYLLABLES.iter().find(|(romaji, _)| *romaji == piece).map(|(_, kana)| (len, *kana))
+        })?;
+        kana.push_str(syllable);
+        i += len;
+    }
+    Some(kana)
+}
+
+// 片假名换成对应的平假名，其他字原样保留
+pub fn to_hiragana(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'ァ'..='ヶ' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+// 两种罗马音写法是不是同一串假名；有一边切不成假名（自己加的卡可能有特别的写法）时按原样比
+pub fn same_romaji(a: &str, b: &str) -> bool {
+    match (romaji_to_kana(a), romaji_to_kana(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => {
+            let plain = |s: &str| s.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).collect::<String>();
+            plain(a) == plain(b)
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+pub const SPRINT_SECONDS: RangeInclusive<u64> = 10..=3600;
+pub const COUNT_CARDS: RangeInclusive<usize> = 1..=1000;
+
+// 会话目标：什么时候算练完。通过 PUT /api/v1/session 开始一轮新的会话
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SessionMode {
+    // 不限时也不限量（原来的行为）
+    #[default]
+    Open,
+    // 限时冲刺，例如 60/120/300 秒
+    Sprint { seconds: u64 },
+    // 做满固定张数
+    Count { cards: usize },
+    // 把开始时到期的卡全部答对为止
+    Due,
+}
+
+impl SessionMode {
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            Self::Sprint { seconds } if !SPRINT_SECONDS.contains(&seconds) => Err(format!(
+                "seconds must be between {} and {} (got {})",
+                SPRINT_SECONDS.start(),
+                SPRINT_SECONDS.end(),
+                seconds
+            )),
+            Self::Count { cards } if !COUNT_CARDS.contains(&cards) => Err(format!(
+                "cards must be between {} and {} (got {})",
+                COUNT_CARDS.start(),
+                COUNT_CARDS.end(),
+                cards
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SessionSummary {
+    pub mode: SessionMode,
+    pub finished: bool,
+    pub elapsed_secs: f64,
+    // 冲刺模式剩余秒数
+    pub remaining_secs: Option<f64>,
+    pub answered: usize,
+    pub correct: usize,
+    pub accuracy: f64,
+    pub cards_per_minute: f64,
+    // Due 模式：开始时到期的卡数和还没答对的卡数
+    pub due_total: Option<usize>,
+    pub due_remaining: Option<usize>,
+}
@@ -0,0 +1,69 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+// 笔顺数据的坐标系，和 KanjiVG 一样是 109 × 109
+pub const STROKE_VIEW_BOX: &str = "0 0 109 109";
+
+// 一个字形（一格）的笔画，按书写顺序排列；每笔是一条 SVG path，从起笔画到收笔
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct GlyphStrokes {
+    pub kana: String,
+    pub strokes: Vec<String>,
+}
+
+// 拗音和多字卡片按字拆成几格，小字 ゃ ゅ ょ 已经缩小放在自己那格的左下
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct StrokeData {
+    pub kana_char: String,
+    pub view_box: String,
+    pub glyphs: Vec<GlyphStrokes>,
+}
+
+// 手写时画的一笔：按时间顺序的点，坐标系和笔顺数据一样（格子高 109，多个字横着排开）
+pub type DrawnStroke = Vec<[f32; 2]>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StrokeProblem {
+    // 笔画数不对时不再逐笔比较
+    Count { expected: usize, drawn: usize },
+    // 写的第 stroke 笔其实是第 expected 笔（都从 1 开始数）
+    Order { stroke: usize, expected: usize },
+    Direction { stroke: usize },
+    Shape { stroke: usize },
+}
+
+impl fmt::Display for StrokeProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StrokeProblem::Count { expected, drawn } => write!(f, "应该是 {} 笔，写了 {} 笔", expected, drawn),
+            StrokeProblem::Order { stroke, expected } => write!(f, "笔顺不对：写的第 {} 笔应该是第 {} 笔", stroke, expected),
+            StrokeProblem::Direction { stroke } => write!(f, "第 {} 笔方向反了", stroke),
+            StrokeProblem::Shape { stroke } => write!(f, "第 {} 笔形状或位置偏差太大", stroke),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HandwritingScore {
+    pub correct: bool,
+    // 0–100，整体和参考字形有多像；笔画数不对时为 0
+    pub score: u8,
+    pub expected_strokes: usize,
+    pub drawn_strokes: usize,
+    pub problems: Vec<StrokeProblem>,
+}
+
+// 只判分不记录，用来调试或者拿录好的笔迹做回归
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HandwritingCheck {
+    pub kana_char: String,
+    #[cfg_attr(feature = "openapi", schema(value_type = Vec<Vec<Vec<f32>>>))]
+    pub strokes: Vec<DrawnStroke>,
+}
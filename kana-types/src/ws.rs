@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+use crate::api::{Card, Grade, MnemonicNote};
+use crate::session::{SessionMode, SessionSummary};
+use crate::strokes::DrawnStroke;
+
+// WebSocket 协议：一张卡一来一回就够了。
+// 客户端发 submit，服务端依次回 verdict、stats、下一张 card（或 session_end），
+// 不再需要 /api/v1/next_batch 轮询，也不会出现提交和取卡互相抢跑。
+// 每张 card 都带一张票，submit 凭票作答：重复提交只回放上次的 verdict，过期的票回 error。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    // 要当前这张卡（连上以后先发一次）
+    Next,
+    // 判分在服务端做，客户端只发原始输入
+    Submit {
+        ticket: String,
+        answer: String,
+        #[serde(default)]
+        response_ms: Option<u64>,
+        // 听音作答时假名也收，按读音判分
+        #[serde(default)]
+        prompt: Prompt,
+    },
+    StartSession { mode: SessionMode },
+    // 手写作答：服务端拿笔迹和笔顺数据比对后判分，verdict 的 explanation 里说哪里不对
+    SubmitStrokes {
+        ticket: String,
+        #[cfg_attr(feature = "openapi", schema(value_type = Vec<Vec<Vec<f32>>>))]
+        strokes: Vec<DrawnStroke>,
+        #[serde(default)]
+        response_ms: Option<u64>,
+    },
+}
+
+// 题目是怎么给的：看假名，还是只听发音（/api/v1/audio）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Prompt {
+    #[default]
+    Text,
+    Audio,
+    // 听写：放整串假名的发音，写下听到的，按拍判分
+    Dictation,
+}
+
+// 听写时每一拍的结果；answer 是作答里对齐到这一拍的那一段，没写就是空的
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DictationSegment {
+    pub kana: String,
+    pub expected: String,
+    pub answer: String,
+    pub correct: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Card {
+        ticket: String,
+        card: Card,
+        batch_current: usize,
+        batch_total: usize,
+        remaining_in_deck: usize,
+        is_review: bool,
+    },
+    Verdict {
+        ticket: String,
+        card_id: String,
+        correct: bool,
+        expected: String,
+        grade: Grade,
+        #[serde(default)]
+        leech: bool,
+        // 答错时：和哪个假名混了之类的说明，以及助记
+        #[serde(default)]
+        explanation: Option<String>,
+        #[serde(default)]
+        mnemonics: Vec<MnemonicNote>,
+        // 听写时逐拍的判分
+        #[serde(default)]
+        segments: Vec<DictationSegment>,
+    },
+    // 这次作答带来的变化，客户端自己累加
+    Stats {
+        answered: usize,
+        correct: usize,
+        cycle_mistakes_count: usize,
+    },
+    SessionEnd { summary: SessionSummary },
+    // code 与 HTTP 接口的错误码一致；stale_session 时重新发 next 即可
+    Error { code: String, message: String },
+}
@@ -0,0 +1,194 @@
+use kana_types::{romaji_to_kana, same_romaji, to_hiragana};
+
+// 每个假名的各种写法，标准写法排第一
+const SPELLINGS: &[(&str, &[&str])] = &[
+    ("あ", &["a"]), ("い", &["i"]), ("う", &["u"]), ("え", &["e"]), ("お", &["o"]),
+    ("か", &["ka"]), ("き", &["ki"]), ("く", &["ku"]), ("け", &["ke"]), ("こ", &["ko"]),
+    ("さ", &["sa"]), ("し", &["shi", "si"]), ("す", &["su"]), ("せ", &["se"]), ("そ", &["so"]),
+    ("た", &["ta"]), ("ち", &["chi", "ti"]), ("つ", &["tsu", "tu"]), ("て", &["te"]), ("と", &["to"]),
+    ("な", &["na"]), ("に", &["ni"]), ("ぬ", &["nu"]), ("ね", &["ne"]), ("の", &["no"]),
+    ("は", &["ha"]), ("ひ", &["hi"]), ("ふ", &["fu", "hu"]), ("へ", &["he"]), ("ほ", &["ho"]),
+    ("ま", &["ma"]), ("み", &["mi"]), ("む", &["mu"]), ("め", &["me"]), ("も", &["mo"]),
+    ("や", &["ya"]), ("ゆ", &["yu"]), ("よ", &["yo"]),
+    ("ら", &["ra"]), ("り", &["ri"]), ("る", &["ru"]), ("れ", &["re"]), ("ろ", &["ro"]),
+    ("わ", &["wa"]), ("を", &["wo"]), ("ん", &["n", "nn", "n'"]),
+    ("が", &["ga"]), ("ぎ", &["gi"]), ("ぐ", &["gu"]), ("げ", &["ge"]), ("ご", &["go"]),
+    ("ざ", &["za"]), ("じ", &["ji", "zi"]), ("ず", &["zu"]), ("ぜ", &["ze"]), ("ぞ", &["zo"]),
+    ("だ", &["da"]), ("ぢ", &["di"]), ("づ", &["du"]), ("で", &["de"]), ("ど", &["do"]),
+    ("ば", &["ba"]), ("び", &["bi"]), ("ぶ", &["bu"]), ("べ", &["be"]), ("ぼ", &["bo"]),
+    ("ぱ", &["pa"]), ("ぴ", &["pi"]), ("ぷ", &["pu"]), ("ぺ", &["pe"]), ("ぽ", &["po"]),
+    ("きゃ", &["kya"]), ("きゅ", &["kyu"]), ("きょ", &["kyo"]),
+    ("しゃ", &["sha", "sya"]), ("しゅ", &["shu", "syu"]), ("しょ", &["sho", "syo"]),
+    ("ちゃ", &["cha", "tya", "cya"]), ("ちゅ", &["chu", "tyu", "cyu"]), ("ちょ", &["cho", "tyo", "cyo"]),
+    ("にゃ", &["nya"]), ("にゅ", &["nyu"]), ("にょ", &["nyo"]),
+    ("ひゃ", &["hya"]), ("ひゅ", &["hyu"]), ("ひょ", &["hyo"]),
+    ("みゃ", &["mya"]), ("みゅ", &["myu"]), ("みょ", &["myo"]),
+    ("りゃ", &["rya"]), ("りゅ", &["ryu"]), ("りょ", &["ryo"]),
+    ("ぎゃ", &["gya"]), ("ぎゅ", &["gyu"]), ("ぎょ", &["gyo"]),
+    ("じゃ", &["ja", "zya", "jya"]), ("じゅ", &["ju", "zyu", "jyu"]), ("じょ", &["jo", "zyo", "jyo"]),
+    ("ぢゃ", &["dya"]), ("ぢゅ", &["dyu"]), ("ぢょ", &["dyo"]),
+    ("びゃ", &["bya"]), ("びゅ", &["byu"]), ("びょ", &["byo"]),
+    ("ぴゃ", &["pya"]), ("ぴゅ", &["pyu"]), ("ぴょ", &["pyo"]),
+];
+
+// ん 后面跟着元音或 y 时只能写 n'，否则会和下一拍连成 な行；nn 也一样。
+// 后面又是一个写成 n / nn 的 ん 时，单写 n 会和它并成一个 ん
+fn spellings_before(kana: &str, spellings: &'static [&'static str], next: Option<&str>) -> Vec<String> {
+    if kana != "ん" {
+        return spellings.iter().map(|s| s.to_string()).collect();
+    }
+    let next = next.unwrap_or("");
+    let mut chars = next.chars();
+    let mut out: Vec<String> = match (chars.next(), chars.next()) {
+        (Some('a' | 'i' | 'u' | 'e' | 'o' | 'y'), _) => vec!["n'".to_string()],
+        (Some('n'), second) if !matches!(second, Some('a' | 'i' | 'u' | 'e' | 'o' | 'y')) => {
+            spellings.iter().filter(|s| **s != "n").map(|s| s.to_string()).collect()
+        }
+        _ => spellings.iter().map(|s| s.to_string()).collect(),
+    };
+    if matches!(next.chars().next(), Some('b' | 'm' | 'p')) {
+        out.push("m".to_string());
+    }
+    out
+}
+
+// 一串假名所有写法的组合，每个是 (写法, 应该切出来的假名)
+fn combinations(moras: &[(&'static str, &'static [&'static str])]) -> Vec<(String, String)> {
+    let Some(((kana, spellings), rest)) = moras.split_first() else {
+        return vec![(String::new(), String::new())];
+    };
+    let mut out = Vec::new();
+    for (tail, tail_kana) in combinations(rest) {
+        for head in spellings_before(kana, spellings, Some(tail.as_str()).filter(|t| !t.is_empty())) {
+            out.push((format!("{}{}", head, tail), format!("{}{}", kana, tail_kana)));
+        }
+    }
+    out
+}
+
+fn check(cases: &[(String, String)]) -> usize {
+    let wrong: Vec<String> = cases
+        .iter()
+        .filter(|(romaji, kana)| romaji_to_kana(romaji).as_deref() != Some(kana.as_str()))
+        .map(|(romaji, kana)| format!("{} -> {:?}, expected {}", romaji, romaji_to_kana(romaji), kana))
+        .collect();
+    assert!(wrong.is_empty(), "{} of {} wrong:\n{}", wrong.len(), cases.len(), wrong.join("\n"));
+    cases.len()
+}
+
+#[test]
+fn every_single_kana_spelling() {
+    let cases: Vec<(String, String)> = SPELLINGS.iter().flat_map(|m| combinations(&[*m])).collect();
+    assert!(check(&cases) > 100);
+}
+
+#[test]
+fn every_pair_of_kana() {
+    let mut cases = Vec::new();
+    for a in SPELLINGS {
+        for b in SPELLINGS {
+            cases.extend(combinations(&[*a, *b]));
+        }
+    }
+    assert!(check(&cases) > 15_000);
+}
+
+#[test]
+fn three_kana_combos() {
+    // 换行规则最容易出错的几类：ん、拗音、し / ち / つ / ふ / じ 的训令式
+    let tricky: Vec<_> = SPELLINGS
+        .iter()
+        .filter(|(kana, _)| ["ん", "し", "ち", "つ", "ふ", "じ", "ぢ", "に", "を", "しゅ", "ちゃ", "じょ", "にゃ", "あ", "や", "か"].contains(kana))
+        .copied()
+        .collect();
+    let mut cases = Vec::new();
+    for a in &tricky {
+        for b in &tricky {
+            for c in &tricky {
+                cases.extend(combinations(&[*a, *b, *c]));
+            }
+        }
+    }
+    assert!(check(&cases) > 10_000);
+}
+
+#[test]
+fn sokuon_doubles_the_next_consonant() {
+    let mut cases = Vec::new();
+    for (kana, spellings) in SPELLINGS {
+        for spelling in *spellings {
+            let first = spelling.chars().next().unwrap();
+            // n、m 双写是 ん（onna、sammai）
+            if matches!(first, 'a' | 'i' | 'u' | 'e' | 'o' | 'n' | 'm') {
+                continue;
+            }
+            cases.push((format!("a{}{}", first, spelling), format!("あっ{}", kana)));
+            if spelling.starts_with("ch") {
+                cases.push((format!("at{}", spelling), format!("あっ{}", kana)));
+            }
+        }
+    }
+    check(&cases);
+}
+
+#[test]
+fn words() {
+    let cases: Vec<(String, String)> = [
+        ("konnichiwa", "こんにちわ"),
+        ("kon'nichiwa", "こんにちわ"),
+        ("konnnichiwa", "こんにちわ"),
+        ("kon'ya", "こんや"),
+        ("konya", "こにゃ"),
+        ("onna", "おんな"),
+        ("shimbun", "しんぶん"),
+        ("shinbun", "しんぶん"),
+        ("sammai", "さんまい"),
+        ("matcha", "まっちゃ"),
+        ("maccha", "まっちゃ"),
+        ("kitte", "きって"),
+        ("zasshi", "ざっし"),
+        ("tsukue", "つくえ"),
+        ("tukue", "つくえ"),
+        ("hutatu", "ふたつ"),
+        ("zyoyuu", "じょゆう"),
+        ("jyoyuu", "じょゆう"),
+        ("tyotto", "ちょっと"),
+        ("cyotto", "ちょっと"),
+        ("Sushi", "すし"),
+        ("su shi", "すし"),
+        ("hann", "はん"),
+        ("kanji", "かんじ"),
+        ("kanzi", "かんじ"),
+    ]
+    .iter()
+    .map(|(r, k)| (r.to_string(), k.to_string()))
+    .collect();
+    check(&cases);
+}
+
+#[test]
+fn rejects_what_is_not_romaji() {
+    for input in ["x", "kq", "shy", "ca", "tsa", "n''", "abc", "か"] {
+        assert_eq!(romaji_to_kana(input), None, "{}", input);
+    }
+}
+
+#[test]
+fn same_romaji_across_systems() {
+    // 以前按顺序替换子串时，shu 里的 hu 会先被换成 fu，syu / tyu 就和 shu / chu 对不上
+    for (a, b) in [("syu", "shu"), ("tyu", "chu"), ("syo", "sho"), ("zya", "ja"), ("situ", "shitsu"), ("hutu", "futsu")] {
+        assert!(same_romaji(a, b), "{} / {}", a, b);
+    }
+    // ぢ / づ 按字面写的 di / du 不能和 ji / zu 混为一谈
+    for (a, b) in [("di", "ji"), ("du", "zu"), ("wo", "o"), ("konya", "kon'ya")] {
+        assert!(!same_romaji(a, b), "{} / {}", a, b);
+    }
+    // 切不成假名时按原样比
+    assert!(same_romaji("Tokyo-to", "tokyo-to"));
+}
+
+#[test]
+fn katakana_to_hiragana() {
+    assert_eq!(to_hiragana("カタカナ ジャ ヂ ヲ ン"), "かたかな じゃ ぢ を ん");
+    assert_eq!(to_hiragana("ひらがな"), "ひらがな");
+}
@@ -1,60 +1,252 @@
 use axum::{
-    extract::{Json, State},
+    extract::{multipart::MultipartRejection, DefaultBodyLimit, Json, Multipart, State},
+    http::{header, HeaderValue, Method},
+    middleware,
     response::IntoResponse,
-    routing::{get, post},
+    routing::{delete, get, post, put},
     Router,
 };
-use serde::{Deserialize, Serialize};
+use kana_types::{BatchResponse, HandwritingCheck, HandwritingScore, SubmitRequest, SubmitResponse};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tower_http::cors::CorsLayer;
-use crate::app::App;
-use crate::db::Card;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use crate::app::{Answered, App, Submission};
+use crate::auth;
+use crate::cards;
+use crate::config::AuthConfig;
+use crate::backup::{self, Backup};
+use crate::combo::ComboStrategy;
+use crate::handwriting;
+use crate::db::{Db, KanaLatency, MergeReport};
+use crate::error::{ApiError, ApiJson, ApiQuery, ErrorBody};
+use crate::import::ImportReport;
+use crate::import::{self, ImportFormat, ImportOptions};
+use crate::media::MediaStore;
+use crate::session::{SessionMode, SessionSummary};
+use crate::openapi;
+use crate::ws;
+
+// .apkg 带媒体文件、几个月的备份都可能很大
+const UPLOAD_BODY_LIMIT: usize = 64 * 1024 * 1024;
 
 #[derive(Clone)]
 pub struct ApiState {
     pub app: Arc<Mutex<App>>,
+    // 认证每个请求都要查库，不去抢 App 的锁
+    pub db: Arc<Db>,
+    pub auth: AuthConfig,
+    // CORS 和免登录请求的来源检查共用
+    pub allowed_origins: Arc<[String]>,
+    pub media: MediaStore,
 }
 
+// 新客户端一律用 /api/v1；/api 是旧路径的别名，先留着给老前端和脚本
 pub fn app_router(state: ApiState) -> Router {
+    let cors = cors_layer(&state.allowed_origins);
+    Router::new()
+        .nest(
+            "/api/v1",
+            api_routes(state.clone()).route("/openapi.json", get(openapi::openapi_json))
+        )
+        .nest("/api", api_routes(state))
+        .layer(cors)
+}
+
+// 只放行配置里列出的来源；带 cookie 的跨域请求需要 allow_credentials
+fn cors_layer(allowed_origins: &[String]) -> CorsLayer {
+    let origins: Vec<HeaderValue> = allowed_origins
+        .iter()
+        .filter_map(|o| HeaderValue::from_str(o).ok())
+        .collect();
+
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
+        .allow_credentials(true)
+}
+
+fn api_routes(state: ApiState) -> Router {
+    // 登录、登出和查询登录状态不需要认证，其余全部要
     Router::new()
         .route(
-            "/api/next_batch",
-            get(get_next_batch).with_state(state.clone())
+            "/auth/login",
+            post(auth::login).with_state(state.clone())
+        )
+        .route(
+            "/auth/logout",
+            post(auth::logout).with_state(state.clone())
         )
         .route(
-            "/api/submit",
-            post(submit_answer).with_state(state)
+            "/auth/status",
+            get(auth::status).with_state(state.clone())
         )
-        .layer(CorsLayer::permissive())
+        .merge(protected_routes(state))
 }
 
-#[derive(Serialize)]
-pub struct BatchResponse {
-    pub batch_current: usize,
-    pub batch_total: usize,
-    pub remaining_in_deck: usize,
-    pub is_review: bool,
-    pub cycle_mistakes_count: usize,
-    pub cards: Vec<Card>,
-    // 【新增】后端告诉前端：现在到底该做第几张了！
-    pub current_card_index: usize,
+fn protected_routes(state: ApiState) -> Router {
+    // multipart 的边界和字段头另外留一点余量
+    let image_body_limit = state.media.max_image_bytes() + 64 * 1024;
+
+    Router::new()
+        .route(
+            "/auth/tokens",
+            get(auth::list_tokens)
+                .post(auth::create_token)
+                .with_state(state.clone())
+        )
+        .route(
+            "/auth/tokens/:id",
+            delete(auth::revoke_token).with_state(state.clone())
+        )
+        .route(
+            "/next_batch",
+            get(get_next_batch).with_state(state.clone())
+        )
+        .route(
+            "/submit",
+            post(submit_answer).with_state(state.clone())
+        )
+        .route(
+            "/handwriting/check",
+            post(check_handwriting)
+        )
+        .route(
+            "/ws",
+            get(ws::ws_handler).with_state(state.clone())
+        )
+        .route(
+            "/session",
+            put(start_session).with_state(state.clone())
+        )
+        .route(
+            "/session/summary",
+            get(get_session_summary).with_state(state.clone())
+        )
+        .route(
+            "/stats/latency",
+            get(get_latency).with_state(state.clone())
+        )
+        .route(
+            "/session/combo",
+            get(get_combo_strategy)
+                .put(set_combo_strategy)
+                .with_state(state.clone())
+        )
+        .route(
+            "/session/deck",
+            get(cards::get_session_deck)
+                .put(cards::set_session_deck)
+                .with_state(state.clone())
+        )
+        .route(
+            "/cards",
+            get(cards::list_cards)
+                .post(cards::create_card)
+                .with_state(state.clone())
+        )
+        .route(
+            "/cards/:id",
+            get(cards::get_card)
+                .put(cards::update_card)
+                .delete(cards::delete_card)
+                .with_state(state.clone())
+        )
+        .route(
+            "/cards/:id/suspend",
+            post(cards::suspend_card)
+                .delete(cards::unsuspend_card)
+                .with_state(state.clone())
+        )
+        .route(
+            "/cards/:id/mnemonic/image",
+            get(cards::get_mnemonic_image)
+                .put(cards::upload_mnemonic_image)
+                .delete(cards::delete_mnemonic_image)
+                .layer(DefaultBodyLimit::max(image_body_limit))
+                .with_state(state.clone())
+        )
+        .route(
+            "/cards/:id/strokes",
+            get(cards::get_card_strokes).with_state(state.clone())
+        )
+        .route(
+            "/strokes/:kana",
+            get(cards::get_kana_strokes)
+        )
+        .route(
+            "/audio/:id",
+            get(cards::get_card_audio).with_state(state.clone())
+        )
+        .route(
+            "/audio/kana/:kana",
+            get(cards::get_kana_audio)
+        )
+        .route(
+            "/cards/:id/bury",
+            post(cards::bury_card)
+                .delete(cards::unbury_card)
+                .with_state(state.clone())
+        )
+        .route(
+            "/decks",
+            get(cards::list_decks)
+                .post(cards::create_deck)
+                .with_state(state.clone())
+        )
+        .route(
+            "/decks/:id",
+            get(cards::get_deck)
+                .put(cards::rename_deck)
+                .delete(cards::delete_deck)
+                .with_state(state.clone())
+        )
+        .route(
+            "/decks/:id/cards",
+            put(cards::set_deck_cards)
+                .post(cards::add_deck_cards)
+                .with_state(state.clone())
+        )
+        .route(
+            "/decks/:id/cards/:card_id",
+            delete(cards::remove_deck_card).with_state(state.clone())
+        )
+        .route(
+            "/import",
+            post(import_deck)
+                .layer(DefaultBodyLimit::max(UPLOAD_BODY_LIMIT))
+                .with_state(state.clone())
+        )
+        .route(
+            "/export",
+            get(export_progress).with_state(state.clone())
+        )
+        .route(
+            "/restore",
+            post(restore_backup)
+                .layer(DefaultBodyLimit::max(UPLOAD_BODY_LIMIT))
+                .with_state(state.clone())
+        )
+        .route_layer(middleware::from_fn_with_state(state, auth::require_auth))
 }
 
-async fn get_next_batch(State(state): State<ApiState>) -> impl IntoResponse {
+#[utoipa::path(
+    get,
+    path = "/api/v1/next_batch",
+    tag = "session",
+    responses((status = 200, description = "当前批次和当前这张卡的票据", body = BatchResponse))
+)]
+pub(crate) async fn get_next_batch(State(state): State<ApiState>) -> Json<BatchResponse> {
     let mut app = state.app.lock().await;
 
-    let is_batch_empty = app.due_cards.is_empty();
-    let is_batch_finished = app.current_card_index >= app.due_cards.len();
-
-    if is_batch_empty {
-        app.start_quiz().await;
-    } else if is_batch_finished {
-        app.next_card().await;
-    }
+    // 只取不前进：没答之前反复请求拿到的是同一张卡、同一张票
+    let ticket = app.current().await.map(|t| t.id);
 
     let remaining = app.deck_queue.len();
-    let is_review_effective = app.is_review_phase || (remaining == 0 && !app.cycle_mistakes.is_empty());
+    let is_review_effective = app.is_review_effective();
+    let session_finished = app.progress.is_finished();
 
     let resp = BatchResponse {
         batch_current: app.batch_counter,
@@ -62,47 +254,264 @@ async fn get_next_batch(State(state): State<ApiState>) -> impl IntoResponse {
         remaining_in_deck: remaining,
         is_review: is_review_effective,
         cycle_mistakes_count: app.cycle_mistakes.len(),
-        cards: app.due_cards.clone(),
+        cards: if session_finished { Vec::new() } else { app.due_cards.clone() },
         // 【填充数据】把真实的进度发出去
         current_card_index: app.current_card_index,
+        session_finished,
+        ticket,
     };
 
-    Json(resp).into_response()
+    Json(resp)
+}
+
+fn submit_response(answered: &Answered, duplicate: bool) -> SubmitResponse {
+    SubmitResponse {
+        new_interval: answered.outcome.new_interval,
+        grade: answered.outcome.grade,
+        duplicate,
+        leech: answered.outcome.leech,
+        mnemonics: answered.mnemonics.clone(),
+    }
 }
 
-#[derive(Deserialize)]
-struct SubmitRequest {
-    card_id: String,
-    correct: bool,
+#[utoipa::path(
+    post,
+    path = "/api/v1/submit",
+    tag = "session",
+    request_body = SubmitRequest,
+    responses(
+        (status = 200, description = "本次评级；重复提交时 duplicate 为 true", body = SubmitResponse),
+        (status = 400, body = ErrorBody),
+        (status = 404, description = "卡片已被删除", body = ErrorBody),
+        (status = 409, description = "票据不是当前这张卡", body = ErrorBody)
+    )
+)]
+pub(crate) async fn submit_answer(
+    State(state): State<ApiState>,
+    ApiJson(payload): ApiJson<SubmitRequest>,
+) -> Result<Json<SubmitResponse>, ApiError> {
+    let mut app = state.app.lock().await;
+
+    match app.submit(&payload.ticket, payload.correct, payload.response_ms).await? {
+        Submission::Accepted(answered) => Ok(Json(submit_response(&answered, false))),
+        Submission::Duplicate(answered) => Ok(Json(submit_response(&answered, true))),
+        Submission::Stale => Err(ApiError::StaleSession(format!(
+            "ticket {} is not the current card, fetch /api/v1/next_batch again",
+            payload.ticket
+        ))),
+    }
 }
 
-#[derive(Serialize)]
-struct SubmitResponse {
-    new_interval: i64,
+// 只判分，不动当前卡和调度；练习时走 WebSocket 的 submit_strokes
+#[utoipa::path(
+    post,
+    path = "/api/v1/handwriting/check",
+    tag = "session",
+    request_body = HandwritingCheck,
+    responses(
+        (status = 200, body = HandwritingScore),
+        (status = 404, description = "有字没有笔顺数据", body = ErrorBody)
+    )
+)]
+pub(crate) async fn check_handwriting(ApiJson(payload): ApiJson<HandwritingCheck>) -> Result<Json<HandwritingScore>, ApiError> {
+    handwriting::check(&payload.kana_char, &payload.strokes)
+        .map(Json)
+        .map_err(|c| ApiError::NotFound(format!("no stroke data for '{}'", c)))
 }
 
-async fn submit_answer(
+#[utoipa::path(
+    put,
+    path = "/api/v1/session",
+    tag = "session",
+    request_body = SessionMode,
+    responses(
+        (status = 200, description = "新会话的初始总结", body = SessionSummary),
+        (status = 400, body = ErrorBody)
+    )
+)]
+pub(crate) async fn start_session(
     State(state): State<ApiState>,
-    Json(payload): Json<SubmitRequest>,
-) -> impl IntoResponse {
+    ApiJson(mode): ApiJson<SessionMode>,
+) -> Result<Json<SessionSummary>, ApiError> {
+    mode.validate().map_err(ApiError::BadRequest)?;
+
     let mut app = state.app.lock().await;
+    app.start_session(mode).await?;
+    Ok(Json(app.progress.summary()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/session/summary",
+    tag = "session",
+    responses((status = 200, body = SessionSummary))
+)]
+pub(crate) async fn get_session_summary(State(state): State<ApiState>) -> Json<SessionSummary> {
+    let app = state.app.lock().await;
+    Json(app.progress.summary())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/stats/latency",
+    tag = "stats",
+    responses((status = 200, description = "从慢到快排列", body = Vec<KanaLatency>))
+)]
+pub(crate) async fn get_latency(State(state): State<ApiState>) -> Result<Json<Vec<KanaLatency>>, ApiError> {
+    Ok(Json(state.db.latency_by_kana().await?))
+}
 
-    if payload.correct {
-        if app.is_review_phase {
-            app.cycle_mistakes.remove(&payload.card_id);
+#[utoipa::path(
+    get,
+    path = "/api/v1/session/combo",
+    tag = "session",
+    responses((status = 200, body = ComboStrategy))
+)]
+pub(crate) async fn get_combo_strategy(State(state): State<ApiState>) -> Json<ComboStrategy> {
+    let app = state.app.lock().await;
+    Json(app.combo_strategy.clone())
+}
+
+// 只影响之后取的卡，当前这张不变
+#[utoipa::path(
+    put,
+    path = "/api/v1/session/combo",
+    tag = "session",
+    request_body = ComboStrategy,
+    responses((status = 200, body = ComboStrategy), (status = 400, body = ErrorBody))
+)]
+pub(crate) async fn set_combo_strategy(
+    State(state): State<ApiState>,
+    ApiJson(strategy): ApiJson<ComboStrategy>,
+) -> Result<Json<ComboStrategy>, ApiError> {
+    strategy.validate().map_err(ApiError::BadRequest)?;
+
+    let mut app = state.app.lock().await;
+    app.combo_strategy = strategy;
+    Ok(Json(app.combo_strategy.clone()))
+}
+
+// import 的 multipart 表单，只用来生成文档，实际按字段逐个解析
+#[allow(dead_code)]
+#[derive(ToSchema)]
+pub struct ImportForm {
+    // .apkg、.csv 或 .tsv 文件
+    #[schema(format = Binary, value_type = String)]
+    file: Vec<u8>,
+    // apkg / csv / tsv，不填按扩展名判断（.txt 当 tsv）
+    format: Option<String>,
+    front: Option<usize>,
+    back: Option<usize>,
+    meaning: Option<usize>,
+    tags_field: Option<usize>,
+    // 逗号或空格分隔
+    tags: Option<String>,
+    with_history: Option<bool>,
+    skip_header: Option<bool>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/import",
+    tag = "data",
+    request_body(content = ImportForm, content_type = "multipart/form-data"),
+    responses((status = 200, body = ImportReport), (status = 400, body = ErrorBody))
+)]
+pub(crate) async fn import_deck(
+    State(state): State<ApiState>,
+    multipart: Result<Multipart, MultipartRejection>,
+) -> Result<Json<ImportReport>, ApiError> {
+    let mut multipart = multipart?;
+    let mut file: Option<(String, Vec<u8>)> = None;
+    let mut opts = ImportOptions::default();
+
+    while let Some(field) = multipart.next_field().await? {
+        let name = field.name().unwrap_or_default().to_string();
+        if name == "file" {
+            let file_name = field.file_name().unwrap_or_default().to_string();
+            file = Some((file_name, field.bytes().await?.to_vec()));
+            continue;
         }
-    } else {
-        app.cycle_mistakes.insert(payload.card_id.clone());
+
+        let value = field.text().await?;
+        apply_import_option(&mut opts, &name, value.trim()).map_err(ApiError::BadRequest)?;
     }
 
-    app.current_card_index += 1;
+    let Some((file_name, bytes)) = file else {
+        return Err(ApiError::BadRequest("missing 'file' field".to_string()));
+    };
 
-    if app.current_card_index >= app.due_cards.len() {
-        app.next_card().await;
-    }
+    Ok(Json(import::import_bytes(&state.db, &file_name, &bytes, &opts).await?))
+}
+
+fn apply_import_option(opts: &mut ImportOptions, name: &str, value: &str) -> Result<(), String> {
+    let index = |v: &str| v.parse::<usize>().map_err(|_| format!("'{}' must be a field index", name));
+    let flag = |v: &str| matches!(v, "1" | "true" | "on" | "yes");
 
-    match app.db.update_card(&payload.card_id, payload.correct).await {
-        Ok(interval) => Json(SubmitResponse { new_interval: interval }).into_response(),
-        Err(e) => (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    match name {
+        "format" => {
+            let format = <ImportFormat as clap::ValueEnum>::from_str(value, true)?;
+            opts.format = Some(format);
+        }
+        "front" => opts.front_field = index(value)?,
+        "back" => opts.back_field = index(value)?,
+        "meaning" => opts.meaning_field = Some(index(value)?),
+        "tags_field" => opts.tags_field = Some(index(value)?),
+        "tags" => opts.tags = import::split_tags(value),
+        "with_history" => opts.with_history = flag(value),
+        "skip_header" => opts.skip_header = flag(value),
+        _ => return Err(format!("unknown field '{}'", name)),
     }
-}
\ No newline at end of file
+    Ok(())
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct ExportQuery {
+    // "json"（默认）或 "csv"
+    format: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/export",
+    tag = "data",
+    params(ExportQuery),
+    responses(
+        (status = 200, content((Backup = "application/json"), (String = "text/csv"))),
+        (status = 400, body = ErrorBody)
+    )
+)]
+pub(crate) async fn export_progress(
+    State(state): State<ApiState>,
+    ApiQuery(query): ApiQuery<ExportQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let response = match query.format.as_deref().unwrap_or("json") {
+        "json" => (
+            [(header::CONTENT_DISPOSITION, "attachment; filename=\"kana-tutor-backup.json\"")],
+            Json(backup::export(&state.db).await?),
+        ).into_response(),
+        "csv" => (
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+                (header::CONTENT_DISPOSITION, "attachment; filename=\"kana-tutor-reviews.csv\""),
+            ],
+            backup::export_review_log_csv(&state.db).await?,
+        ).into_response(),
+        other => return Err(ApiError::BadRequest(format!("unknown export format '{}'", other))),
+    };
+    Ok(response)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/restore",
+    tag = "data",
+    request_body = Backup,
+    responses((status = 200, body = MergeReport), (status = 400, body = ErrorBody))
+)]
+pub(crate) async fn restore_backup(
+    State(state): State<ApiState>,
+    ApiJson(data): ApiJson<Backup>,
+) -> Result<Json<MergeReport>, ApiError> {
+    Ok(Json(backup::restore(&state.db, &data).await?))
+}
@@ -1,9 +1,47 @@
-use std::time::Instant;
-use crate::db::{Db, Card};
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::combo::{self, ComboStrategy};
+use crate::config::SessionConfig;
+use crate::recent::RecentBuffer;
+use crate::session::{SessionMode, SessionProgress};
+use crate::sampler::WeightedSampler;
+use crate::db::{Db, Card, Grade, ReviewOutcome};
+use crate::mnemonic;
+use crate::data::split_kana;
+use kana_types::MnemonicNote;
+use crate::error::CardNotFound;
 use std::sync::Arc;
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 
+// 重复提交时能认出来的最近答过的票数
+const ANSWERED_HISTORY: usize = 32;
+
+// 服务端发出的每张卡都带一张票，提交时凭票作答
+#[derive(Debug, Clone)]
+pub struct Ticket {
+    pub id: String,
+    pub card: Card,
+}
+
+#[derive(Debug, Clone)]
+pub struct Answered {
+    pub ticket: String,
+    pub card_id: String,
+    pub correct: bool,
+    pub expected: String,
+    pub outcome: ReviewOutcome,
+    // 答错时组成这张卡的各个假名的助记
+    pub mnemonics: Vec<MnemonicNote>,
+}
+
+pub enum Submission {
+    Accepted(Answered),
+    // 同一张票又交了一次，不再计分，返回第一次的结果
+    Duplicate(Answered),
+    // 不是当前这张卡的票
+    Stale,
+}
+
 #[derive(Clone, Copy, PartialEq)]
 pub enum AppState {
     Dashboard,
@@ -20,29 +58,34 @@ pub struct App {
     pub current_feedback: Option<String>,
     pub feedback_detail: String,
     pub due_count: i64,
-    pub session_start: Instant,
+    pub progress: SessionProgress,
 
     pub deck_queue: Vec<String>,
-    pub cycle_mistakes: std::collections::HashSet<String>,
+    pub cycle_mistakes: HashSet<String>,
     pub batch_counter: usize,
     pub total_cards_count: usize,
     pub estimated_total_batches: usize,
     pub is_review_phase: bool,
+    pub session: SessionConfig,
+    // 可以通过 /api/v1/session/combo 按会话切换
+    pub combo_strategy: ComboStrategy,
+    // 只练这个卡组，None 为全部卡片；通过 /api/v1/session/deck 切换
+    pub deck: Option<String>,
+    pub sampler: WeightedSampler,
+    pub recent: RecentBuffer,
+    // 当前这批缝合卡 id -> 组成它的各张卡 id
+    combo_members: HashMap<String, Vec<String>>,
+    ticket: Option<Ticket>,
+    answered: VecDeque<Answered>,
 }
 
 impl App {
-    pub async fn new() -> anyhow::Result<Self> {
-        let db = Arc::new(Db::new().await?);
+    pub async fn new(db: Arc<Db>, session: SessionConfig) -> anyhow::Result<Self> {
         let due_count = db.get_count_due().await?;
         let total_cards_count = db.get_total_count().await?;
+        let recent = RecentBuffer::load(&db, session.recent_buffer_size).await?;
 
-        // 既然是单张模式，估算批次也没太大意义了，保持简单防止除零错误
-        let batch_size = 1.0;
-        let estimated_total_batches = if total_cards_count > 0 {
-            (total_cards_count as f64 / batch_size).ceil() as usize
-        } else {
-            1
-        };
+        let estimated_total_batches = estimate_batches(total_cards_count, &session);
 
         Ok(Self {
             db,
@@ -53,82 +96,346 @@ impl App {
             current_feedback: None,
             feedback_detail: String::new(),
             due_count,
-            session_start: Instant::now(),
+            progress: SessionProgress::new(SessionMode::Open, Vec::new()),
             deck_queue: Vec::new(),
-            cycle_mistakes: std::collections::HashSet::new(),
+            cycle_mistakes: HashSet::new(),
             batch_counter: 0,
             total_cards_count,
             estimated_total_batches,
             is_review_phase: false,
+            combo_strategy: ComboStrategy::Fixed { length: session.combo_size },
+            deck: None,
+            sampler: WeightedSampler::new(session.sampling_temperature, session.coverage_floor),
+            recent,
+            combo_members: HashMap::new(),
+            ticket: None,
+            answered: VecDeque::new(),
+            session,
         })
     }
 
+    // 开始一轮新的会话：计数清零，重新洗牌
+    pub async fn start_session(&mut self, mode: SessionMode) -> anyhow::Result<()> {
+        let due_ids = if mode == SessionMode::Due { self.db.get_due_ids(self.deck.as_deref()).await? } else { Vec::new() };
+        self.due_count = self.db.get_count_due().await?;
+        self.progress = SessionProgress::new(mode, due_ids);
+        self.due_cards.clear();
+        self.ticket = None;
+        self.current_card_index = 0;
+        self.start_quiz().await;
+        Ok(())
+    }
+
+    // 换卡组等于重新开一轮，模式不变
+    pub async fn set_deck(&mut self, deck: Option<String>) -> anyhow::Result<()> {
+        self.deck = deck;
+        self.start_session(self.progress.mode.clone()).await
+    }
+
     pub async fn start_quiz(&mut self) {
         self.cycle_mistakes.clear();
         self.batch_counter = 1;
         self.is_review_phase = false;
 
-        if let Ok(mut all_ids) = self.db.get_all_ids().await {
-            {
-                let mut rng = thread_rng();
-                all_ids.shuffle(&mut rng);
+        let deck_filter = self.deck.as_deref();
+        let Ok(mut pool) = self.db.get_next_batch(&[], deck_filter).await else { return };
+        // Due 模式只练开始时到期、还没答对的卡
+        if self.progress.mode == SessionMode::Due {
+            pool.retain(|c| self.progress.due_pending.contains(&c.id));
+        }
+        let pool_size = pool.len();
+        self.recent.limit_to_pool(pool_size);
+
+        // 没在缓冲里的卡排前面：默认按掌握程度加权抽牌，弱项多出现；关掉时和原来一样均匀洗牌
+        let (fresh, mut stale): (Vec<Card>, Vec<Card>) = pool.into_iter().partition(|c| !self.recent.contains(&c.id));
+        let mut deck = if self.session.weighted_sampling {
+            self.sampler.build_deck(&fresh, &mut thread_rng())
+        } else {
+            let mut ids: Vec<String> = fresh.into_iter().map(|c| c.id).collect();
+            ids.shuffle(&mut thread_rng());
+            ids
+        };
+
+        // 最近见过的卡按从早到晚接在后面，等它们移出缓冲再轮到
+        stale.sort_by_key(|c| self.recent.position(&c.id));
+        deck.extend(stale.into_iter().map(|c| c.id));
+
+        // 只练一个卡组或有停用的卡时，总批数按实际能抽到的卡算
+        self.total_cards_count = pool_size;
+        self.estimated_total_batches = estimate_batches(pool_size, &self.session);
+        self.deck_queue = deck;
+        self.load_next_queue_batch().await;
+    }
+
+    async fn load_next_queue_batch(&mut self) {
+        // 每批 batch_size 张卡（默认 1），每张由组合策略决定缝合哪几个假名（默认固定 3 个）
+        let mut combo_cards = Vec::new();
+        let mut members = HashMap::new();
+
+        'batch: for _ in 0..self.session.batch_size {
+            if self.deck_queue.is_empty() {
+                break;
             }
 
-            self.deck_queue = all_ids;
-            self.load_next_queue_batch().await;
+            let length = self.combo_strategy.combo_length();
+            let mut window_size = self.combo_strategy.window_size(length);
+            let (positions, window_ids, found, chosen, first) = loop {
+                let positions = self.candidate_positions(window_size, length);
+                let window_ids: Vec<String> = positions.iter().map(|&i| self.deck_queue[i].clone()).collect();
+                let Ok(window_cards) = self.db.get_practice_batch(&window_ids, self.deck.as_deref()).await else { break 'batch };
+                let found: HashSet<String> = window_cards.iter().map(|c| c.id.clone()).collect();
+                let first = window_cards.first().cloned();
+                let chosen = self.combo_strategy.pick(window_cards, length, &self.sampler);
+
+                // 窗口里没有能打头的卡（比如前面全是 ん）时往牌堆后面找
+                if chosen.is_empty() && !found.is_empty() && window_size < self.deck_queue.len() {
+                    window_size = self.deck_queue.len();
+                    continue;
+                }
+                break (positions, window_ids, found, chosen, first);
+            };
+            // 整个牌堆都没有能打头的卡（只练 ん 的卡组）时只好单独出，不然这些卡永远练不到
+            let chosen = if chosen.is_empty() { first.into_iter().collect() } else { chosen };
+            let window = positions.len();
+
+            // 加权牌堆里同一张卡可能出现多次，只移除窗口里被挑中的那一次；
+            // 已经被删掉、停用或移出卡组的卡片直接从窗口里丢掉
+            let mut remove = vec![false; window];
+            for card in &chosen {
+                if let Some(pos) = (0..window).find(|&i| !remove[i] && window_ids[i] == card.id) {
+                    remove[pos] = true;
+                }
+            }
+            for (i, id) in window_ids.iter().enumerate() {
+                if !found.contains(id) {
+                    remove[i] = true;
+                }
+            }
+            let removed: HashSet<usize> = (0..window).filter(|&i| remove[i]).map(|i| positions[i]).collect();
+            let mut pos = 0;
+            self.deck_queue.retain(|_| {
+                let keep = !removed.contains(&pos);
+                pos += 1;
+                keep
+            });
+
+            if !chosen.is_empty() {
+                for card in &chosen {
+                    self.recent.push(card.id.clone());
+                }
+                let merged = combo::merge(&chosen);
+                members.insert(merged.id.clone(), chosen.iter().map(|c| c.id.clone()).collect());
+                combo_cards.push(merged);
+            }
+        }
+
+        if !combo_cards.is_empty() {
+            if let Err(e) = self.recent.save(&self.db).await {
+                log::warn!("failed to persist recently shown cards: {}", e);
+            }
+        }
+
+        if !combo_cards.is_empty() {
+            self.due_cards = combo_cards;
+            self.combo_members = members;
+            // 每次从数据库取新牌，索引必然归零
+            self.current_card_index = 0;
+            self.state = AppState::Quiz;
         }
     }
 
-    async fn load_next_queue_batch(&mut self) {
-        // === 核心修改：一次只发一张 ===
-        const COMBO_SIZE: usize = 3; // 依然保持3个假名缝合
-        const BATCH_SIZE: usize = 1; // 【改为1】每次只处理1个组合
-        // ===========================
+    // 按牌堆顺序挑最多 window 张不在缓冲里的卡，返回它们在牌堆里的位置；同一张卡只取一次。
+    // 不够 length 张时，才用最早见过的卡补上
+    fn candidate_positions(&self, window: usize, length: usize) -> Vec<usize> {
+        let mut seen = HashSet::new();
+        let mut positions = Vec::new();
+        let mut fallback = Vec::new();
+
+        for (i, id) in self.deck_queue.iter().enumerate() {
+            if !seen.insert(id.as_str()) {
+                continue;
+            }
+            if self.recent.contains(id) {
+                fallback.push(i);
+            } else {
+                positions.push(i);
+                if positions.len() >= window {
+                    break;
+                }
+            }
+        }
+
+        if positions.len() < length {
+            fallback.sort_by_key(|&i| self.recent.position(&self.deck_queue[i]));
+            positions.extend(fallback.into_iter().take(length - positions.len()));
+        }
+        positions
+    }
 
-        let ids_needed = BATCH_SIZE * COMBO_SIZE;
-        let drain_count = std::cmp::min(ids_needed, self.deck_queue.len());
+    // 保证 due_cards 里有当前要做的卡片：还没开始就洗牌开局，这一批没取到就再取。
+    // 只负责“补牌”，不会让进度前进；前进只发生在 submit 里
+    pub async fn ensure_current_card(&mut self) {
+        // 会话目标已达成就不再发牌，等前端取总结
+        if self.progress.is_finished() {
+            return;
+        }
 
-        let batch_ids: Vec<String> = self.deck_queue.drain(0..drain_count).collect();
+        if self.due_cards.is_empty() {
+            self.start_quiz().await;
+        } else if self.current_card_index >= self.due_cards.len() {
+            self.refill().await;
+        }
+    }
+
+    // 当前要做的卡和它的票据；同一张卡在答完之前反复取，拿到的都是同一张票
+    pub async fn current(&mut self) -> Option<Ticket> {
+        self.ensure_current_card().await;
+        if self.progress.is_finished() {
+            return None;
+        }
+
+        let card = self.due_cards.get(self.current_card_index)?.clone();
+        if self.ticket.as_ref().map(|t| t.card.id != card.id).unwrap_or(true) {
+            self.ticket = Some(Ticket { id: uuid::Uuid::new_v4().to_string(), card });
+        }
+        self.ticket.clone()
+    }
+
+    // 票据还有效时返回它对应的卡，用来在服务端判分
+    pub fn ticket_card(&self, ticket_id: &str) -> Option<&Card> {
+        self.ticket.as_ref().filter(|t| t.id == ticket_id).map(|t| &t.card)
+    }
 
-        if !batch_ids.is_empty() {
-            if let Ok(raw_cards) = self.db.get_batch_by_ids(&batch_ids).await {
+    // 牌堆打完但还有错题时，也算进入复习阶段
+    pub fn is_review_effective(&self) -> bool {
+        self.is_review_phase || (self.deck_queue.is_empty() && !self.cycle_mistakes.is_empty())
+    }
 
-                let mut combo_cards = Vec::new();
+    // 按票据提交：只有当前这张票会被受理并前进一张；刚答过的票重复提交时原样返回上次的结果，
+    // 其他的票一律视为过期。response_ms：从出卡到提交的毫秒数，客户端没计时就是 None
+    pub async fn submit(&mut self, ticket_id: &str, correct: bool, response_ms: Option<u64>) -> anyhow::Result<Submission> {
+        self.submit_graded(ticket_id, correct, None, response_ms).await
+    }
 
-                for chunk in raw_cards.chunks(COMBO_SIZE) {
-                    if chunk.is_empty() { continue; }
+    // 听写按拍提交：moras 是每一拍对不对，每张组成卡只按自己那几拍记分
+    pub async fn submit_moras(&mut self, ticket_id: &str, moras: &[bool], response_ms: Option<u64>) -> anyhow::Result<Submission> {
+        let correct = moras.iter().all(|&ok| ok);
+        self.submit_graded(ticket_id, correct, Some(moras), response_ms).await
+    }
 
-                    let mut merged_kana = String::new();
-                    let mut merged_romaji = String::new();
+    async fn submit_graded(
+        &mut self,
+        ticket_id: &str,
+        correct: bool,
+        moras: Option<&[bool]>,
+        response_ms: Option<u64>,
+    ) -> anyhow::Result<Submission> {
+        if let Some(answered) = self.answered.iter().find(|a| a.ticket == ticket_id) {
+            return Ok(Submission::Duplicate(answered.clone()));
+        }
+        let Some(ticket) = self.ticket.take_if(|t| t.id == ticket_id) else {
+            return Ok(Submission::Stale);
+        };
 
-                    let primary_id = chunk[0].id.clone();
-                    let primary_stability = chunk[0].stability;
-                    let primary_difficulty = chunk[0].difficulty;
+        let members = self.combo_members.get(&ticket.card.id).cloned().unwrap_or_else(|| vec![ticket.card.id.clone()]);
+        let grade = self.db.grade(correct, response_ms, members.len());
+        let member_correct = match moras {
+            Some(moras) => self.member_results(&members, moras).await.unwrap_or_else(|| vec![correct; members.len()]),
+            None => vec![correct; members.len()],
+        };
+        // 每个假名各记一次复习，用时平分，不然整张缝合卡的用时都算在第一个假名头上
+        let reviews: Vec<(String, Grade)> = members
+            .iter()
+            .zip(&member_correct)
+            .map(|(id, &ok)| (id.clone(), self.db.grade(ok, response_ms, members.len())))
+            .collect();
+        let per_kana_ms = response_ms.map(|ms| ms / members.len().max(1) as u64);
 
-                    for card in chunk {
-                        merged_kana.push_str(&card.kana_char);
-                        // 依然保留去空格逻辑
-                        merged_romaji.push_str(card.romaji.trim());
-                    }
+        let outcome = match self.db.update_cards(&reviews, per_kana_ms).await {
+            Ok(outcomes) => ReviewOutcome {
+                // 缝合卡按最早到期的那个假名算
+                new_interval: outcomes.iter().map(|o| o.new_interval).min().unwrap_or(0),
+                grade,
+                leech: outcomes.iter().any(|o| o.leech),
+            },
+            Err(e) if e.is::<CardNotFound>() => {
+                // 卡片已经被删了，跳过它，不然客户端会一直卡在这张上
+                self.advance().await;
+                return Err(e);
+            }
+            Err(e) => {
+                // 其他错误把票还回去，客户端可以原样重试
+                self.ticket = Some(ticket);
+                return Err(e);
+            }
+        };
 
-                    let virtual_card = Card {
-                        id: primary_id,
-                        kana_char: merged_kana,
-                        romaji: merged_romaji,
-                        stability: primary_stability,
-                        difficulty: primary_difficulty,
-                        last_review: None,
-                    };
+        let card = ticket.card;
+        self.progress.record(&members, &member_correct);
 
-                    combo_cards.push(virtual_card);
+        // 分数已经记上了，取助记失败只少一段提示，不影响这次提交
+        // 听写按拍判分时只给答错的那几个假名出助记
+        let missed: Vec<String> = members.iter().zip(&member_correct).filter(|(_, &ok)| !ok).map(|(id, _)| id.clone()).collect();
+        let mnemonics = if missed.is_empty() {
+            Vec::new()
+        } else {
+            match self.db.get_batch_by_ids(&missed).await {
+                Ok(cards) => cards.iter().filter_map(mnemonic::note).collect(),
+                Err(e) => {
+                    log::warn!("failed to load mnemonics for {}: {:#}", card.id, e);
+                    Vec::new()
                 }
+            }
+        };
+
+        if correct {
+            if self.is_review_phase {
+                self.cycle_mistakes.remove(&card.id);
+            }
+        } else {
+            self.cycle_mistakes.insert(card.id.clone());
+        }
+
+        let answered = Answered {
+            ticket: ticket.id,
+            card_id: card.id,
+            correct,
+            expected: card.romaji,
+            outcome,
+            mnemonics,
+        };
+        self.answered.push_back(answered.clone());
+        if self.answered.len() > ANSWERED_HISTORY {
+            self.answered.pop_front();
+        }
 
-                self.due_cards = combo_cards;
-                // 每次从数据库取新牌，索引必然归零
-                self.current_card_index = 0;
-                self.state = AppState::Quiz;
+        self.advance().await;
+        Ok(Submission::Accepted(answered))
+    }
+
+    // 把逐拍的结果分给组成卡：一张卡的几拍全对才算对。
+    // 拍数对不上（自己加的卡切不成内置假名）或者取卡失败时返回 None，整张一起算
+    async fn member_results(&self, members: &[String], moras: &[bool]) -> Option<Vec<bool>> {
+        let cards = match self.db.get_batch_by_ids(members).await {
+            Ok(cards) => cards,
+            Err(e) => {
+                log::warn!("failed to load combo members {:?}: {:#}", members, e);
+                return None;
+            }
+        };
+        let mut rest = moras;
+        let mut results = Vec::with_capacity(members.len());
+        for id in members {
+            let card = cards.iter().find(|c| &c.id == id)?;
+            let count = split_kana(&card.kana_char).ok()?.len();
+            if count > rest.len() {
+                return None;
             }
+            let (mine, tail) = rest.split_at(count);
+            results.push(mine.iter().all(|&ok| ok));
+            rest = tail;
         }
+        rest.is_empty().then_some(results)
     }
 
     #[allow(unused)]
@@ -137,22 +444,129 @@ impl App {
         self.current_card_index = 0;
     }
 
-    #[allow(unused)]
-    pub async fn next_card(&mut self) {
+    // 前进一张：这一批做完了就立即取下一批（会话结束时不再取）
+    async fn advance(&mut self) {
         self.current_card_index += 1;
         self.user_input.clear();
         self.current_feedback = None;
         self.feedback_detail.clear();
 
-        // 因为 BATCH_SIZE = 1，做完一张后 index 变成 1，1 >= 1 成立
-        // 立即去取下一张，没有任何“批次”残留
-        if self.current_card_index >= self.due_cards.len() {
-            if !self.deck_queue.is_empty() {
-                self.batch_counter += 1;
-                self.load_next_queue_batch().await;
-            } else {
-                self.start_quiz().await;
+        if self.current_card_index >= self.due_cards.len() && !self.progress.is_finished() {
+            self.refill().await;
+        }
+    }
+
+    async fn refill(&mut self) {
+        if !self.deck_queue.is_empty() {
+            self.batch_counter += 1;
+            self.load_next_queue_batch().await;
+        } else {
+            self.start_quiz().await;
+        }
+    }
+}
+
+// 每批消耗 batch_size * combo_size 个假名
+fn estimate_batches(total_cards: usize, session: &SessionConfig) -> usize {
+    let ids_per_batch = (session.batch_size * session.combo_size).max(1) as f64;
+    if total_cards > 0 {
+        (total_cards as f64 / ids_per_batch).ceil() as usize
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn app() -> App {
+        let db = Arc::new(Db::in_memory().await.unwrap());
+        let mut app = App::new(db, SessionConfig::default()).await.unwrap();
+        app.start_session(SessionMode::Open).await.unwrap();
+        app
+    }
+
+    async fn review_count(app: &App) -> usize {
+        app.db.get_review_log().await.unwrap().len()
+    }
+
+    #[tokio::test]
+    async fn duplicate_submit_returns_first_result_without_advancing() {
+        let mut app = app().await;
+        let first = app.current().await.unwrap();
+        let Submission::Accepted(accepted) = app.submit(&first.id, true, None).await.unwrap() else {
+            panic!("first submit was not accepted");
+        };
+        let next = app.current().await.unwrap();
+        let reviews = review_count(&app).await;
+
+        // 第二次交的答案不一样也不算，返回第一次的结果
+        let Submission::Duplicate(duplicate) = app.submit(&first.id, false, None).await.unwrap() else {
+            panic!("second submit was not reported as a duplicate");
+        };
+        assert_eq!(duplicate.ticket, accepted.ticket);
+        assert!(duplicate.correct);
+
+        assert_eq!(app.current().await.unwrap().id, next.id);
+        assert_eq!(app.progress.answered, 1);
+        assert_eq!(review_count(&app).await, reviews);
+    }
+
+    #[tokio::test]
+    async fn old_ticket_is_stale() {
+        let mut app = app().await;
+        let old = app.current().await.unwrap();
+        // 重新开局后旧票作废
+        app.start_session(SessionMode::Open).await.unwrap();
+        let current = app.current().await.unwrap();
+
+        assert!(matches!(app.submit(&old.id, true, None).await.unwrap(), Submission::Stale));
+        assert!(matches!(app.submit("no-such-ticket", true, None).await.unwrap(), Submission::Stale));
+        assert_eq!(app.current().await.unwrap().id, current.id);
+        assert_eq!(app.progress.answered, 0);
+        assert_eq!(review_count(&app).await, 0);
+    }
+
+    #[tokio::test]
+    async fn double_submit_never_skips_a_card() {
+        let mut app = app().await;
+        let mut shown = Vec::new();
+        let mut answered = Vec::new();
+
+        for _ in 0..20 {
+            let ticket = app.current().await.unwrap();
+            shown.push(ticket.card.id.clone());
+            for _ in 0..2 {
+                if let Submission::Accepted(a) = app.submit(&ticket.id, true, None).await.unwrap() {
+                    answered.push(a.card_id);
+                }
             }
         }
+
+        assert_eq!(answered, shown);
+        assert_eq!(app.progress.answered, 20);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn due_mode_clears_only_correct_members() {
+        let mut app = app().await;
+        app.start_session(SessionMode::Due).await.unwrap();
+        let ticket = app.current().await.unwrap();
+        let members = app.combo_members[&ticket.card.id].clone();
+        assert!(members.len() > 1);
+
+        // 最后一拍写错：只有最后一张组成卡还留在待完成里
+        let mut moras = vec![true; split_kana(&ticket.card.kana_char).unwrap().len()];
+        *moras.last_mut().unwrap() = false;
+        let Submission::Accepted(answered) = app.submit_moras(&ticket.id, &moras, None).await.unwrap() else {
+            panic!("submit was not accepted");
+        };
+        assert!(!answered.correct);
+
+        let (last, rest) = members.split_last().unwrap();
+        assert!(app.progress.due_pending.contains(last));
+        assert!(rest.iter().all(|id| !app.progress.due_pending.contains(id)));
+        assert_eq!(app.progress.correct, 0);
+    }
+}
@@ -0,0 +1,399 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::sync::OnceLock;
+
+use kana_types::{romaji_to_kana, to_hiragana};
+
+use crate::data::{split_kana, KANA_DATA};
+
+// 内置的小型共振峰合成器：不带录音文件，也不依赖系统 TTS，每个假名按罗马音现场合成。
+// 同一个读音合成出来的声音完全一样，じ / ぢ、ず / づ 本来就读得一样，这里也不会有区别
+pub const SAMPLE_RATE: u32 = 16_000;
+// 拍与拍之间的停顿
+const GAP_MS: f32 = 80.0;
+// 声道共振峰的带宽
+const BANDWIDTHS: [f32; 3] = [90.0, 110.0, 170.0];
+// 音高从开头到结尾慢慢往下走，听起来像单独念一个字
+const PITCH_START: f32 = 150.0;
+const PITCH_END: f32 = 115.0;
+
+// 某一时刻的发音参数，段内线性插值
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    formants: [f32; 3],
+    // 声带振动
+    voice: f32,
+    // 送气：噪声走声道共振，h 和清塞音后面那一小段
+    aspiration: f32,
+    // 摩擦：噪声单独过一个共振峰，s / sh 和爆破的那一下
+    frication: f32,
+    frication_hz: f32,
+}
+
+const SILENCE: Frame = Frame { formants: [500.0, 1500.0, 2500.0], voice: 0.0, aspiration: 0.0, frication: 0.0, frication_hz: 4000.0 };
+
+impl Frame {
+    fn lerp(&self, to: &Frame, t: f32) -> Frame {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        Frame {
+            formants: [0, 1, 2].map(|i| mix(self.formants[i], to.formants[i])),
+            voice: mix(self.voice, to.voice),
+            aspiration: mix(self.aspiration, to.aspiration),
+            frication: mix(self.frication, to.frication),
+            frication_hz: mix(self.frication_hz, to.frication_hz),
+        }
+    }
+
+    fn voiced(self, voice: f32) -> Frame {
+        Frame { voice, ..self }
+    }
+}
+
+fn vowel(v: char) -> Option<Frame> {
+    let formants = match v {
+        'a' => [750.0, 1250.0, 2600.0],
+        'i' => [300.0, 2250.0, 3000.0],
+        'u' => [340.0, 1350.0, 2400.0],
+        'e' => [480.0, 1850.0, 2600.0],
+        'o' => [480.0, 850.0, 2500.0],
+        _ => return None,
+    };
+    Some(Frame { formants, voice: 1.0, ..SILENCE })
+}
+
+struct Segment {
+    ms: f32,
+    from: Frame,
+    to: Frame,
+}
+
+// 一拍的参数轨迹：一段一段往后接
+struct Track {
+    segments: Vec<Segment>,
+    last: Frame,
+}
+
+impl Track {
+    // 从当前状态过渡到 to
+    fn to(&mut self, ms: f32, to: Frame) {
+        self.segments.push(Segment { ms, from: self.last, to });
+        self.last = to;
+    }
+
+    fn hold(&mut self, ms: f32, frame: Frame) {
+        self.segments.push(Segment { ms, from: frame, to: frame });
+        self.last = frame;
+    }
+
+    // 不占时间，直接跳到 frame（除阻的瞬间）
+    fn set(&mut self, frame: Frame) {
+        self.last = frame;
+    }
+}
+
+// 辅音除阻时第二共振峰的起点：双唇低、齿龈中、硬腭高，软腭跟着后面的元音走
+fn locus(consonant: &str, target: &Frame) -> Frame {
+    let f2 = match consonant {
+        "p" | "b" | "m" | "f" | "w" => 900.0,
+        "sh" | "j" | "ch" => 2100.0,
+        "k" | "g" => (target.formants[1] + 300.0).min(2400.0),
+        _ => 1750.0,
+    };
+    Frame { formants: [250.0, f2, target.formants[2]], ..SILENCE }
+}
+
+// 辅音部分，结束时停在除阻的状态，后面由调用方过渡到元音
+fn consonant(track: &mut Track, c: &str, target: &Frame) {
+    let release = locus(c, target);
+    match c {
+        "" => track.set(target.voiced(0.0)),
+        "w" => {
+            let w = Frame { formants: [320.0, 750.0, 2300.0], ..SILENCE };
+            track.set(w);
+            track.to(40.0, w.voiced(0.8));
+        }
+        "k" | "g" | "t" | "d" | "p" | "b" => {
+            let voiced = matches!(c, "g" | "d" | "b");
+            let burst_hz = match c {
+                "k" | "g" if target.formants[1] > 1600.0 => 3000.0,
+                "k" | "g" => 1800.0,
+                "t" | "d" => 4000.0,
+                _ => 1200.0,
+            };
+            // 浊音的闭塞段有低频的“浊音杠”
+            let closure = Frame { formants: [200.0, release.formants[1], release.formants[2]], ..SILENCE };
+            track.hold(70.0, closure.voiced(if voiced { 0.12 } else { 0.0 }));
+            track.hold(12.0, Frame { frication: 1.0, frication_hz: burst_hz, voice: if voiced { 0.2 } else { 0.0 }, ..release });
+            if !voiced {
+                track.hold(30.0, Frame { aspiration: 0.3, formants: target.formants, ..SILENCE });
+            }
+            track.set(release.voiced(if voiced { 0.6 } else { 0.3 }));
+        }
+        "s" | "sh" | "z" | "j" | "ch" | "ts" | "f" => {
+            let voiced = matches!(c, "z" | "j");
+            let (hz, ms, level) = match c {
+                "s" | "z" | "ts" => (5200.0, 120.0, 0.6),
+                "f" => (1400.0, 90.0, 0.8),
+                _ => (3000.0, 130.0, 0.6),
+            };
+            // 词首的 z / j 实际是塞擦音，前面先堵一下
+            let affricate = matches!(c, "ch" | "ts" | "z" | "j");
+            if affricate {
+                track.hold(if voiced { 30.0 } else { 60.0 }, release.voiced(if voiced { 0.12 } else { 0.0 }));
+            }
+            let noise = Frame { frication: level, frication_hz: hz, voice: if voiced { 0.3 } else { 0.0 }, ..release };
+            track.set(Frame { frication: if affricate { level } else { 0.0 }, ..noise });
+            track.to(if affricate { 10.0 } else { 30.0 }, noise);
+            track.hold(if affricate { ms - 50.0 } else { ms }, noise);
+            track.set(release.voiced(if voiced { 0.6 } else { 0.3 }));
+        }
+        "h" => {
+            // ひ 的 h 靠前，接近 sh；其余是送气
+            if target.formants[1] > 2000.0 {
+                let noise = Frame { frication: 0.4, frication_hz: 3600.0, ..*target }.voiced(0.0);
+                track.set(Frame { frication: 0.0, ..noise });
+                track.to(30.0, noise);
+                track.hold(70.0, noise);
+            } else {
+                let breath = Frame { aspiration: 0.25, ..*target }.voiced(0.0);
+                track.set(Frame { aspiration: 0.0, ..breath });
+                track.to(30.0, breath);
+                track.hold(70.0, breath);
+            }
+            track.set(target.voiced(0.3));
+        }
+        "m" | "n" => {
+            let murmur = Frame { formants: [250.0, if c == "m" { 1100.0 } else { 1700.0 }, 2400.0], voice: 0.45, ..SILENCE };
+            track.set(murmur.voiced(0.0));
+            track.to(25.0, murmur);
+            track.hold(60.0, murmur);
+            track.set(release.voiced(0.6));
+        }
+        // 日语的 r 是闪音：一个很短的弱音
+        "r" => {
+            let flap = Frame { formants: [300.0, 1500.0, 2200.0], ..SILENCE };
+            track.set(flap);
+            track.to(25.0, flap.voiced(0.5));
+            track.hold(15.0, flap.voiced(0.3));
+        }
+        _ => track.set(target.voiced(0.0)),
+    }
+}
+
+// 一拍的轨迹，认不出的读音返回 None
+fn mora(romaji: &str) -> Option<Track> {
+    let mut track = Track { segments: vec![Segment { ms: 30.0, from: SILENCE, to: SILENCE }], last: SILENCE };
+
+    // ん：鼻音拖长一点
+    if romaji == "n" {
+        let murmur = Frame { formants: [250.0, 1700.0, 2500.0], voice: 0.5, ..SILENCE };
+        track.set(murmur.voiced(0.0));
+        track.to(40.0, murmur);
+        track.hold(220.0, murmur);
+        track.to(80.0, murmur.voiced(0.0));
+        return Some(track);
+    }
+
+    // を 现代读 o
+    let romaji = if romaji == "wo" { "o" } else { romaji };
+    let (onset, v) = romaji.split_at(romaji.len().checked_sub(1)?);
+    let v = vowel(v.chars().next()?)?;
+    // きゃ = き的辅音 + 从 i 滑到 a；や 就是没有辅音的滑音
+    let (c, glide) = match onset.strip_suffix('y') {
+        Some(c) => (c, true),
+        None => (onset, false),
+    };
+    let i = vowel('i')?;
+    let target = if glide { i } else { v };
+
+    consonant(&mut track, c, &target);
+    if glide {
+        track.to(40.0, i);
+        track.to(90.0, v);
+    } else {
+        track.to(if c == "w" { 90.0 } else { 45.0 }, v);
+    }
+    track.hold(220.0, v);
+    track.to(90.0, v.voiced(0.0));
+    Some(track)
+}
+
+// 两极点共振器（Klatt），每个采样按当前频率重新算系数
+#[derive(Default, Clone, Copy)]
+struct Resonator {
+    y1: f32,
+    y2: f32,
+}
+
+impl Resonator {
+    fn step(&mut self, x: f32, hz: f32, bandwidth: f32) -> f32 {
+        let t = 1.0 / SAMPLE_RATE as f32;
+        let c = -(-2.0 * PI * bandwidth * t).exp();
+        let b = 2.0 * (-PI * bandwidth * t).exp() * (2.0 * PI * hz * t).cos();
+        let y = (1.0 - b - c) * x + b * self.y1 + c * self.y2;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+// 固定种子的线性同余噪声，同一个读音每次合成出来都一样
+struct Noise(u32);
+
+impl Noise {
+    fn next(&mut self) -> f32 {
+        self.0 = self.0.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (self.0 >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    }
+}
+
+// 声门气流：开相升起、闭相快速落下，取差分当声源
+fn glottal_flow(phase: f32) -> f32 {
+    if phase < 0.6 {
+        (1.0 - (PI * phase / 0.6).cos()) / 2.0
+    } else if phase < 0.8 {
+        (PI / 2.0 * (phase - 0.6) / 0.2).cos()
+    } else {
+        0.0
+    }
+}
+
+fn render(track: &Track) -> Vec<f32> {
+    let total: usize = track.segments.iter().map(|s| samples_in(s.ms)).sum();
+    let mut out = Vec::with_capacity(total);
+    let mut noise = Noise(0x2545_f491);
+    let mut cascade = [Resonator::default(); 3];
+    let mut fricative = Resonator::default();
+    let (mut phase, mut flow) = (0.0_f32, 0.0_f32);
+
+    for segment in &track.segments {
+        let n = samples_in(segment.ms);
+        for i in 0..n {
+            let frame = segment.from.lerp(&segment.to, i as f32 / n as f32);
+            let pitch = PITCH_START + (PITCH_END - PITCH_START) * out.len() as f32 / total.max(1) as f32;
+            phase = (phase + pitch / SAMPLE_RATE as f32).fract();
+            let next_flow = glottal_flow(phase);
+            let source = (next_flow - flow) * 20.0;
+            flow = next_flow;
+
+            let mut x = frame.voice * source + frame.aspiration * noise.next();
+            for (k, resonator) in cascade.iter_mut().enumerate() {
+                x = resonator.step(x, frame.formants[k], BANDWIDTHS[k]);
+            }
+            let hiss = fricative.step(noise.next(), frame.frication_hz, frame.frication_hz * 0.4);
+            out.push(x + frame.frication * hiss * 0.4);
+        }
+    }
+    out
+}
+
+fn samples_in(ms: f32) -> usize {
+    (ms * SAMPLE_RATE as f32 / 1000.0) as usize
+}
+
+// 每拍单独把峰值拉到同一个音量，拼起来的时候不会忽大忽小
+fn clip(romaji: &str) -> Option<Vec<i16>> {
+    let samples = render(&mora(romaji)?);
+    let peak = samples.iter().fold(0.0_f32, |m, s| m.max(s.abs())).max(1e-6);
+    Some(samples.iter().map(|s| (s / peak * 0.7 * i16::MAX as f32) as i16).collect())
+}
+
+// 每个读音的片段只在第一次用到时合成一遍，之后拼接都用这一份
+fn clips() -> &'static HashMap<&'static str, Vec<i16>> {
+    static CLIPS: OnceLock<HashMap<&'static str, Vec<i16>>> = OnceLock::new();
+    CLIPS.get_or_init(|| KANA_DATA.iter().filter_map(|k| Some((k.romaji, clip(k.romaji)?))).collect())
+}
+
+// 16 位单声道 PCM
+fn wav(samples: &[i16]) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut out = Vec::with_capacity(44 + samples.len() * 2);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    out.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    out.extend_from_slice(&2u16.to_le_bytes());
+    out.extend_from_slice(&16u16.to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+    for s in samples {
+        out.extend_from_slice(&s.to_le_bytes());
+    }
+    out
+}
+
+// 逐拍取片段，中间隔一小段静音拼成一个 WAV；同一串假名拼出来每次都一样。
+// 有字不是内置假名时返回 Err(那个字)
+pub fn speech(text: &str) -> Result<Vec<u8>, char> {
+    let mut samples = Vec::new();
+    for (i, pair) in split_kana(text)?.into_iter().enumerate() {
+        if i > 0 {
+            samples.resize(samples.len() + samples_in(GAP_MS), 0);
+        }
+        // KANA_DATA 的罗马音都合成得出来，这里只是以防万一
+        let Some(clip) = clips().get(pair.romaji) else {
+            return Err(pair.kana.chars().next().unwrap_or(' '));
+        };
+        samples.extend_from_slice(clip);
+    }
+    Ok(wav(&samples))
+}
+
+// 听音作答时按读音比：假名先换成罗马音，平片假名、じ / ぢ、ず / づ、お / を 都听不出区别
+pub fn heard(answer: &str) -> String {
+    let kana = match split_kana(answer) {
+        Ok(moras) if !moras.is_empty() => to_hiragana(&moras.iter().map(|k| k.kana).collect::<String>()),
+        _ => romaji_to_kana(answer).unwrap_or_else(|| answer.trim().to_lowercase()),
+    };
+    kana.replace('ぢ', "じ").replace('づ', "ず").replace('を', "お")
+}
+
+// 听音答错时补一句：这张卡里有同音的假名
+pub fn homophone_note(kana: &str) -> Option<String> {
+    let notes: Vec<&str> = [
+        ("ぢヂ", "ぢ 和 じ 读音相同（ji）"),
+        ("づヅ", "づ 和 ず 读音相同（zu）"),
+        ("をヲ", "を 和 お 读音相同（o）"),
+    ]
+    .into_iter()
+    .filter(|(chars, _)| kana.chars().any(|c| chars.contains(c)))
+    .map(|(_, note)| note)
+    .collect();
+    (!notes.is_empty()).then(|| format!("{}，听写时写哪个都算对。", notes.join("；")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speech_is_byte_identical() {
+        let first = speech("あいうきゃを").unwrap();
+        assert!(first.starts_with(b"RIFF"));
+        assert_eq!(speech("あいうきゃを").unwrap(), first);
+        // 缓存之外重新合成一遍也得是同一份
+        for pair in split_kana("あいうきゃを").unwrap() {
+            assert_eq!(clip(pair.romaji).as_ref(), clips().get(pair.romaji), "{}", pair.kana);
+        }
+        // 片假名和平假名是同一个音
+        assert_eq!(speech("アイウキャヲ").unwrap(), first);
+    }
+
+    #[test]
+    fn different_kana_sound_different() {
+        assert_ne!(speech("か").unwrap(), speech("き").unwrap());
+        assert_eq!(speech("か漢"), Err('漢'));
+    }
+
+    #[test]
+    fn homophones_are_heard_the_same() {
+        assert_eq!(heard("ぢ"), heard("ji"));
+        assert_eq!(heard("ヅ"), heard("zu"));
+        assert_eq!(heard("を"), heard("o"));
+        assert_ne!(heard("ち"), heard("ji"));
+    }
+}
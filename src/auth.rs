@@ -0,0 +1,272 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use axum::{
+    extract::{ConnectInfo, Path, Request, State},
+    http::{header, HeaderMap, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use kana_types::{ApiTokenInfo, AuthMethod, AuthStatus, CreateTokenRequest, LoginRequest, NewApiToken};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use crate::api::ApiState;
+use crate::db::Db;
+use crate::error::{ApiError, ApiJson, ErrorBody};
+
+pub const SESSION_COOKIE: &str = "kana_session";
+// 一眼能认出是 kana-tutor 的 token，泄露到日志或代码里也好搜
+const TOKEN_PREFIX: &str = "kt_";
+
+pub fn hash_password(password: &str) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| anyhow!("failed to hash password: {}", e))?;
+    Ok(hash.to_string())
+}
+
+fn verify_password(hash: &str, password: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|parsed| Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok())
+        .unwrap_or(false)
+}
+
+// 32 字节随机数的十六进制
+fn random_secret() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex(&bytes)
+}
+
+// 会话和 token 在数据库里只存哈希，库文件泄露也拿不到能用的凭据
+fn secret_hash(secret: &str) -> String {
+    hex(&Sha256::digest(secret.as_bytes()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub async fn create_api_token(db: &Db, name: &str) -> Result<NewApiToken> {
+    let token = format!("{}{}", TOKEN_PREFIX, random_secret());
+    let id = db.insert_api_token(name, &secret_hash(&token)).await?;
+    Ok(NewApiToken { id, name: name.to_string(), token })
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    value.strip_prefix("Bearer ").map(str::trim)
+}
+
+fn session_cookie(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == SESSION_COOKIE)
+        .map(|(_, value)| value)
+}
+
+fn is_loopback(peer: Option<SocketAddr>) -> bool {
+    peer.is_some_and(|addr| addr.ip().is_loopback())
+}
+
+// 经过反向代理的请求，对端地址是代理自己，不能当成本机请求
+fn is_forwarded(headers: &HeaderMap) -> bool {
+    headers.contains_key("x-forwarded-for") || headers.contains_key(header::FORWARDED)
+}
+
+// 没有 Origin（脚本、curl）或者来源是本站自己、在 server.allowed_origins 里时放行。
+// 本站自己指 Origin 去掉 scheme 后和 Host 一致；反向代理改了 Host 时要把对外的地址加进 allowed_origins
+pub fn origin_allowed(state: &ApiState, headers: &HeaderMap) -> bool {
+    let Some(origin) = headers.get(header::ORIGIN) else { return true };
+    let Ok(origin) = origin.to_str() else { return false };
+
+    if state.allowed_origins.iter().any(|o| o.eq_ignore_ascii_case(origin)) {
+        return true;
+    }
+    let host = headers.get(header::HOST).and_then(|h| h.to_str().ok());
+    match (origin.split_once("://"), host) {
+        (Some((_, origin_host)), Some(host)) => origin_host.eq_ignore_ascii_case(host),
+        _ => false,
+    }
+}
+
+async fn authenticate(state: &ApiState, headers: &HeaderMap, peer: Option<SocketAddr>) -> Result<Option<AuthMethod>> {
+    if !state.auth.enabled {
+        return Ok(Some(AuthMethod::Disabled));
+    }
+    if let Some(token) = bearer_token(headers) {
+        // 带了 token 就只看 token，错了不再退回 cookie，免得脚本悄悄用上浏览器的登录
+        return Ok(state.db.touch_api_token(&secret_hash(token)).await?.then_some(AuthMethod::Token));
+    }
+    if let Some(session) = session_cookie(headers) {
+        if state.db.auth_session_valid(&secret_hash(session)).await? {
+            return Ok(Some(AuthMethod::Session));
+        }
+    }
+    // 还没设密码时按配置信任本机，装好以后先在服务器上跑 `kana-tutor passwd`
+    if state.auth.trust_loopback
+        && is_loopback(peer)
+        && !is_forwarded(headers)
+        && state.db.get_password_hash().await?.is_none()
+    {
+        return Ok(Some(AuthMethod::Local));
+    }
+    Ok(None)
+}
+
+fn peer_addr(req: &Request) -> Option<SocketAddr> {
+    req.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0)
+}
+
+// 挂在所有需要登录的 /api 路由上
+pub async fn require_auth(State(state): State<ApiState>, req: Request, next: Next) -> Result<Response, ApiError> {
+    let peer = peer_addr(&req);
+    match authenticate(&state, req.headers(), peer).await? {
+        // 免登录的请求不带任何凭据，别的网页在浏览器里也能发过来，所以改数据的请求要核对来源
+        Some(AuthMethod::Local | AuthMethod::Disabled) if !req.method().is_safe() && !origin_allowed(&state, req.headers()) => {
+            Err(ApiError::Forbidden("cross-origin request rejected".to_string()))
+        }
+        Some(_) => Ok(next.run(req).await),
+        None => Err(ApiError::Unauthorized("login required".to_string())),
+    }
+}
+
+fn cookie_header(state: &ApiState, value: &str, max_age_secs: u64) -> HeaderValue {
+    let mut cookie = format!(
+        "{}={}; Path=/; HttpOnly; SameSite=Strict; Max-Age={}",
+        SESSION_COOKIE, value, max_age_secs
+    );
+    if state.auth.secure_cookie {
+        cookie.push_str("; Secure");
+    }
+    HeaderValue::from_str(&cookie).expect("cookie is ASCII")
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "登录成功，同时下发 HttpOnly 会话 cookie", body = AuthStatus),
+        (status = 401, description = "密码错误或还没设置密码", body = ErrorBody)
+    )
+)]
+pub(crate) async fn login(
+    State(state): State<ApiState>,
+    ApiJson(payload): ApiJson<LoginRequest>,
+) -> Result<Response, ApiError> {
+    let Some(hash) = state.db.get_password_hash().await? else {
+        return Err(ApiError::Unauthorized("no password set, run `kana-tutor passwd` on the server".to_string()));
+    };
+    if !verify_password(&hash, &payload.password) {
+        // 拖慢一点，让在线猜密码更费劲
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        return Err(ApiError::Unauthorized("wrong password".to_string()));
+    }
+
+    let session = random_secret();
+    let ttl_secs = state.auth.session_ttl_hours * 3600;
+    state.db.create_auth_session(&secret_hash(&session), ttl_secs).await?;
+
+    let status = AuthStatus { authenticated: true, password_set: true, method: Some(AuthMethod::Session) };
+    Ok(([(header::SET_COOKIE, cookie_header(&state, &session, ttl_secs))], Json(status)).into_response())
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/logout",
+    tag = "auth",
+    responses((status = 200, description = "删除当前会话并清掉 cookie", body = AuthStatus))
+)]
+pub(crate) async fn logout(State(state): State<ApiState>, req: Request) -> Result<Response, ApiError> {
+    if let Some(session) = session_cookie(req.headers()) {
+        state.db.delete_auth_session(&secret_hash(session)).await?;
+    }
+    let status = AuthStatus {
+        authenticated: false,
+        password_set: state.db.get_password_hash().await?.is_some(),
+        method: None,
+    };
+    Ok(([(header::SET_COOKIE, cookie_header(&state, "", 0))], Json(status)).into_response())
+}
+
+// 不需要登录，前端靠它决定显示登录框还是直接开始
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/status",
+    tag = "auth",
+    responses((status = 200, body = AuthStatus))
+)]
+pub(crate) async fn status(State(state): State<ApiState>, req: Request) -> Result<Json<AuthStatus>, ApiError> {
+    let method = authenticate(&state, req.headers(), peer_addr(&req)).await?;
+    Ok(Json(AuthStatus {
+        authenticated: method.is_some(),
+        password_set: state.db.get_password_hash().await?.is_some(),
+        method,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/tokens",
+    tag = "auth",
+    responses((status = 200, body = Vec<ApiTokenInfo>), (status = 401, body = ErrorBody))
+)]
+pub(crate) async fn list_tokens(State(state): State<ApiState>) -> Result<Json<Vec<ApiTokenInfo>>, ApiError> {
+    Ok(Json(state.db.list_api_tokens().await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/tokens",
+    tag = "auth",
+    request_body = CreateTokenRequest,
+    responses(
+        (status = 200, description = "token 只在这里出现一次", body = NewApiToken),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody)
+    )
+)]
+pub(crate) async fn create_token(
+    State(state): State<ApiState>,
+    ApiJson(payload): ApiJson<CreateTokenRequest>,
+) -> Result<Json<NewApiToken>, ApiError> {
+    let name = payload.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::BadRequest("token name must not be empty".to_string()));
+    }
+    Ok(Json(create_api_token(&state.db, name).await?))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/auth/tokens/{id}",
+    tag = "auth",
+    params(("id" = String, Path, description = "token 的 id（不是 token 本身）")),
+    responses(
+        (status = 200, body = Vec<ApiTokenInfo>),
+        (status = 401, body = ErrorBody),
+        (status = 404, body = ErrorBody)
+    )
+)]
+pub(crate) async fn revoke_token(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<ApiTokenInfo>>, ApiError> {
+    if !state.db.delete_api_token(&id).await? {
+        return Err(ApiError::NotFound(format!("token {} not found", id)));
+    }
+    Ok(Json(state.db.list_api_tokens().await?))
+}
@@ -0,0 +1,169 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::db::{BackupDeck, Card, Db, MergeReport, ReviewEntry};
+use crate::error::InvalidData;
+
+// 备份格式版本，结构有不兼容改动时递增。
+// 2：加了 decks，旧版本读到会把卡组悄悄丢掉，所以要让它直接拒绝
+pub const BACKUP_VERSION: u32 = 2;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Backup {
+    pub version: u32,
+    pub exported_at: String,
+    pub cards: Vec<Card>,
+    pub review_log: Vec<ReviewEntry>,
+    #[serde(default)]
+    pub decks: Vec<BackupDeck>,
+    #[serde(default)]
+    pub settings: BTreeMap<String, String>,
+}
+
+pub async fn export(db: &Db) -> Result<Backup> {
+    Ok(Backup {
+        version: BACKUP_VERSION,
+        exported_at: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        cards: db.get_all_cards().await?,
+        review_log: db.get_review_log().await?,
+        decks: db.get_backup_decks().await?,
+        settings: db.get_settings().await?,
+    })
+}
+
+// 复习记录导出为 CSV，顺带假名和罗马音方便在表格里看
+pub async fn export_review_log_csv(db: &Db) -> Result<String> {
+    let cards: HashMap<String, Card> = db
+        .get_all_cards()
+        .await?
+        .into_iter()
+        .map(|c| (c.id.clone(), c))
+        .collect();
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(["id", "card_id", "kana_char", "romaji", "reviewed_at", "correct", "source", "response_ms"])?;
+
+    for entry in db.get_review_log().await? {
+        let (kana, romaji) = cards
+            .get(&entry.card_id)
+            .map(|c| (c.kana_char.as_str(), c.romaji.as_str()))
+            .unwrap_or_default();
+        writer.write_record([
+            entry.id.as_str(),
+            entry.card_id.as_str(),
+            kana,
+            romaji,
+            entry.reviewed_at.as_str(),
+            if entry.correct { "1" } else { "0" },
+            entry.source.as_str(),
+            &entry.response_ms.map(|ms| ms.to_string()).unwrap_or_default(),
+        ])?;
+    }
+
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+// 先完整校验再合并，校验不过的备份一条都不会写进数据库
+pub async fn restore(db: &Db, backup: &Backup) -> Result<MergeReport> {
+    let existing_ids: HashSet<String> = db.get_all_ids().await?.into_iter().collect();
+    validate(backup, &existing_ids)?;
+    db.merge_backup(&backup.cards, &backup.review_log, &backup.decks, &backup.settings).await
+}
+
+fn validate(backup: &Backup, existing_ids: &HashSet<String>) -> Result<(), InvalidData> {
+    if backup.version == 0 || backup.version > BACKUP_VERSION {
+        return Err(InvalidData(format!("unsupported backup version {} (this build understands up to {})", backup.version, BACKUP_VERSION)));
+    }
+
+    let mut card_ids = HashSet::new();
+    for card in &backup.cards {
+        if card.id.trim().is_empty() {
+            return Err(InvalidData(format!("card with empty id ({})", card.kana_char)));
+        }
+        if card.kana_char.trim().is_empty() || card.romaji.trim().is_empty() {
+            return Err(InvalidData(format!("card {} has an empty kana_char or romaji", card.id)));
+        }
+        if !card.stability.is_finite() || !card.difficulty.is_finite() {
+            return Err(InvalidData(format!("card {} has non-finite scheduling state", card.id)));
+        }
+        if !card_ids.insert(card.id.as_str()) {
+            return Err(InvalidData(format!("duplicate card id {}", card.id)));
+        }
+    }
+
+    for entry in &backup.review_log {
+        if entry.id.trim().is_empty() {
+            return Err(InvalidData("review entry with empty id".to_string()));
+        }
+        if !card_ids.contains(entry.card_id.as_str()) && !existing_ids.contains(&entry.card_id) {
+            return Err(InvalidData(format!("review {} references unknown card {}", entry.id, entry.card_id)));
+        }
+    }
+
+    let mut deck_ids = HashSet::new();
+    for deck in &backup.decks {
+        if deck.id.trim().is_empty() || deck.name.trim().is_empty() {
+            return Err(InvalidData("deck with empty id or name".to_string()));
+        }
+        if !deck_ids.insert(deck.id.as_str()) {
+            return Err(InvalidData(format!("duplicate deck id {}", deck.id)));
+        }
+        for card_id in &deck.card_ids {
+            if !card_ids.contains(card_id.as_str()) && !existing_ids.contains(card_id) {
+                return Err(InvalidData(format!("deck {} references unknown card {}", deck.name, card_id)));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Grade;
+
+    async fn card_by_kana(db: &Db, kana: &str) -> Card {
+        db.find_card_by_kana(kana).await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn restore_keeps_suspended_buried_and_lapses() {
+        let source = Db::in_memory().await.unwrap();
+        let suspended = card_by_kana(&source, "あ").await.id;
+        let buried = card_by_kana(&source, "い").await.id;
+        let lapsed = card_by_kana(&source, "う").await.id;
+        let reviewed = card_by_kana(&source, "え").await.id;
+
+        // 没复习过的卡停用、埋掉；复习过的卡遗忘两次；复习过的卡再停用
+        source.set_suspended(&suspended, true).await.unwrap();
+        source.bury_card(&buried).await.unwrap();
+        for grade in [Grade::Good, Grade::Again, Grade::Again] {
+            source.update_cards(&[(lapsed.clone(), grade)], None).await.unwrap();
+        }
+        source.update_cards(&[(reviewed.clone(), Grade::Good)], None).await.unwrap();
+        source.set_suspended(&reviewed, true).await.unwrap();
+
+        let backup = export(&source).await.unwrap();
+        // 新装的库种子卡片 id 不一样，按假名对上
+        let target = Db::in_memory().await.unwrap();
+        restore(&target, &backup).await.unwrap();
+
+        assert!(card_by_kana(&target, "あ").await.suspended);
+        assert_eq!(card_by_kana(&target, "い").await.buried_until, card_by_kana(&source, "い").await.buried_until);
+        assert!(card_by_kana(&target, "い").await.buried_until.is_some());
+        assert_eq!(card_by_kana(&target, "う").await.lapses, 2);
+        let restored = card_by_kana(&target, "え").await;
+        assert!(restored.suspended);
+        assert_eq!(restored.last_review, card_by_kana(&source, "え").await.last_review);
+        assert!(!card_by_kana(&target, "お").await.suspended);
+        assert_eq!(target.get_review_log().await.unwrap().len(), 4);
+
+        // 再恢复一次什么都不变
+        let again = restore(&target, &backup).await.unwrap();
+        assert_eq!((again.cards_added, again.cards_updated, again.reviews_added), (0, 0, 0));
+    }
+}
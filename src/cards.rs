@@ -0,0 +1,620 @@
+use std::collections::HashSet;
+
+use axum::{
+    extract::{multipart::MultipartRejection, Multipart, Path, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use kana_types::{Card, CardQuery, CardRequest, CardUpdate, Deck, DeckCards, DeckRequest, SessionDeck, StrokeData};
+
+use crate::api::ApiState;
+use crate::audio::speech;
+use crate::db::{Db, NewCard};
+use crate::error::{ApiError, ApiJson, ApiQuery, CardNotFound, ErrorBody};
+use crate::import::split_tags;
+use crate::mnemonic::default_mnemonic;
+use crate::strokes::stroke_data;
+
+fn clean_kana(kana: &str) -> Result<String, ApiError> {
+    let kana = kana.trim();
+    if kana.is_empty() {
+        return Err(ApiError::BadRequest("kana_char must not be empty".to_string()));
+    }
+    Ok(kana.to_string())
+}
+
+// 判分时按罗马音比对，只接受字母，' 和 - 用来写 n'a 这类拆分
+fn clean_romaji(romaji: &str) -> Result<String, ApiError> {
+    let romaji = romaji.trim().to_lowercase();
+    if romaji.is_empty() {
+        return Err(ApiError::BadRequest("romaji must not be empty".to_string()));
+    }
+    if !romaji.chars().all(|c| c.is_ascii_alphabetic() || c == '\'' || c == '-') {
+        return Err(ApiError::BadRequest(format!("romaji '{}' may only contain ASCII letters, ' and -", romaji)));
+    }
+    Ok(romaji)
+}
+
+fn clean_meaning(meaning: Option<&str>) -> Option<String> {
+    meaning.map(str::trim).filter(|m| !m.is_empty()).map(str::to_string)
+}
+
+// 每一项里也可以用空格或逗号隔开多个标签，去重后按空格拼起来存
+fn clean_tags(tags: &[String]) -> String {
+    let mut seen = HashSet::new();
+    let tags: Vec<String> = tags
+        .iter()
+        .flat_map(|t| split_tags(t))
+        .filter(|t| seen.insert(t.clone()))
+        .collect();
+    tags.join(" ")
+}
+
+async fn ensure_unique_kana(db: &Db, kana: &str, except_id: Option<&str>) -> Result<(), ApiError> {
+    if let Some(existing) = db.find_card_by_kana(kana).await? {
+        if Some(existing.id.as_str()) != except_id {
+            return Err(ApiError::BadRequest(format!("a card for '{}' already exists ({})", kana, existing.id)));
+        }
+    }
+    Ok(())
+}
+
+async fn find_card(db: &Db, id: &str) -> Result<Card, ApiError> {
+    db.get_card(id).await?.ok_or_else(|| ApiError::NotFound(CardNotFound(id.to_string()).to_string()))
+}
+
+// 只改了一个标记的接口：没改到任何行就是卡片不存在
+async fn updated_card(db: &Db, id: &str, updated: bool) -> Result<Json<Card>, ApiError> {
+    if !updated {
+        return Err(ApiError::NotFound(CardNotFound(id.to_string()).to_string()));
+    }
+    Ok(Json(find_card(db, id).await?))
+}
+
+async fn find_deck(db: &Db, id: &str) -> Result<Deck, ApiError> {
+    db.get_deck(id).await?.ok_or_else(|| ApiError::NotFound(format!("deck {} not found", id)))
+}
+
+fn clean_deck_name(name: &str) -> Result<String, ApiError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ApiError::BadRequest("deck name must not be empty".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+async fn ensure_unique_deck_name(db: &Db, name: &str, except_id: Option<&str>) -> Result<(), ApiError> {
+    if let Some(existing) = db.find_deck_by_name(name).await? {
+        if Some(existing.id.as_str()) != except_id {
+            return Err(ApiError::BadRequest(format!("a deck named '{}' already exists", name)));
+        }
+    }
+    Ok(())
+}
+
+async fn ensure_cards_exist(db: &Db, ids: &[String]) -> Result<(), ApiError> {
+    let found: HashSet<String> = db.get_batch_by_ids(ids).await?.into_iter().map(|c| c.id).collect();
+    match ids.iter().find(|id| !found.contains(*id)) {
+        Some(missing) => Err(ApiError::BadRequest(format!("unknown card id {}", missing))),
+        None => Ok(()),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/cards",
+    tag = "cards",
+    params(CardQuery),
+    responses((status = 200, body = Vec<Card>), (status = 400, body = ErrorBody))
+)]
+pub(crate) async fn list_cards(
+    State(state): State<ApiState>,
+    ApiQuery(query): ApiQuery<CardQuery>,
+) -> Result<Json<Vec<Card>>, ApiError> {
+    Ok(Json(state.db.list_cards(&query).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/cards",
+    tag = "cards",
+    request_body = CardRequest,
+    responses((status = 200, description = "新建的卡片", body = Card), (status = 400, body = ErrorBody))
+)]
+pub(crate) async fn create_card(
+    State(state): State<ApiState>,
+    ApiJson(payload): ApiJson<CardRequest>,
+) -> Result<Json<Card>, ApiError> {
+    let kana_char = clean_kana(&payload.kana_char)?;
+    let romaji = clean_romaji(&payload.romaji)?;
+    ensure_unique_kana(&state.db, &kana_char, None).await?;
+    // 没写助记时，单个假名或拗音套用内置的
+    let mnemonic = clean_meaning(payload.mnemonic.as_deref()).or_else(|| default_mnemonic(&kana_char));
+
+    let id = state
+        .db
+        .insert_card(&NewCard {
+            mnemonic,
+            kana_char,
+            romaji,
+            meaning: clean_meaning(payload.meaning.as_deref()),
+            tags: clean_tags(&payload.tags),
+            stability: 0.0,
+            difficulty: 0.0,
+            last_review: None,
+        })
+        .await?;
+    Ok(Json(find_card(&state.db, &id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/cards/{id}",
+    tag = "cards",
+    params(("id" = String, Path)),
+    responses((status = 200, body = Card), (status = 404, body = ErrorBody))
+)]
+pub(crate) async fn get_card(State(state): State<ApiState>, Path(id): Path<String>) -> Result<Json<Card>, ApiError> {
+    Ok(Json(find_card(&state.db, &id).await?))
+}
+
+// 改名、改读音、打标签、停用都走这里；调度状态和复习记录不受影响
+#[utoipa::path(
+    put,
+    path = "/api/v1/cards/{id}",
+    tag = "cards",
+    params(("id" = String, Path)),
+    request_body = CardUpdate,
+    responses(
+        (status = 200, description = "修改后的卡片", body = Card),
+        (status = 400, body = ErrorBody),
+        (status = 404, body = ErrorBody)
+    )
+)]
+pub(crate) async fn update_card(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    ApiJson(update): ApiJson<CardUpdate>,
+) -> Result<Json<Card>, ApiError> {
+    let mut card = find_card(&state.db, &id).await?;
+
+    if let Some(kana) = &update.kana_char {
+        card.kana_char = clean_kana(kana)?;
+        ensure_unique_kana(&state.db, &card.kana_char, Some(&card.id)).await?;
+    }
+    if let Some(romaji) = &update.romaji {
+        card.romaji = clean_romaji(romaji)?;
+    }
+    if let Some(meaning) = &update.meaning {
+        card.meaning = clean_meaning(Some(meaning));
+    }
+    if let Some(tags) = &update.tags {
+        card.tags = clean_tags(tags);
+    }
+    if let Some(mnemonic) = &update.mnemonic {
+        card.mnemonic = clean_meaning(Some(mnemonic));
+    }
+    if let Some(suspended) = update.suspended {
+        card.suspended = suspended;
+    }
+
+    state.db.save_card(&card).await?;
+    Ok(Json(card))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/cards/{id}",
+    tag = "cards",
+    params(("id" = String, Path)),
+    responses(
+        (status = 200, description = "被删除的卡片，复习记录一并删除", body = Card),
+        (status = 404, body = ErrorBody)
+    )
+)]
+pub(crate) async fn delete_card(State(state): State<ApiState>, Path(id): Path<String>) -> Result<Json<Card>, ApiError> {
+    let card = find_card(&state.db, &id).await?;
+    state.db.delete_card(&id).await?;
+    if let Some(file) = &card.mnemonic_image {
+        remove_image_file(&state, file).await;
+    }
+    Ok(Json(card))
+}
+
+// 卡片已经改好了，旧图删不掉只会留个孤儿文件，不值得让请求失败
+async fn remove_image_file(state: &ApiState, file: &str) {
+    if let Err(e) = state.media.remove_mnemonic_image(file).await {
+        log::warn!("failed to remove mnemonic image {}: {:#}", file, e);
+    }
+}
+
+// 助记图片的 multipart 表单，只用来生成文档
+#[allow(dead_code)]
+#[derive(utoipa::ToSchema)]
+pub struct MnemonicImageForm {
+    // PNG、JPEG、GIF 或 WebP，大小受 media.max_image_kb 限制
+    #[schema(format = Binary, value_type = String)]
+    image: Vec<u8>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/cards/{id}/mnemonic/image",
+    tag = "cards",
+    params(("id" = String, Path)),
+    responses(
+        (status = 200, description = "图片内容，Content-Type 按文件内容给出", content_type = "image/*"),
+        (status = 404, body = ErrorBody)
+    )
+)]
+pub(crate) async fn get_mnemonic_image(State(state): State<ApiState>, Path(id): Path<String>) -> Result<Response, ApiError> {
+    let card = find_card(&state.db, &id).await?;
+    let Some(file) = card.mnemonic_image else {
+        return Err(ApiError::NotFound(format!("card {} has no mnemonic image", id)));
+    };
+    let Some((bytes, mime)) = state.media.read_mnemonic_image(&file).await? else {
+        return Err(ApiError::NotFound(format!("mnemonic image {} is missing from the media dir", file)));
+    };
+    // 每次上传文件名都会变，URL 里带着文件名，可以放心缓存
+    Ok(([(header::CONTENT_TYPE, mime), (header::CACHE_CONTROL, "private, max-age=604800")], bytes).into_response())
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/cards/{id}/mnemonic/image",
+    tag = "cards",
+    params(("id" = String, Path)),
+    request_body(content = MnemonicImageForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "换上新图片后的卡片", body = Card),
+        (status = 400, body = ErrorBody),
+        (status = 404, body = ErrorBody)
+    )
+)]
+pub(crate) async fn upload_mnemonic_image(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    multipart: Result<Multipart, MultipartRejection>,
+) -> Result<Json<Card>, ApiError> {
+    let mut multipart = multipart?;
+    let card = find_card(&state.db, &id).await?;
+
+    let mut image = None;
+    while let Some(field) = multipart.next_field().await? {
+        if field.name() == Some("image") {
+            image = Some(field.bytes().await?);
+        }
+    }
+    let Some(image) = image else {
+        return Err(ApiError::BadRequest("missing 'image' field".to_string()));
+    };
+    if image.len() > state.media.max_image_bytes() {
+        return Err(ApiError::BadRequest(format!(
+            "image is {} KB, the limit is {} KB",
+            image.len() / 1024,
+            state.media.max_image_bytes() / 1024
+        )));
+    }
+    if crate::media::image_type(&image).is_none() {
+        return Err(ApiError::BadRequest("unsupported image, expected PNG, JPEG, GIF or WebP".to_string()));
+    }
+
+    let file = state.media.save_mnemonic_image(&card.id, &image).await?;
+    if !state.db.set_mnemonic_image(&id, Some(&file)).await? {
+        // 上传途中卡片被删了
+        remove_image_file(&state, &file).await;
+        return Err(ApiError::NotFound(CardNotFound(id).to_string()));
+    }
+    if let Some(old) = &card.mnemonic_image {
+        remove_image_file(&state, old).await;
+    }
+    Ok(Json(find_card(&state.db, &id).await?))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/cards/{id}/mnemonic/image",
+    tag = "cards",
+    params(("id" = String, Path)),
+    responses((status = 200, description = "去掉图片后的卡片", body = Card), (status = 404, body = ErrorBody))
+)]
+pub(crate) async fn delete_mnemonic_image(State(state): State<ApiState>, Path(id): Path<String>) -> Result<Json<Card>, ApiError> {
+    let card = find_card(&state.db, &id).await?;
+    let updated = state.db.set_mnemonic_image(&id, None).await?;
+    if let Some(file) = &card.mnemonic_image {
+        remove_image_file(&state, file).await;
+    }
+    updated_card(&state.db, &id, updated).await
+}
+
+fn strokes_for(kana: &str) -> Result<Json<StrokeData>, ApiError> {
+    stroke_data(kana)
+        .map(Json)
+        .map_err(|c| ApiError::NotFound(format!("no stroke data for '{}'", c)))
+}
+
+// 内置的假名都有笔顺，自己加的卡只要每个字都是这些假名也能拼出来
+#[utoipa::path(
+    get,
+    path = "/api/v1/cards/{id}/strokes",
+    tag = "cards",
+    params(("id" = String, Path)),
+    responses((status = 200, body = StrokeData), (status = 404, description = "卡片不存在或有字没有笔顺数据", body = ErrorBody))
+)]
+pub(crate) async fn get_card_strokes(State(state): State<ApiState>, Path(id): Path<String>) -> Result<Json<StrokeData>, ApiError> {
+    let card = find_card(&state.db, &id).await?;
+    strokes_for(&card.kana_char)
+}
+
+// 连击模式下显示的是几张卡拼起来的虚拟卡，前端直接按假名取
+#[utoipa::path(
+    get,
+    path = "/api/v1/strokes/{kana}",
+    tag = "cards",
+    params(("kana" = String, Path, description = "一个或几个假名，需要 URL 编码")),
+    responses((status = 200, body = StrokeData), (status = 404, body = ErrorBody))
+)]
+pub(crate) async fn get_kana_strokes(Path(kana): Path<String>) -> Result<Json<StrokeData>, ApiError> {
+    strokes_for(&kana)
+}
+
+fn audio_for(kana: &str) -> Result<Response, ApiError> {
+    let wav = speech(kana).map_err(|c| ApiError::NotFound(format!("no audio for '{}'", c)))?;
+    // 同一串假名合成出来永远一样，可以放心缓存
+    Ok(([(header::CONTENT_TYPE, "audio/wav"), (header::CACHE_CONTROL, "private, max-age=604800")], wav).into_response())
+}
+
+// 内置的假名都能合成，自己加的卡只要每个字都是这些假名也能拼出来
+#[utoipa::path(
+    get,
+    path = "/api/v1/audio/{id}",
+    tag = "cards",
+    params(("id" = String, Path)),
+    responses(
+        (status = 200, description = "WAV（16 kHz 单声道）", content_type = "audio/wav"),
+        (status = 404, description = "卡片不存在或有字合成不了", body = ErrorBody)
+    )
+)]
+pub(crate) async fn get_card_audio(State(state): State<ApiState>, Path(id): Path<String>) -> Result<Response, ApiError> {
+    let card = find_card(&state.db, &id).await?;
+    audio_for(&card.kana_char)
+}
+
+// 和笔顺一样，连击模式下的虚拟卡直接按假名取
+#[utoipa::path(
+    get,
+    path = "/api/v1/audio/kana/{kana}",
+    tag = "cards",
+    params(("kana" = String, Path, description = "一个或几个假名，需要 URL 编码")),
+    responses((status = 200, description = "WAV（16 kHz 单声道）", content_type = "audio/wav"), (status = 404, body = ErrorBody))
+)]
+pub(crate) async fn get_kana_audio(Path(kana): Path<String>) -> Result<Response, ApiError> {
+    audio_for(&kana)
+}
+
+// 停用：一直不出现，直到手动恢复；进度和复习记录保留
+#[utoipa::path(
+    post,
+    path = "/api/v1/cards/{id}/suspend",
+    tag = "cards",
+    params(("id" = String, Path)),
+    responses((status = 200, body = Card), (status = 404, body = ErrorBody))
+)]
+pub(crate) async fn suspend_card(State(state): State<ApiState>, Path(id): Path<String>) -> Result<Json<Card>, ApiError> {
+    let updated = state.db.set_suspended(&id, true).await?;
+    updated_card(&state.db, &id, updated).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/cards/{id}/suspend",
+    tag = "cards",
+    params(("id" = String, Path)),
+    responses((status = 200, body = Card), (status = 404, body = ErrorBody))
+)]
+pub(crate) async fn unsuspend_card(State(state): State<ApiState>, Path(id): Path<String>) -> Result<Json<Card>, ApiError> {
+    let updated = state.db.set_suspended(&id, false).await?;
+    updated_card(&state.db, &id, updated).await
+}
+
+// 埋到明天（本地时间零点），当前这一轮剩下的时间里不再出现
+#[utoipa::path(
+    post,
+    path = "/api/v1/cards/{id}/bury",
+    tag = "cards",
+    params(("id" = String, Path)),
+    responses((status = 200, body = Card), (status = 404, body = ErrorBody))
+)]
+pub(crate) async fn bury_card(State(state): State<ApiState>, Path(id): Path<String>) -> Result<Json<Card>, ApiError> {
+    let updated = state.db.bury_card(&id).await?;
+    updated_card(&state.db, &id, updated).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/cards/{id}/bury",
+    tag = "cards",
+    params(("id" = String, Path)),
+    responses((status = 200, body = Card), (status = 404, body = ErrorBody))
+)]
+pub(crate) async fn unbury_card(State(state): State<ApiState>, Path(id): Path<String>) -> Result<Json<Card>, ApiError> {
+    let updated = state.db.unbury_card(&id).await?;
+    updated_card(&state.db, &id, updated).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/decks",
+    tag = "cards",
+    responses((status = 200, body = Vec<Deck>))
+)]
+pub(crate) async fn list_decks(State(state): State<ApiState>) -> Result<Json<Vec<Deck>>, ApiError> {
+    Ok(Json(state.db.list_decks().await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/decks",
+    tag = "cards",
+    request_body = DeckRequest,
+    responses((status = 200, description = "新建的卡组", body = Deck), (status = 400, body = ErrorBody))
+)]
+pub(crate) async fn create_deck(
+    State(state): State<ApiState>,
+    ApiJson(payload): ApiJson<DeckRequest>,
+) -> Result<Json<Deck>, ApiError> {
+    let name = clean_deck_name(&payload.name)?;
+    ensure_unique_deck_name(&state.db, &name, None).await?;
+    ensure_cards_exist(&state.db, &payload.card_ids).await?;
+
+    let id = state.db.create_deck(&name).await?;
+    state.db.add_deck_cards(&id, &payload.card_ids).await?;
+    Ok(Json(find_deck(&state.db, &id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/decks/{id}",
+    tag = "cards",
+    params(("id" = String, Path)),
+    responses((status = 200, body = Deck), (status = 404, body = ErrorBody))
+)]
+pub(crate) async fn get_deck(State(state): State<ApiState>, Path(id): Path<String>) -> Result<Json<Deck>, ApiError> {
+    Ok(Json(find_deck(&state.db, &id).await?))
+}
+
+// 只改名；卡组里有哪些卡用 /decks/{id}/cards 管理
+#[utoipa::path(
+    put,
+    path = "/api/v1/decks/{id}",
+    tag = "cards",
+    params(("id" = String, Path)),
+    request_body = DeckRequest,
+    responses((status = 200, body = Deck), (status = 400, body = ErrorBody), (status = 404, body = ErrorBody))
+)]
+pub(crate) async fn rename_deck(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    ApiJson(payload): ApiJson<DeckRequest>,
+) -> Result<Json<Deck>, ApiError> {
+    let name = clean_deck_name(&payload.name)?;
+    find_deck(&state.db, &id).await?;
+    ensure_unique_deck_name(&state.db, &name, Some(&id)).await?;
+
+    state.db.rename_deck(&id, &name).await?;
+    Ok(Json(find_deck(&state.db, &id).await?))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/decks/{id}",
+    tag = "cards",
+    params(("id" = String, Path)),
+    responses(
+        (status = 200, description = "剩下的卡组；卡片本身不会被删除", body = Vec<Deck>),
+        (status = 404, body = ErrorBody)
+    )
+)]
+pub(crate) async fn delete_deck(State(state): State<ApiState>, Path(id): Path<String>) -> Result<Json<Vec<Deck>>, ApiError> {
+    if !state.db.delete_deck(&id).await? {
+        return Err(ApiError::NotFound(format!("deck {} not found", id)));
+    }
+
+    // 正在练的卡组被删了就退回全部卡片
+    let mut app = state.app.lock().await;
+    if app.deck.as_deref() == Some(id.as_str()) {
+        app.set_deck(None).await?;
+    }
+    Ok(Json(state.db.list_decks().await?))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/decks/{id}/cards",
+    tag = "cards",
+    params(("id" = String, Path)),
+    request_body(content = DeckCards, description = "卡组的全部卡片，替换原有内容"),
+    responses((status = 200, body = Deck), (status = 400, body = ErrorBody), (status = 404, body = ErrorBody))
+)]
+pub(crate) async fn set_deck_cards(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    ApiJson(payload): ApiJson<DeckCards>,
+) -> Result<Json<Deck>, ApiError> {
+    find_deck(&state.db, &id).await?;
+    ensure_cards_exist(&state.db, &payload.card_ids).await?;
+
+    state.db.set_deck_cards(&id, &payload.card_ids).await?;
+    Ok(Json(find_deck(&state.db, &id).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/decks/{id}/cards",
+    tag = "cards",
+    params(("id" = String, Path)),
+    request_body(content = DeckCards, description = "要加进卡组的卡，已经在里面的忽略"),
+    responses((status = 200, body = Deck), (status = 400, body = ErrorBody), (status = 404, body = ErrorBody))
+)]
+pub(crate) async fn add_deck_cards(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    ApiJson(payload): ApiJson<DeckCards>,
+) -> Result<Json<Deck>, ApiError> {
+    find_deck(&state.db, &id).await?;
+    ensure_cards_exist(&state.db, &payload.card_ids).await?;
+
+    state.db.add_deck_cards(&id, &payload.card_ids).await?;
+    Ok(Json(find_deck(&state.db, &id).await?))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/decks/{id}/cards/{card_id}",
+    tag = "cards",
+    params(("id" = String, Path), ("card_id" = String, Path)),
+    responses((status = 200, body = Deck), (status = 404, body = ErrorBody))
+)]
+pub(crate) async fn remove_deck_card(
+    State(state): State<ApiState>,
+    Path((id, card_id)): Path<(String, String)>,
+) -> Result<Json<Deck>, ApiError> {
+    find_deck(&state.db, &id).await?;
+    if !state.db.remove_deck_card(&id, &card_id).await? {
+        return Err(ApiError::NotFound(format!("card {} is not in deck {}", card_id, id)));
+    }
+    Ok(Json(find_deck(&state.db, &id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/session/deck",
+    tag = "session",
+    responses((status = 200, body = SessionDeck))
+)]
+pub(crate) async fn get_session_deck(State(state): State<ApiState>) -> Json<SessionDeck> {
+    let app = state.app.lock().await;
+    Json(SessionDeck { deck_id: app.deck.clone() })
+}
+
+// 切换卡组会按当前模式重新开一轮
+#[utoipa::path(
+    put,
+    path = "/api/v1/session/deck",
+    tag = "session",
+    request_body = SessionDeck,
+    responses((status = 200, body = SessionDeck), (status = 404, body = ErrorBody))
+)]
+pub(crate) async fn set_session_deck(
+    State(state): State<ApiState>,
+    ApiJson(payload): ApiJson<SessionDeck>,
+) -> Result<Json<SessionDeck>, ApiError> {
+    if let Some(id) = &payload.deck_id {
+        find_deck(&state.db, id).await?;
+    }
+
+    let mut app = state.app.lock().await;
+    app.set_deck(payload.deck_id).await?;
+    Ok(Json(SessionDeck { deck_id: app.deck.clone() }))
+}
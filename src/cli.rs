@@ -0,0 +1,196 @@
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::import::{ImportFormat, ImportOptions};
+use crate::session::SessionMode;
+
+#[derive(Parser)]
+#[command(name = "kana-tutor", version, about = "日语假名抽认卡")]
+pub struct Cli {
+    /// 配置文件（TOML），默认读取当前目录下的 kana-tutor.toml
+    #[arg(long, global = true, env = "KANA_TUTOR_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// SQLite 数据库（URL 或文件路径），覆盖配置文件和 DATABASE_URL
+    #[arg(long, global = true)]
+    pub database: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// 启动 Web 服务器（默认）
+    Serve(ServeArgs),
+    /// 终端练习模式（适合 SSH / tmux）
+    Tui(TuiArgs),
+    /// 创建数据库并灌入默认假名（已存在时只补齐表结构）
+    Init,
+    /// 显示学习统计
+    Stats,
+    /// 清空学习进度
+    Reset {
+        /// 同时删除所有卡片并重新灌入默认假名
+        #[arg(long)]
+        cards: bool,
+        /// 确认执行，不加此参数不会做任何修改
+        #[arg(long)]
+        yes: bool,
+    },
+    /// 打印合并后的最终配置
+    Config,
+    /// 把一串假名合成为 WAV，和网页上听到的一样
+    Say {
+        /// 一个或几个内置假名，例如 きゃにを
+        kana: String,
+        /// 输出的 WAV 文件
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// 整理数据库文件（ANALYZE + VACUUM）
+    Optimize,
+    /// 检查数据库完整性和数据一致性
+    CheckDb,
+    /// 从 Anki .apkg 或 CSV/TSV 文件导入卡片
+    Import(ImportArgs),
+    /// 导出学习进度（JSON 备份或 CSV 复习记录）
+    Export(ExportArgs),
+    /// 从 JSON 备份恢复，合并进当前数据库
+    Restore {
+        /// export 生成的 JSON 文件
+        path: PathBuf,
+    },
+    /// 设置网页登录密码（会注销所有已登录的浏览器）
+    Passwd {
+        /// 从标准输入读一行作为密码，适合脚本和容器
+        #[arg(long)]
+        stdin: bool,
+    },
+    /// 管理给脚本用的 API token
+    #[command(subcommand)]
+    Token(TokenCommand),
+}
+
+#[derive(Subcommand)]
+pub enum TokenCommand {
+    /// 新建 token，只在这里显示一次
+    Create {
+        /// 用来认出是哪个脚本在用
+        name: String,
+    },
+    /// 列出所有 token（不含 token 本身）
+    List,
+    /// 吊销 token
+    Revoke {
+        /// list 里显示的 id
+        id: String,
+    },
+}
+
+// 不写默认值：没给的参数沿用配置文件 / 环境变量
+#[derive(Args, Default)]
+pub struct ServeArgs {
+    /// 监听地址 [默认: 0.0.0.0]
+    #[arg(long)]
+    pub bind: Option<IpAddr>,
+    /// 监听端口 [默认: 3000]
+    #[arg(long)]
+    pub port: Option<u16>,
+    /// 前端静态文件目录 [默认: frontend/dist]
+    #[arg(long)]
+    pub static_dir: Option<PathBuf>,
+}
+
+// 都不给就是不限时不限量
+#[derive(Args)]
+#[group(multiple = false)]
+pub struct TuiArgs {
+    /// 限时冲刺，单位秒（例如 60、120、300）
+    #[arg(long, value_name = "SECONDS")]
+    pub sprint: Option<u64>,
+    /// 做满固定张数后结束（例如 50）
+    #[arg(long, value_name = "CARDS")]
+    pub count: Option<usize>,
+    /// 把当前到期的卡全部答对为止
+    #[arg(long)]
+    pub due: bool,
+}
+
+impl TuiArgs {
+    pub fn mode(&self) -> SessionMode {
+        if let Some(seconds) = self.sprint {
+            SessionMode::Sprint { seconds }
+        } else if let Some(cards) = self.count {
+            SessionMode::Count { cards }
+        } else if self.due {
+            SessionMode::Due
+        } else {
+            SessionMode::Open
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// 完整备份：卡片、调度状态、复习记录和设置
+    Json,
+    /// 仅复习记录
+    Csv,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// 输出文件，缺省时写到标准输出
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    #[arg(long, value_enum, default_value = "json")]
+    pub format: ExportFormat,
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    /// 要导入的文件
+    pub path: PathBuf,
+    /// 文件格式，默认按扩展名判断
+    #[arg(long, value_enum)]
+    pub format: Option<ImportFormat>,
+    /// 作为 kana_char 的字段下标（从 0 开始）
+    #[arg(long, default_value_t = 0)]
+    pub front: usize,
+    /// 作为 romaji 的字段下标
+    #[arg(long, default_value_t = 1)]
+    pub back: usize,
+    /// 作为释义的字段下标
+    #[arg(long)]
+    pub meaning: Option<usize>,
+    /// CSV/TSV 中存放标签的字段下标
+    #[arg(long)]
+    pub tags_field: Option<usize>,
+    /// 给导入的卡片额外打上的标签，可重复
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+    /// 同时导入 Anki 复习记录和调度状态
+    #[arg(long)]
+    pub with_history: bool,
+    /// CSV/TSV 第一行是表头
+    #[arg(long)]
+    pub skip_header: bool,
+}
+
+impl ImportArgs {
+    pub fn to_options(&self) -> ImportOptions {
+        ImportOptions {
+            format: self.format,
+            front_field: self.front,
+            back_field: self.back,
+            meaning_field: self.meaning,
+            tags_field: self.tags_field,
+            tags: self.tags.clone(),
+            with_history: self.with_history,
+            skip_header: self.skip_header,
+        }
+    }
+}
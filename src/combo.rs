@@ -0,0 +1,281 @@
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::db::Card;
+use crate::sampler::WeightedSampler;
+
+pub const MIN_COMBO_LENGTH: usize = 1;
+pub const MAX_COMBO_LENGTH: usize = 8;
+
+// 组合策略：决定一张“缝合卡”由几个假名组成、从牌堆前面挑哪几个。
+// 所有策略都只在牌堆前面的一个窗口里挑，挑中的才从牌堆移除，没挑中的留给后面的卡。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ComboStrategy {
+    // 固定长度，按洗牌后的顺序依次缝合（原来的行为）
+    Fixed { length: usize },
+    // 每张卡的长度在 [min, max] 里随机
+    RandomRange { min: usize, max: usize },
+    // 读起来像日语：ん/っ/小写假名不打头，小写 ゃゅょ 只跟在い段假名后面，っ 不结尾
+    Phonotactic { length: usize },
+    // 优先挑 stability 低、difficulty 高的卡
+    WeakWeighted { length: usize },
+    // 平假名和片假名交替出现
+    MixedScript { length: usize },
+}
+
+impl Default for ComboStrategy {
+    fn default() -> Self {
+        Self::Fixed { length: 3 }
+    }
+}
+
+impl ComboStrategy {
+    pub fn validate(&self) -> Result<(), String> {
+        let check = |name: &str, n: usize| {
+            if (MIN_COMBO_LENGTH..=MAX_COMBO_LENGTH).contains(&n) {
+                Ok(())
+            } else {
+                Err(format!("{} must be between {} and {} (got {})", name, MIN_COMBO_LENGTH, MAX_COMBO_LENGTH, n))
+            }
+        };
+
+        match *self {
+            Self::RandomRange { min, max } => {
+                check("min", min)?;
+                check("max", max)?;
+                if min > max {
+                    return Err(format!("min ({}) must not exceed max ({})", min, max));
+                }
+                Ok(())
+            }
+            Self::Fixed { length }
+            | Self::Phonotactic { length }
+            | Self::WeakWeighted { length }
+            | Self::MixedScript { length } => check("length", length),
+        }
+    }
+
+    pub fn combo_length(&self) -> usize {
+        match *self {
+            Self::RandomRange { min, max } => thread_rng().gen_range(min..=max),
+            Self::Fixed { length }
+            | Self::Phonotactic { length }
+            | Self::WeakWeighted { length }
+            | Self::MixedScript { length } => length,
+        }
+    }
+
+    // 需要从牌堆前面看多少张候选
+    pub fn window_size(&self, length: usize) -> usize {
+        match self {
+            Self::Fixed { .. } | Self::RandomRange { .. } => length,
+            _ => (length * 4).max(16),
+        }
+    }
+
+    // 从候选窗口里按策略挑出 length 张，返回顺序即缝合顺序。
+    // Phonotactic 在窗口里找不到能接上的卡时就此打住，宁可短一点也不拼出不合规则的串；
+    // 一张都挑不出来（窗口里没有能打头的卡）时返回空
+    pub fn pick(&self, mut window: Vec<Card>, length: usize, sampler: &WeightedSampler) -> Vec<Card> {
+        let mut chosen: Vec<Card> = Vec::with_capacity(length);
+        let mut rng = thread_rng();
+
+        while chosen.len() < length && !window.is_empty() {
+            let is_last = chosen.len() + 1 == length;
+            let prev = chosen.last().map(|c| c.kana_char.as_str());
+
+            let idx = match self {
+                Self::Fixed { .. } | Self::RandomRange { .. } => 0,
+                Self::Phonotactic { .. } => {
+                    match window.iter().position(|c| fits_phonotactics(prev, &c.kana_char, is_last)) {
+                        Some(idx) => idx,
+                        None => break,
+                    }
+                }
+                Self::WeakWeighted { .. } => sampler.pick_index(&window, &mut rng).unwrap_or(0),
+                Self::MixedScript { .. } => {
+                    let want = match prev.and_then(script_of) {
+                        Some(Script::Hiragana) => Script::Katakana,
+                        Some(Script::Katakana) => Script::Hiragana,
+                        None => {
+                            if rng.gen_bool(0.5) { Script::Hiragana } else { Script::Katakana }
+                        }
+                    };
+                    window
+                        .iter()
+                        .position(|c| script_of(&c.kana_char) == Some(want))
+                        .unwrap_or(0)
+                }
+            };
+
+            chosen.push(window.remove(idx));
+        }
+
+        // 提前打住时最后一个可能是 っ，去掉它，留在牌堆里给后面的卡
+        if matches!(self, Self::Phonotactic { .. }) {
+            while chosen.last().is_some_and(|c| c.kana_char.ends_with(SOKUON)) {
+                chosen.pop();
+            }
+        }
+
+        chosen
+    }
+}
+
+// 把几张卡缝成一张虚拟卡，id 用第一张的；提交时按 App 记下的组成卡逐张记分
+pub fn merge(chunk: &[Card]) -> Card {
+    let mut merged_kana = String::new();
+    let mut merged_romaji = String::new();
+
+    for card in chunk {
+        merged_kana.push_str(&card.kana_char);
+        // 依然保留去空格逻辑
+        merged_romaji.push_str(card.romaji.trim());
+    }
+
+    Card {
+        id: chunk[0].id.clone(),
+        kana_char: merged_kana,
+        romaji: merged_romaji,
+        stability: chunk[0].stability,
+        difficulty: chunk[0].difficulty,
+        last_review: None,
+        meaning: None,
+        tags: chunk[0].tags.clone(),
+        suspended: false,
+        lapses: chunk[0].lapses,
+        buried_until: None,
+        // 助记按组成它的每个假名单独给
+        mnemonic: None,
+        mnemonic_image: None,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Script {
+    Hiragana,
+    Katakana,
+}
+
+fn script_of(kana: &str) -> Option<Script> {
+    match kana.chars().next()? {
+        '\u{3041}'..='\u{309F}' => Some(Script::Hiragana),
+        '\u{30A0}'..='\u{30FF}' => Some(Script::Katakana),
+        _ => None,
+    }
+}
+
+const SMALL_YOON: &[char] = &['ゃ', 'ゅ', 'ょ', 'ャ', 'ュ', 'ョ'];
+const SMALL_VOWELS: &[char] = &['ぁ', 'ぃ', 'ぅ', 'ぇ', 'ぉ', 'ァ', 'ィ', 'ゥ', 'ェ', 'ォ'];
+const SOKUON: &[char] = &['っ', 'ッ'];
+const MORAIC_N: &[char] = &['ん', 'ン'];
+const VOWELS: &[char] = &['あ', 'い', 'う', 'え', 'お', 'ア', 'イ', 'ウ', 'エ', 'オ'];
+// い段：后面可以接小写 ゃゅょ 构成拗音
+const I_ROW: &[char] = &[
+    'き', 'し', 'ち', 'に', 'ひ', 'み', 'り', 'ぎ', 'じ', 'ぢ', 'び', 'ぴ',
+    'キ', 'シ', 'チ', 'ニ', 'ヒ', 'ミ', 'リ', 'ギ', 'ジ', 'ヂ', 'ビ', 'ピ',
+];
+
+fn fits_phonotactics(prev: Option<&str>, next: &str, is_last: bool) -> bool {
+    let Some(first) = next.chars().next() else { return false };
+    let last_of_next = next.chars().last().unwrap_or(first);
+
+    // っ 不能出现在词尾
+    if is_last && SOKUON.contains(&last_of_next) {
+        return false;
+    }
+
+    let Some(prev) = prev else {
+        // 词首不能是 ん、っ、长音或小写假名
+        return !(MORAIC_N.contains(&first)
+            || SOKUON.contains(&first)
+            || first == 'ー'
+            || SMALL_YOON.contains(&first)
+            || SMALL_VOWELS.contains(&first));
+    };
+
+    let Some(prev_last) = prev.chars().last() else { return true };
+
+    if SMALL_YOON.contains(&first) {
+        // 小写 ゃゅょ 只能跟在い段假名后面，元音后面不行
+        return I_ROW.contains(&prev_last);
+    }
+    if SMALL_VOWELS.contains(&first) || first == 'ー' {
+        return !(MORAIC_N.contains(&prev_last) || SOKUON.contains(&prev_last));
+    }
+    // っ 后面要接辅音开头的假名
+    if SOKUON.contains(&prev_last) {
+        return !(VOWELS.contains(&first) || MORAIC_N.contains(&first) || SOKUON.contains(&first));
+    }
+    // 不要连续两个完全一样的假名
+    prev != next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(kana: &str) -> Card {
+        Card {
+            id: kana.to_string(),
+            kana_char: kana.to_string(),
+            romaji: String::new(),
+            stability: 0.0,
+            difficulty: 0.0,
+            last_review: None,
+            meaning: None,
+            tags: String::new(),
+            suspended: false,
+            lapses: 0,
+            buried_until: None,
+            mnemonic: None,
+            mnemonic_image: None,
+        }
+    }
+
+    fn pick_phonotactic(window: &[&str], length: usize) -> Vec<String> {
+        let window = window.iter().map(|k| card(k)).collect();
+        ComboStrategy::Phonotactic { length }
+            .pick(window, length, &WeightedSampler::new(1.0, 0.2))
+            .into_iter()
+            .map(|c| c.kana_char)
+            .collect()
+    }
+
+    #[test]
+    fn never_starts_with_n_sokuon_or_small_kana() {
+        for kana in ["ん", "ン", "っ", "ッ", "ゃ", "ョ", "ぁ", "ィ", "ー"] {
+            assert!(!fits_phonotactics(None, kana, false), "{}", kana);
+        }
+        assert!(fits_phonotactics(None, "か", false));
+
+        assert_eq!(pick_phonotactic(&["ん", "っ", "ゃ", "ぁ", "か"], 1), ["か"]);
+        // 窗口里没有能打头的卡
+        assert!(pick_phonotactic(&["ん", "っ", "ゃ"], 2).is_empty());
+    }
+
+    #[test]
+    fn small_yoon_only_after_i_row() {
+        for prev in ["き", "し", "ぴ", "キ"] {
+            assert!(fits_phonotactics(Some(prev), "ゃ", false), "{}", prev);
+        }
+        for prev in ["か", "あ", "ん", "っ"] {
+            assert!(!fits_phonotactics(Some(prev), "ゅ", false), "{}", prev);
+        }
+
+        assert_eq!(pick_phonotactic(&["ゃ", "き", "ゃ"], 2), ["き", "ゃ"]);
+        assert_eq!(pick_phonotactic(&["か", "ょ", "こ"], 2), ["か", "こ"]);
+    }
+
+    #[test]
+    fn never_ends_with_sokuon() {
+        assert!(!fits_phonotactics(Some("か"), "っ", true));
+        assert!(fits_phonotactics(Some("か"), "っ", false));
+
+        assert_eq!(pick_phonotactic(&["か", "っ", "っ"], 2), ["か"]);
+        // 接不上后面的卡提前打住时，结尾的 っ 也要去掉
+        assert_eq!(pick_phonotactic(&["っ", "ゃ", "き", "ゃ", "っ"], 4), ["き"]);
+    }
+}
@@ -0,0 +1,390 @@
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::db::DEFAULT_DATABASE_URL;
+
+// 没有 --config / KANA_TUTOR_CONFIG 时，当前目录下有这个文件就自动读取
+pub const DEFAULT_CONFIG_FILE: &str = "kana-tutor.toml";
+
+// 优先级：默认值 < 配置文件 < 环境变量 < 命令行参数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub session: SessionConfig,
+    pub scheduler: SchedulerConfig,
+    pub logging: LoggingConfig,
+    pub auth: AuthConfig,
+    pub media: MediaConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind: IpAddr,
+    pub port: u16,
+    pub static_dir: PathBuf,
+    // 允许跨域调用 API 的来源，例如 "http://localhost:8080"；为空时只允许同源
+    pub allowed_origins: Vec<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: IpAddr::from([0, 0, 0, 0]),
+            port: 3000,
+            static_dir: PathBuf::from("frontend/dist"),
+            allowed_origins: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub url: String,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self { url: DEFAULT_DATABASE_URL.to_string() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionConfig {
+    // 几个假名缝合成一张卡
+    pub combo_size: usize,
+    // 每次发给前端几张（缝合后的）卡
+    pub batch_size: usize,
+    // false 时退回均匀洗牌
+    pub weighted_sampling: bool,
+    // 越小越偏向弱项，越大越接近均匀
+    pub sampling_temperature: f64,
+    // 0-1，每张卡每轮至少保留的期望出现次数；连续 ceil(1 / coverage_floor) - 1 轮没抽到的卡下一轮必出
+    pub coverage_floor: f64,
+    // 最近展示过的这么多张卡不会再被选中（最多排除卡池的一半），0 表示关闭
+    pub recent_buffer_size: usize,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            combo_size: 3,
+            batch_size: 1,
+            weighted_sampling: true,
+            sampling_temperature: 0.5,
+            coverage_floor: 0.2,
+            recent_buffer_size: 200,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchedulerConfig {
+    // 新卡（stability 为 0）第一次复习时的起始 stability，单位天；之后的每次复习都在它的基础上乘
+    pub initial_stability: f64,
+    // 答对时 stability 乘以该系数，答错时乘以 lapse_factor
+    pub success_factor: f64,
+    pub lapse_factor: f64,
+    // 答对时 difficulty 减少、答错时增加的幅度
+    pub difficulty_step_down: f64,
+    pub difficulty_step_up: f64,
+    // 答对但每个假名超过这么多毫秒记为 Hard，0 表示关闭
+    pub slow_answer_ms: u64,
+    // Hard 时 stability 的乘数，介于 1 和 success_factor 之间
+    pub hard_factor: f64,
+    // 遗忘这么多次的卡算 leech；off 时只计数不处理
+    pub leech_action: LeechAction,
+    pub leech_threshold: i64,
+    // leech_action = "drill" 时把 leech 放进这个卡组，没有就自动建
+    pub leech_deck: String,
+}
+
+// 怎么处理 leech，默认不处理：要不要把卡藏起来由用户自己决定
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeechAction {
+    #[default]
+    Off,
+    // 只打上 leech 标签
+    Tag,
+    // 打标签并停用
+    Suspend,
+    // 打标签并放进专练卡组，正常练习里照样出现
+    Drill,
+}
+
+impl FromStr for LeechAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Self::Off),
+            "tag" => Ok(Self::Tag),
+            "suspend" => Ok(Self::Suspend),
+            "drill" => Ok(Self::Drill),
+            _ => Err("expected off, tag, suspend or drill".to_string()),
+        }
+    }
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            initial_stability: 1.0,
+            success_factor: 1.5,
+            lapse_factor: 0.8,
+            difficulty_step_down: 0.1,
+            difficulty_step_up: 0.2,
+            slow_answer_ms: 0,
+            hard_factor: 1.2,
+            leech_action: LeechAction::Off,
+            leech_threshold: 8,
+            leech_deck: "Leech 专练".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    // env_logger 的过滤语法，例如 "info" 或 "kana_tutor=debug"；设置了 RUST_LOG 时以 RUST_LOG 为准
+    pub level: String,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self { level: "info".to_string() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    // 关掉后所有 /api 接口都不需要登录，只适合只监听 127.0.0.1 的情况
+    pub enabled: bool,
+    // 登录后会话 cookie 的有效期
+    pub session_ttl_hours: u64,
+    // 只通过 HTTPS（或 localhost）访问时保持 true；局域网里直接用 http 访问要改成 false，否则浏览器不会保存 cookie
+    pub secure_cookie: bool,
+    // 还没设密码时放行来自本机（127.0.0.1 / ::1）的请求。
+    // 带 X-Forwarded-For / Forwarded 的请求多半是本机的反向代理转发来的，照样不放行
+    pub trust_loopback: bool,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            session_ttl_hours: 24 * 30,
+            secure_cookie: true,
+            trust_loopback: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MediaConfig {
+    // 上传的助记图片等文件放在这里，和数据库一起备份
+    pub dir: PathBuf,
+    pub max_image_kb: usize,
+}
+
+impl Default for MediaConfig {
+    fn default() -> Self {
+        Self { dir: PathBuf::from("media"), max_image_kb: 1024 }
+    }
+}
+
+impl Config {
+    // path 为 None 时只在默认位置找，找不到就用默认值；显式指定的文件必须存在
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let (path, required) = match path {
+            Some(p) => (p.to_path_buf(), true),
+            None => (PathBuf::from(DEFAULT_CONFIG_FILE), false),
+        };
+
+        if !path.exists() {
+            if required {
+                bail!("config file {} does not exist", path.display());
+            }
+            return Ok(Self::default());
+        }
+
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read config file {}", path.display()))?;
+        toml::from_str(&text).with_context(|| format!("invalid config file {}", path.display()))
+    }
+
+    pub fn apply_env(&mut self) -> Result<()> {
+        if let Ok(url) = std::env::var("DATABASE_URL") {
+            self.database.url = url;
+        }
+        if let Some(bind) = env_parse("KANA_TUTOR_BIND")? {
+            self.server.bind = bind;
+        }
+        if let Some(port) = env_parse("KANA_TUTOR_PORT")? {
+            self.server.port = port;
+        }
+        if let Ok(dir) = std::env::var("KANA_TUTOR_STATIC_DIR") {
+            self.server.static_dir = PathBuf::from(dir);
+        }
+        if let Some(size) = env_parse("KANA_TUTOR_COMBO_SIZE")? {
+            self.session.combo_size = size;
+        }
+        if let Some(size) = env_parse("KANA_TUTOR_BATCH_SIZE")? {
+            self.session.batch_size = size;
+        }
+        if let Some(size) = env_parse("KANA_TUTOR_RECENT_BUFFER")? {
+            self.session.recent_buffer_size = size;
+        }
+        if let Some(action) = env_parse("KANA_TUTOR_LEECH_ACTION")? {
+            self.scheduler.leech_action = action;
+        }
+        if let Ok(origins) = std::env::var("KANA_TUTOR_ALLOWED_ORIGINS") {
+            self.server.allowed_origins = origins
+                .split(',')
+                .map(str::trim)
+                .filter(|o| !o.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Some(enabled) = env_parse("KANA_TUTOR_AUTH")? {
+            self.auth.enabled = enabled;
+        }
+        if let Some(secure) = env_parse("KANA_TUTOR_SECURE_COOKIE")? {
+            self.auth.secure_cookie = secure;
+        }
+        if let Some(trust) = env_parse("KANA_TUTOR_TRUST_LOOPBACK")? {
+            self.auth.trust_loopback = trust;
+        }
+        if let Ok(dir) = std::env::var("KANA_TUTOR_MEDIA_DIR") {
+            self.media.dir = PathBuf::from(dir);
+        }
+        if let Ok(level) = std::env::var("KANA_TUTOR_LOG") {
+            self.logging.level = level;
+        }
+        Ok(())
+    }
+
+    // 一次性列出所有问题，而不是改一个报一个
+    pub fn validate(&self) -> Result<()> {
+        let mut errors = Vec::new();
+
+        if self.server.port == 0 {
+            errors.push("server.port must not be 0".to_string());
+        }
+        for origin in &self.server.allowed_origins {
+            if !is_valid_origin(origin) {
+                errors.push(format!(
+                    "server.allowed_origins entry '{}' must look like \"https://example.com\" (scheme and host, no path)",
+                    origin
+                ));
+            }
+        }
+        if self.database.url.trim().is_empty() {
+            errors.push("database.url must not be empty".to_string());
+        }
+        if !(1..=8).contains(&self.session.combo_size) {
+            errors.push(format!("session.combo_size must be between 1 and 8 (got {})", self.session.combo_size));
+        }
+        if self.session.batch_size == 0 {
+            errors.push("session.batch_size must be at least 1".to_string());
+        }
+        if !(self.session.sampling_temperature.is_finite() && self.session.sampling_temperature > 0.0) {
+            errors.push(format!("session.sampling_temperature must be > 0 (got {})", self.session.sampling_temperature));
+        }
+        if !(self.session.coverage_floor > 0.0 && self.session.coverage_floor <= 1.0) {
+            errors.push(format!("session.coverage_floor must be in (0, 1] (got {})", self.session.coverage_floor));
+        }
+
+        let s = &self.scheduler;
+        if !(s.initial_stability.is_finite() && s.initial_stability > 0.0) {
+            errors.push(format!("scheduler.initial_stability must be a positive number of days (got {})", s.initial_stability));
+        }
+        if !(s.success_factor.is_finite() && s.success_factor >= 1.0) {
+            errors.push(format!("scheduler.success_factor must be >= 1.0 (got {})", s.success_factor));
+        }
+        if !(s.lapse_factor.is_finite() && s.lapse_factor > 0.0 && s.lapse_factor <= 1.0) {
+            errors.push(format!("scheduler.lapse_factor must be in (0, 1] (got {})", s.lapse_factor));
+        }
+        if !(s.hard_factor.is_finite() && s.hard_factor >= 1.0 && s.hard_factor <= s.success_factor) {
+            errors.push(format!("scheduler.hard_factor must be between 1.0 and success_factor (got {})", s.hard_factor));
+        }
+        for (name, value) in [("difficulty_step_down", s.difficulty_step_down), ("difficulty_step_up", s.difficulty_step_up)] {
+            if !(value.is_finite() && value >= 0.0) {
+                errors.push(format!("scheduler.{} must be a non-negative number (got {})", name, value));
+            }
+        }
+        if s.leech_threshold < 1 {
+            errors.push(format!("scheduler.leech_threshold must be at least 1 (got {})", s.leech_threshold));
+        }
+        if s.leech_action == LeechAction::Drill && s.leech_deck.trim().is_empty() {
+            errors.push("scheduler.leech_deck must not be empty when leech_action is \"drill\"".to_string());
+        }
+
+        if self.auth.session_ttl_hours == 0 {
+            errors.push("auth.session_ttl_hours must be at least 1".to_string());
+        }
+        if self.media.max_image_kb == 0 {
+            errors.push("media.max_image_kb must be at least 1".to_string());
+        }
+
+        if !is_valid_log_filter(&self.logging.level) {
+            errors.push(format!(
+                "logging.level '{}' is not a valid filter (expected e.g. \"info\" or \"kana_tutor=debug,warn\")",
+                self.logging.level
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            bail!("invalid configuration:\n  - {}", errors.join("\n  - "))
+        }
+    }
+}
+
+// 只检查每条指令的级别部分，模块路径部分 env_logger 本身也不校验
+fn is_valid_log_filter(filter: &str) -> bool {
+    let directives = filter.split('/').next().unwrap_or_default();
+    !directives.trim().is_empty()
+        && directives.split(',').map(str::trim).filter(|d| !d.is_empty()).all(|d| {
+            match d.rsplit_once('=') {
+                Some((_, level)) => log::LevelFilter::from_str(level).is_ok(),
+                // 单独一个词：要么是级别，要么是模块路径（等价于 module=trace）
+                None => log::LevelFilter::from_str(d).is_ok() || d.contains("::") || d.contains('_'),
+            }
+        })
+}
+
+// 浏览器发来的 Origin 头只有 scheme://host[:port]，多写了路径或斜杠就永远匹配不上
+fn is_valid_origin(origin: &str) -> bool {
+    let Some((scheme, host)) = origin.split_once("://") else { return false };
+    matches!(scheme, "http" | "https")
+        && !host.is_empty()
+        && !host.contains('/')
+        && axum::http::HeaderValue::from_str(origin).is_ok()
+}
+
+fn env_parse<T: FromStr>(name: &str) -> Result<Option<T>>
+where
+    T::Err: std::fmt::Display,
+{
+    match std::env::var(name) {
+        Ok(raw) => match raw.parse() {
+            Ok(v) => Ok(Some(v)),
+            Err(e) => bail!("environment variable {}='{}' is invalid: {}", name, raw, e),
+        },
+        Err(_) => Ok(None),
+    }
+}
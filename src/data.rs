@@ -1,8 +1,32 @@
+use kana_types::romaji_to_kana;
+
 pub struct KanaPair {
     pub kana: &'static str,
+    // 标准写法（平文式），显示和合成发音都用它
     pub romaji: &'static str,
 }
 
+// 除了标准写法以外也算对的写法。训令式 si / ti / zya 这类对所有假名都适用的差别由 romaji_to_kana 统一，
+// 这里只放某个假名自己的：ぢ / づ 按字面写 di / du，を 按读音写 o，ん 写成 nn 或 n'（元音和 y 前面只能用 n'）
+const ALTERNATE_READINGS: &[(&str, &[&str])] = &[
+    ("ぢ", &["di"]),
+    ("づ", &["du"]),
+    ("を", &["o"]),
+    ("ん", &["nn", "n'"]),
+    ("ヂ", &["di"]),
+    ("ヅ", &["du"]),
+    ("ヲ", &["o"]),
+    ("ン", &["nn", "n'"]),
+];
+
+impl KanaPair {
+    // 所有算对的写法，标准写法排第一
+    pub fn readings(&self) -> impl Iterator<Item = &'static str> {
+        let alternates = ALTERNATE_READINGS.iter().find(|(k, _)| *k == self.kana).map(|(_, r)| *r).unwrap_or(&[]);
+        std::iter::once(self.romaji).chain(alternates.iter().copied())
+    }
+}
+
 pub const KANA_DATA: &[KanaPair] = &[
     // --- HIRAGANA ---
     // Seion (Basic)
@@ -231,7 +255,43 @@ pub const KANA_DATA: &[KanaPair] = &[
     KanaPair { kana: "ピョ", romaji: "pyo" },
 ];
 
+// 浊音和半浊音在 Unicode 里紧跟在清音后面：が = か + 1，ぱ = は + 2；半浊音只有 は 行。
+// is_seion 由调用方给，清音本身要先单独判断（か - 1 恰好是 お）
+pub fn unvoiced(c: char, is_seion: impl Fn(char) -> bool) -> Option<(char, bool)> {
+    for (offset, handakuten) in [(1, false), (2, true)] {
+        let Some(base) = (c as u32).checked_sub(offset).and_then(char::from_u32) else { continue };
+        if is_seion(base) && (!handakuten || "はひふへほハヒフヘホ".contains(base)) {
+            return Some((base, handakuten));
+        }
+    }
+    None
+}
+
+// 按 KANA_DATA 把一串假名切成一拍一拍，拗音两个字算一拍，先试长的；空白跳过。
+// 有字切不出来时返回 Err(那个字)
+pub fn split_kana(text: &str) -> Result<Vec<&'static KanaPair>, char> {
+    let chars: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let found = [2, 1].into_iter().filter(|n| i + n <= chars.len()).find_map(|n| {
+            let piece: String = chars[i..i + n].iter().collect();
+            KANA_DATA.iter().find(|k| k.kana == piece).map(|k| (k, n))
+        });
+        let Some((pair, n)) = found else { return Err(chars[i]) };
+        out.push(pair);
+        i += n;
+    }
+    Ok(out)
+}
+
+// 能写成 reading 的所有假名（标准写法和其他写法都算），平片假名各算一个
+pub fn kana_with_reading(reading: &str) -> Vec<&'static KanaPair> {
+    let Some(kana) = romaji_to_kana(reading).filter(|k| !k.is_empty()) else { return Vec::new() };
+    KANA_DATA.iter().filter(|k| k.readings().any(|r| romaji_to_kana(r).as_deref() == Some(kana.as_str()))).collect()
+}
+
 // 必须添加这个函数，否则 db.rs 无法调用
 pub fn get_all_kana() -> Vec<(&'static str, &'static str)> {
     KANA_DATA.iter().map(|k| (k.kana, k.romaji)).collect()
-}
\ No newline at end of file
+}
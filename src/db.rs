@@ -1,27 +1,123 @@
-use sqlx::{SqlitePool, FromRow};
+use sqlx::{SqliteConnection, SqlitePool, FromRow};
 use serde::{Serialize, Deserialize};
+use utoipa::ToSchema;
 use anyhow::Result;
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::str::FromStr;
+use crate::config::{LeechAction, SchedulerConfig};
+use crate::error::CardNotFound;
 use crate::data::get_all_kana; // 引入数据源
+use crate::mnemonic::default_mnemonic;
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
-pub struct Card {
-    pub id: String,
+pub use kana_types::{ApiTokenInfo, Card, CardQuery, Deck, Grade};
+
+// 导入/新建卡片时用，id 由数据库层生成
+pub struct NewCard {
     pub kana_char: String,
     pub romaji: String,
+    pub meaning: Option<String>,
+    pub tags: String,
+    pub mnemonic: Option<String>,
     pub stability: f64,
     pub difficulty: f64,
     pub last_review: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ReviewEntry {
+    pub id: String,
+    pub card_id: String,
+    pub reviewed_at: String,
+    pub correct: bool,
+    // "local" 表示本应用内的复习，"anki" 表示从 .apkg 导入的历史
+    pub source: String,
+    // 从出卡到提交的毫秒数；旧记录和没有计时的客户端为空
+    #[serde(default)]
+    pub response_ms: Option<i64>,
+}
+
+// 备份里的卡组，只带成员卡片的 id
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BackupDeck {
+    pub id: String,
+    pub name: String,
+    pub card_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReviewOutcome {
+    // 距下次到期的天数：到期条件是距上次复习满 stability 天，这里向上取整
+    pub new_interval: i64,
+    pub grade: Grade,
+    // 这次遗忘让卡片达到了 leech 阈值
+    pub leech: bool,
+}
+
+pub const LEECH_TAG: &str = "leech";
+
+// settings 里记一下默认助记已经补过了
+const MNEMONICS_SEEDED_KEY: &str = "mnemonics_seeded";
+
+// 某个假名答对时的反应时间中位数
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct KanaLatency {
+    pub kana_char: String,
+    pub romaji: String,
+    pub median_ms: i64,
+    pub samples: usize,
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct MergeReport {
+    pub cards_added: usize,
+    pub cards_updated: usize,
+    pub reviews_added: usize,
+    pub settings_updated: usize,
+    pub decks_merged: usize,
+}
+
+pub const DEFAULT_DATABASE_URL: &str = "sqlite:japanese_test.db";
+
+// 没复习过，或者距上次复习已经过了 stability 天
+const DUE_CONDITION: &str = "(last_review IS NULL OR julianday('now') - julianday(last_review) >= stability)";
+
+// 能被抽到的卡：没停用、没被埋，指定了卡组时还得在卡组里（调用方把卡组 id 绑定为最后一个参数）
+fn practice_condition(deck: Option<&str>) -> &'static str {
+    match deck {
+        Some(_) => "suspended = 0 AND (buried_until IS NULL OR buried_until <= CURRENT_TIMESTAMP) \
+                    AND id IN (SELECT card_id FROM deck_cards WHERE deck_id = ?)",
+        None => "suspended = 0 AND (buried_until IS NULL OR buried_until <= CURRENT_TIMESTAMP)",
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct DbStats {
+    pub total_cards: usize,
+    pub reviewed_cards: usize,
+    pub total_reviews: usize,
+    pub correct_reviews: usize,
+    pub last_review: Option<String>,
+    // 难度最高的几张卡：(假名, 罗马音, difficulty)
+    pub hardest: Vec<(String, String, f64)>,
+    // 反应最慢的几个假名
+    pub slowest: Vec<KanaLatency>,
+}
+
 pub struct Db {
     pool: SqlitePool,
+    scheduler: SchedulerConfig,
 }
 
 impl Db {
-    pub async fn new() -> Result<Self> {
-        let database_url = std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite:japanese_test.db".to_string());
+    pub async fn connect(database_url: &str) -> Result<Self> {
+        // 允许直接传文件路径，例如 --database kana.db
+        let database_url = if database_url.starts_with("sqlite:") {
+            database_url.to_string()
+        } else {
+            format!("sqlite:{}", database_url)
+        };
 
         // 1. 配置连接选项：如果文件不存在，自动创建
         let options = SqliteConnectOptions::from_str(&database_url)?
@@ -34,7 +130,25 @@ impl Db {
         // 2. 初始化表结构和数据
         Self::initialize_db(&pool).await?;
 
-        Ok(Self { pool })
+        Ok(Self { pool, scheduler: SchedulerConfig::default() })
+    }
+
+    // 测试用：内存数据库每个连接各是一份，所以只开一个连接，也不让它被回收
+    #[cfg(test)]
+    pub async fn in_memory() -> Result<Self> {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect("sqlite::memory:")
+            .await?;
+        Self::initialize_db(&pool).await?;
+        Ok(Self { pool, scheduler: SchedulerConfig::default() })
+    }
+
+    pub fn with_scheduler(mut self, scheduler: SchedulerConfig) -> Self {
+        self.scheduler = scheduler;
+        self
     }
 
     // 初始化数据库：建表 + 灌入数据
@@ -55,6 +169,71 @@ impl Db {
             .execute(pool)
             .await?;
 
+        // 旧库没有这些列，按需补上
+        Self::ensure_column(pool, "cards", "meaning", "TEXT").await?;
+        Self::ensure_column(pool, "cards", "tags", "TEXT NOT NULL DEFAULT ''").await?;
+        Self::ensure_column(pool, "cards", "suspended", "INTEGER NOT NULL DEFAULT 0").await?;
+        Self::ensure_column(pool, "cards", "lapses", "INTEGER NOT NULL DEFAULT 0").await?;
+        Self::ensure_column(pool, "cards", "buried_until", "TEXT").await?;
+        Self::ensure_column(pool, "cards", "mnemonic", "TEXT").await?;
+        Self::ensure_column(pool, "cards", "mnemonic_image", "TEXT").await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS review_log (
+                id TEXT PRIMARY KEY,
+                card_id TEXT NOT NULL,
+                reviewed_at TEXT NOT NULL,
+                correct INTEGER NOT NULL,
+                source TEXT NOT NULL DEFAULT 'local'
+            );
+            CREATE INDEX IF NOT EXISTS idx_review_log_card ON review_log(card_id);
+            CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS decks (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE,
+                created_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS deck_cards (
+                deck_id TEXT NOT NULL,
+                card_id TEXT NOT NULL,
+                PRIMARY KEY (deck_id, card_id)
+            );
+            CREATE INDEX IF NOT EXISTS idx_deck_cards_card ON deck_cards(card_id);
+            "#
+        )
+            .execute(pool)
+            .await?;
+        Self::ensure_column(pool, "review_log", "response_ms", "INTEGER").await?;
+
+        // 认证相关的表不进备份：密码和 token 只存哈希
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS auth_password (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                hash TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS auth_sessions (
+                token_hash TEXT PRIMARY KEY,
+                created_at TEXT NOT NULL,
+                expires_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS api_tokens (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                token_hash TEXT NOT NULL UNIQUE,
+                created_at TEXT NOT NULL,
+                last_used TEXT
+            );
+            "#
+        )
+            .execute(pool)
+            .await?;
+
         // 检查是否为空，如果为空则插入数据
         let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM cards")
             .fetch_one(pool)
@@ -62,34 +241,102 @@ impl Db {
 
         if count.0 == 0 {
             println!("Initializing database with seed data...");
-            let all_kana = get_all_kana();
-            let mut tx = pool.begin().await?;
+            let seeded = Self::seed_cards(pool).await?;
+            println!("Database initialized with {} cards.", seeded);
+        }
+        Self::backfill_mnemonics(pool).await?;
+
+        Ok(())
+    }
 
-            for (kana, romaji) in all_kana {
-                let id = uuid::Uuid::new_v4().to_string();
-                sqlx::query("INSERT INTO cards (id, kana_char, romaji, stability, difficulty) VALUES (?, ?, ?, ?, ?)")
+    // 加助记之前建的库补一次默认助记；只做一次，之后用户清空的不会被填回来
+    async fn backfill_mnemonics(pool: &SqlitePool) -> Result<()> {
+        let done: Option<(String,)> = sqlx::query_as("SELECT value FROM settings WHERE key = ?")
+            .bind(MNEMONICS_SEEDED_KEY)
+            .fetch_optional(pool)
+            .await?;
+        if done.is_some() {
+            return Ok(());
+        }
+
+        let cards: Vec<(String, String)> = sqlx::query_as("SELECT id, kana_char FROM cards WHERE mnemonic IS NULL")
+            .fetch_all(pool)
+            .await?;
+        let mut tx = pool.begin().await?;
+        for (id, kana) in cards {
+            if let Some(text) = default_mnemonic(&kana) {
+                sqlx::query("UPDATE cards SET mnemonic = ? WHERE id = ?")
+                    .bind(text)
                     .bind(id)
-                    .bind(kana)
-                    .bind(romaji)
-                    .bind(0.0) // 初始 stability
-                    .bind(0.0) // 初始 difficulty
                     .execute(&mut *tx)
                     .await?;
             }
-            tx.commit().await?;
-            println!("Database initialized with {} cards.", count.0);
         }
+        sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES (?, '1')")
+            .bind(MNEMONICS_SEEDED_KEY)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn seed_cards(pool: &SqlitePool) -> Result<usize> {
+        let mut tx = pool.begin().await?;
+        let seeded = Self::insert_seed_cards(&mut tx).await?;
+        tx.commit().await?;
+        Ok(seeded)
+    }
 
+    // 放在调用方的事务里，reset 时删卡和重新写入要么都成功要么都不做
+    async fn insert_seed_cards(conn: &mut SqliteConnection) -> Result<usize> {
+        let all_kana = get_all_kana();
+        for (kana, romaji) in &all_kana {
+            let id = uuid::Uuid::new_v4().to_string();
+            sqlx::query("INSERT INTO cards (id, kana_char, romaji, stability, difficulty, mnemonic) VALUES (?, ?, ?, ?, ?, ?)")
+                .bind(id)
+                .bind(kana)
+                .bind(romaji)
+                .bind(0.0) // 初始 stability
+                .bind(0.0) // 初始 difficulty
+                .bind(default_mnemonic(kana))
+                .execute(&mut *conn)
+                .await?;
+        }
+        Ok(all_kana.len())
+    }
+
+    async fn ensure_column(pool: &SqlitePool, table: &str, column: &str, definition: &str) -> Result<()> {
+        let exists: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?")
+            .bind(table)
+            .bind(column)
+            .fetch_one(pool)
+            .await?;
+
+        if exists.0 == 0 {
+            sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+                .execute(pool)
+                .await?;
+        }
         Ok(())
     }
 
     pub async fn get_count_due(&self) -> Result<i64> {
-        let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM cards")
+        let count: (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM cards WHERE {} AND {}", DUE_CONDITION, practice_condition(None)))
             .fetch_one(&self.pool)
             .await?;
         Ok(count.0)
     }
 
+    pub async fn get_due_ids(&self, deck: Option<&str>) -> Result<Vec<String>> {
+        let query = format!("SELECT id FROM cards WHERE {} AND {}", DUE_CONDITION, practice_condition(deck));
+        let mut query_builder = sqlx::query_as::<_, (String,)>(&query);
+        if let Some(deck) = deck {
+            query_builder = query_builder.bind(deck);
+        }
+        let rows = query_builder.fetch_all(&self.pool).await?;
+        Ok(rows.into_iter().map(|r| r.0).collect())
+    }
+
     pub async fn get_total_count(&self) -> Result<usize> {
         let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM cards")
             .fetch_one(&self.pool)
@@ -134,32 +381,896 @@ impl Db {
     pub async fn get_specific_batch(&self, ids: &[String]) -> Result<Vec<Card>> {
         self.get_batch_by_ids(ids).await
     }
-    #[allow(unused)]
-    pub async fn get_next_batch(&self, _seen_ids: &[String]) -> Result<Vec<Card>> {
-        Ok(Vec::new())
+    // 排除最近展示过的卡片，剩下能抽到的卡都是候选
+    pub async fn get_next_batch(&self, seen_ids: &[String], deck: Option<&str>) -> Result<Vec<Card>> {
+        let placeholders: Vec<String> = seen_ids.iter().map(|_| "?".to_string()).collect();
+        let query = format!(
+            "SELECT * FROM cards WHERE id NOT IN ({}) AND {} ORDER BY rowid",
+            placeholders.join(","),
+            practice_condition(deck)
+        );
+
+        let mut query_builder = sqlx::query_as::<_, Card>(&query);
+        for id in seen_ids {
+            query_builder = query_builder.bind(id);
+        }
+
+        if let Some(deck) = deck {
+            query_builder = query_builder.bind(deck);
+        }
+        Ok(query_builder.fetch_all(&self.pool).await?)
+    }
+
+    // 和 get_batch_by_ids 一样按输入顺序返回，但跳过停用、被埋或不在卡组里的卡
+    pub async fn get_practice_batch(&self, ids: &[String], deck: Option<&str>) -> Result<Vec<Card>> {
+        let mut cards = self.get_batch_by_ids(ids).await?;
+        // 和 CURRENT_TIMESTAMP 同一格式，可以直接按字符串比较
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        cards.retain(|c| !c.suspended && c.buried_until.as_ref().is_none_or(|until| *until <= now));
+        if let Some(deck) = deck {
+            let members: HashSet<String> = self.get_deck_card_ids(deck).await?.into_iter().collect();
+            cards.retain(|c| members.contains(&c.id));
+        }
+        Ok(cards)
     }
 
-    pub async fn update_card(&self, id: &str, correct: bool) -> Result<i64> {
+    // kana_count 是这张卡由几个假名缝成的，慢的阈值按假名个数放大
+    pub fn grade(&self, correct: bool, response_ms: Option<u64>, kana_count: usize) -> Grade {
+        let limit = self.scheduler.slow_answer_ms.saturating_mul(kana_count.max(1) as u64);
+        match (correct, response_ms) {
+            (false, _) => Grade::Again,
+            (true, Some(ms)) if limit > 0 && ms > limit => Grade::Hard,
+            (true, _) => Grade::Good,
+        }
+    }
+
+    // 缝合卡的每个假名各记一次复习，在同一个事务里：有一张找不到就都不记。
+    // response_ms 是分摊到每个假名的用时
+    pub async fn update_cards(&self, reviews: &[(String, Grade)], response_ms: Option<u64>) -> Result<Vec<ReviewOutcome>> {
         let mut tx = self.pool.begin().await?;
+        let mut outcomes = Vec::with_capacity(reviews.len());
+        for (id, grade) in reviews {
+            outcomes.push(self.review_card(&mut tx, id, *grade, response_ms).await?);
+        }
+        tx.commit().await?;
+        Ok(outcomes)
+    }
 
+    async fn review_card(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        id: &str,
+        grade: Grade,
+        response_ms: Option<u64>,
+    ) -> Result<ReviewOutcome> {
         let card_res: Option<Card> = sqlx::query_as("SELECT * FROM cards WHERE id = ?")
             .bind(id)
-            .fetch_optional(&mut *tx)
+            .fetch_optional(&mut **tx)
             .await?;
 
-        if let Some(card) = card_res {
-            let new_stability = if correct { card.stability * 1.5 } else { card.stability * 0.8 };
-            let new_difficulty = if correct { card.difficulty - 0.1 } else { card.difficulty + 0.2 };
+        // 卡片可能已经被删掉了，调用方据此返回 404
+        let Some(card) = card_res else {
+            return Err(CardNotFound(id.to_string()).into());
+        };
+
+        let s = &self.scheduler;
+        // 新卡的 stability 是 0，只靠乘法永远是 0，每次都算到期；第一次复习先给一个起始值
+        let stability = if card.stability > 0.0 { card.stability } else { s.initial_stability };
+        let (new_stability, new_difficulty) = match grade {
+            Grade::Again => (stability * s.lapse_factor, card.difficulty + s.difficulty_step_up),
+            Grade::Hard => (stability * s.hard_factor, card.difficulty),
+            Grade::Good => (stability * s.success_factor, card.difficulty - s.difficulty_step_down),
+        };
+
+        // 复习过的卡答错才算遗忘，新卡第一次答错不算
+        let lapsed = grade == Grade::Again && card.last_review.is_some();
+        let lapses = card.lapses + i64::from(lapsed);
+
+        sqlx::query("UPDATE cards SET stability = ?, difficulty = ?, last_review = CURRENT_TIMESTAMP, lapses = ? WHERE id = ?")
+            .bind(new_stability)
+            .bind(new_difficulty)
+            .bind(lapses)
+            .bind(id)
+            .execute(&mut **tx)
+            .await?;
+
+        // 已经带 leech 标签的不再重复处理；用户去掉标签后再遗忘一次会重新触发
+        let leech = lapsed
+            && s.leech_action != LeechAction::Off
+            && lapses >= s.leech_threshold
+            && !card.tags.split_whitespace().any(|t| t == LEECH_TAG);
+        if leech {
+            self.mark_leech(tx, &card).await?;
+        }
+
+        Self::log_review(tx, id, grade != Grade::Again, response_ms).await?;
+
+        Ok(ReviewOutcome { new_interval: new_stability.max(0.0).ceil() as i64, grade, leech })
+    }
+
+    async fn mark_leech(&self, tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, card: &Card) -> Result<()> {
+        let tags = if card.tags.trim().is_empty() { LEECH_TAG.to_string() } else { format!("{} {}", card.tags.trim(), LEECH_TAG) };
+        sqlx::query("UPDATE cards SET tags = ?, suspended = suspended OR ? WHERE id = ?")
+            .bind(tags)
+            .bind(self.scheduler.leech_action == LeechAction::Suspend)
+            .bind(&card.id)
+            .execute(&mut **tx)
+            .await?;
+
+        if self.scheduler.leech_action == LeechAction::Drill {
+            let name = self.scheduler.leech_deck.trim();
+            let existing: Option<(String,)> = sqlx::query_as("SELECT id FROM decks WHERE name = ?")
+                .bind(name)
+                .fetch_optional(&mut **tx)
+                .await?;
+            let deck_id = match existing {
+                Some((id,)) => id,
+                None => {
+                    let id = uuid::Uuid::new_v4().to_string();
+                    sqlx::query("INSERT INTO decks (id, name, created_at) VALUES (?, ?, CURRENT_TIMESTAMP)")
+                        .bind(&id)
+                        .bind(name)
+                        .execute(&mut **tx)
+                        .await?;
+                    id
+                }
+            };
+            sqlx::query("INSERT OR IGNORE INTO deck_cards (deck_id, card_id) VALUES (?, ?)")
+                .bind(deck_id)
+                .bind(&card.id)
+                .execute(&mut **tx)
+                .await?;
+        }
+
+        log::info!("card {} ({}) became a leech after {} lapses", card.id, card.kana_char, card.lapses + 1);
+        Ok(())
+    }
+
+    async fn log_review(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, card_id: &str, correct: bool, response_ms: Option<u64>) -> Result<()> {
+        sqlx::query("INSERT INTO review_log (id, card_id, reviewed_at, correct, source, response_ms) VALUES (?, ?, CURRENT_TIMESTAMP, ?, 'local', ?)")
+            .bind(uuid::Uuid::new_v4().to_string())
+            .bind(card_id)
+            .bind(correct)
+            .bind(response_ms.map(|ms| ms as i64))
+            .execute(&mut **tx)
+            .await?;
+        Ok(())
+    }
+
+    // 导入时整批写入放在一个事务里，配合那几个接收 conn 的函数用
+    pub async fn begin(&self) -> Result<sqlx::Transaction<'static, sqlx::Sqlite>> {
+        Ok(self.pool.begin().await?)
+    }
+
+    pub async fn find_card_by_kana(&self, kana: &str) -> Result<Option<Card>> {
+        Self::card_by_kana(&mut *self.pool.acquire().await?, kana).await
+    }
+
+    pub async fn card_by_kana(conn: &mut SqliteConnection, kana: &str) -> Result<Option<Card>> {
+        let card = sqlx::query_as::<_, Card>("SELECT * FROM cards WHERE kana_char = ? LIMIT 1")
+            .bind(kana)
+            .fetch_optional(&mut *conn)
+            .await?;
+        Ok(card)
+    }
+
+    pub async fn insert_card(&self, card: &NewCard) -> Result<String> {
+        Self::insert_new_card(&mut *self.pool.acquire().await?, card).await
+    }
+
+    pub async fn insert_new_card(conn: &mut SqliteConnection, card: &NewCard) -> Result<String> {
+        let id = uuid::Uuid::new_v4().to_string();
+        sqlx::query("INSERT INTO cards (id, kana_char, romaji, stability, difficulty, last_review, meaning, tags, mnemonic) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)")
+            .bind(&id)
+            .bind(&card.kana_char)
+            .bind(&card.romaji)
+            .bind(card.stability)
+            .bind(card.difficulty)
+            .bind(&card.last_review)
+            .bind(&card.meaning)
+            .bind(&card.tags)
+            .bind(&card.mnemonic)
+            .execute(&mut *conn)
+            .await?;
+        Ok(id)
+    }
+
+    pub async fn get_card(&self, id: &str) -> Result<Option<Card>> {
+        let card = sqlx::query_as::<_, Card>("SELECT * FROM cards WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(card)
+    }
+
+    // 卡片管理页用；按插入顺序，默认假名保持五十音顺序
+    pub async fn list_cards(&self, filter: &CardQuery) -> Result<Vec<Card>> {
+        let mut conditions = Vec::new();
+        if filter.deck.is_some() {
+            conditions.push("id IN (SELECT card_id FROM deck_cards WHERE deck_id = ?)");
+        }
+        if filter.tag.is_some() {
+            conditions.push("instr(' ' || tags || ' ', ' ' || ? || ' ') > 0");
+        }
+        if filter.q.is_some() {
+            conditions.push("(kana_char LIKE ? OR romaji LIKE ? OR IFNULL(meaning, '') LIKE ?)");
+        }
+        if filter.suspended.is_some() {
+            conditions.push("suspended = ?");
+        }
+
+        let mut query = "SELECT * FROM cards".to_string();
+        if !conditions.is_empty() {
+            query.push_str(" WHERE ");
+            query.push_str(&conditions.join(" AND "));
+        }
+        query.push_str(" ORDER BY rowid");
+
+        let mut query_builder = sqlx::query_as::<_, Card>(&query);
+        if let Some(deck) = &filter.deck {
+            query_builder = query_builder.bind(deck);
+        }
+        if let Some(tag) = &filter.tag {
+            query_builder = query_builder.bind(tag);
+        }
+        if let Some(q) = &filter.q {
+            let pattern = format!("%{}%", q.trim());
+            for _ in 0..3 {
+                query_builder = query_builder.bind(pattern.clone());
+            }
+        }
+        if let Some(suspended) = filter.suspended {
+            query_builder = query_builder.bind(suspended);
+        }
+        Ok(query_builder.fetch_all(&self.pool).await?)
+    }
+
+    // 只写可编辑的字段，调度状态不动
+    pub async fn save_card(&self, card: &Card) -> Result<()> {
+        let res = sqlx::query("UPDATE cards SET kana_char = ?, romaji = ?, meaning = ?, tags = ?, suspended = ?, mnemonic = ? WHERE id = ?")
+            .bind(&card.kana_char)
+            .bind(&card.romaji)
+            .bind(&card.meaning)
+            .bind(&card.tags)
+            .bind(card.suspended)
+            .bind(&card.mnemonic)
+            .bind(&card.id)
+            .execute(&self.pool)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Err(CardNotFound(card.id.clone()).into());
+        }
+        Ok(())
+    }
+
+    // 连同复习记录和卡组里的位置一起删掉
+    pub async fn delete_card(&self, id: &str) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        for query in ["DELETE FROM review_log WHERE card_id = ?", "DELETE FROM deck_cards WHERE card_id = ?"] {
+            sqlx::query(query).bind(id).execute(&mut *tx).await?;
+        }
+        let res = sqlx::query("DELETE FROM cards WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    // file 为 None 时清掉图片；返回 false 表示卡片不存在
+    pub async fn set_mnemonic_image(&self, id: &str, file: Option<&str>) -> Result<bool> {
+        let res = sqlx::query("UPDATE cards SET mnemonic_image = ? WHERE id = ?")
+            .bind(file)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn set_suspended(&self, id: &str, suspended: bool) -> Result<bool> {
+        let res = sqlx::query("UPDATE cards SET suspended = ? WHERE id = ?")
+            .bind(suspended)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    // 埋到本地时间的明天零点，存成 UTC
+    pub async fn bury_card(&self, id: &str) -> Result<bool> {
+        let res = sqlx::query("UPDATE cards SET buried_until = datetime('now', 'localtime', 'start of day', '+1 day', 'utc') WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn unbury_card(&self, id: &str) -> Result<bool> {
+        let res = sqlx::query("UPDATE cards SET buried_until = NULL WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn list_decks(&self) -> Result<Vec<Deck>> {
+        let decks = sqlx::query_as::<_, Deck>(
+            "SELECT d.id, d.name, d.created_at, COUNT(dc.card_id) AS card_count
+             FROM decks d LEFT JOIN deck_cards dc ON dc.deck_id = d.id
+             GROUP BY d.id ORDER BY d.created_at, d.name"
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(decks)
+    }
+
+    pub async fn get_deck(&self, id: &str) -> Result<Option<Deck>> {
+        Ok(self.list_decks().await?.into_iter().find(|d| d.id == id))
+    }
+
+    pub async fn find_deck_by_name(&self, name: &str) -> Result<Option<Deck>> {
+        Ok(self.list_decks().await?.into_iter().find(|d| d.name == name))
+    }
 
-            sqlx::query("UPDATE cards SET stability = ?, difficulty = ?, last_review = CURRENT_TIMESTAMP WHERE id = ?")
-                .bind(new_stability)
-                .bind(new_difficulty)
+    pub async fn create_deck(&self, name: &str) -> Result<String> {
+        let id = uuid::Uuid::new_v4().to_string();
+        sqlx::query("INSERT INTO decks (id, name, created_at) VALUES (?, ?, CURRENT_TIMESTAMP)")
+            .bind(&id)
+            .bind(name)
+            .execute(&self.pool)
+            .await?;
+        Ok(id)
+    }
+
+    pub async fn rename_deck(&self, id: &str, name: &str) -> Result<bool> {
+        let res = sqlx::query("UPDATE decks SET name = ? WHERE id = ?")
+            .bind(name)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    // 只删卡组和成员关系，卡片本身不动
+    pub async fn delete_deck(&self, id: &str) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM deck_cards WHERE deck_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        let res = sqlx::query("DELETE FROM decks WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn get_deck_card_ids(&self, deck_id: &str) -> Result<Vec<String>> {
+        let rows: Vec<(String,)> = sqlx::query_as("SELECT card_id FROM deck_cards WHERE deck_id = ? ORDER BY rowid")
+            .bind(deck_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().map(|r| r.0).collect())
+    }
+
+    // 已经在卡组里的卡跳过，返回新加了几张
+    pub async fn add_deck_cards(&self, deck_id: &str, card_ids: &[String]) -> Result<usize> {
+        let mut tx = self.pool.begin().await?;
+        let mut added = 0;
+        for card_id in card_ids {
+            let res = sqlx::query("INSERT OR IGNORE INTO deck_cards (deck_id, card_id) VALUES (?, ?)")
+                .bind(deck_id)
+                .bind(card_id)
+                .execute(&mut *tx)
+                .await?;
+            added += res.rows_affected() as usize;
+        }
+        tx.commit().await?;
+        Ok(added)
+    }
+
+    pub async fn set_deck_cards(&self, deck_id: &str, card_ids: &[String]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM deck_cards WHERE deck_id = ?")
+            .bind(deck_id)
+            .execute(&mut *tx)
+            .await?;
+        for card_id in card_ids {
+            sqlx::query("INSERT OR IGNORE INTO deck_cards (deck_id, card_id) VALUES (?, ?)")
+                .bind(deck_id)
+                .bind(card_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn remove_deck_card(&self, deck_id: &str, card_id: &str) -> Result<bool> {
+        let res = sqlx::query("DELETE FROM deck_cards WHERE deck_id = ? AND card_id = ?")
+            .bind(deck_id)
+            .bind(card_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn set_scheduling(conn: &mut SqliteConnection, id: &str, stability: f64, difficulty: f64, last_review: Option<&str>) -> Result<()> {
+        sqlx::query("UPDATE cards SET stability = ?, difficulty = ?, last_review = ? WHERE id = ?")
+            .bind(stability)
+            .bind(difficulty)
+            .bind(last_review)
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
+        Ok(())
+    }
+
+    // 合并标签，已有的不重复添加
+    pub async fn add_tags(conn: &mut SqliteConnection, id: &str, tags: &[String]) -> Result<()> {
+        let current: Option<(String,)> = sqlx::query_as("SELECT tags FROM cards WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&mut *conn)
+            .await?;
+
+        if let Some((current,)) = current {
+            let mut merged: Vec<String> = current.split_whitespace().map(|t| t.to_string()).collect();
+            for tag in tags {
+                if !merged.contains(tag) {
+                    merged.push(tag.clone());
+                }
+            }
+            sqlx::query("UPDATE cards SET tags = ? WHERE id = ?")
+                .bind(merged.join(" "))
                 .bind(id)
+                .execute(&mut *conn)
+                .await?;
+        }
+        Ok(())
+    }
+
+    // 返回 false 表示该条记录已存在（重复导入时按 id 去重）
+    pub async fn insert_review(conn: &mut SqliteConnection, entry: &ReviewEntry) -> Result<bool> {
+        let res = sqlx::query("INSERT OR IGNORE INTO review_log (id, card_id, reviewed_at, correct, source, response_ms) VALUES (?, ?, ?, ?, ?, ?)")
+            .bind(&entry.id)
+            .bind(&entry.card_id)
+            .bind(&entry.reviewed_at)
+            .bind(entry.correct)
+            .bind(&entry.source)
+            .bind(entry.response_ms)
+            .execute(&mut *conn)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn get_all_cards(&self) -> Result<Vec<Card>> {
+        let cards = sqlx::query_as::<_, Card>("SELECT * FROM cards ORDER BY rowid")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(cards)
+    }
+
+    pub async fn get_review_log(&self) -> Result<Vec<ReviewEntry>> {
+        let entries = sqlx::query_as::<_, ReviewEntry>("SELECT * FROM review_log ORDER BY reviewed_at, id")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(entries)
+    }
+
+    pub async fn get_backup_decks(&self) -> Result<Vec<BackupDeck>> {
+        let mut decks = Vec::new();
+        for deck in self.list_decks().await? {
+            let card_ids = self.get_deck_card_ids(&deck.id).await?;
+            decks.push(BackupDeck { id: deck.id, name: deck.name, card_ids });
+        }
+        Ok(decks)
+    }
+
+    pub async fn get_settings(&self) -> Result<BTreeMap<String, String>> {
+        let rows: Vec<(String, String)> = sqlx::query_as("SELECT key, value FROM settings")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    pub async fn get_setting(&self, key: &str) -> Result<Option<String>> {
+        let row: Option<(String,)> = sqlx::query_as("SELECT value FROM settings WHERE key = ?")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|r| r.0))
+    }
+
+    pub async fn set_setting(&self, key: &str, value: &str) -> Result<()> {
+        sqlx::query("INSERT INTO settings (key, value) VALUES (?, ?) ON CONFLICT(key) DO UPDATE SET value = excluded.value")
+            .bind(key)
+            .bind(value)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_password_hash(&self) -> Result<Option<String>> {
+        let row: Option<(String,)> = sqlx::query_as("SELECT hash FROM auth_password WHERE id = 1")
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|r| r.0))
+    }
+
+    // 换密码时顺便让所有已登录的浏览器失效
+    pub async fn set_password_hash(&self, hash: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("INSERT INTO auth_password (id, hash, updated_at) VALUES (1, ?, CURRENT_TIMESTAMP) ON CONFLICT(id) DO UPDATE SET hash = excluded.hash, updated_at = excluded.updated_at")
+            .bind(hash)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM auth_sessions")
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn create_auth_session(&self, token_hash: &str, ttl_secs: u64) -> Result<()> {
+        sqlx::query("DELETE FROM auth_sessions WHERE expires_at <= CURRENT_TIMESTAMP")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("INSERT INTO auth_sessions (token_hash, created_at, expires_at) VALUES (?, CURRENT_TIMESTAMP, datetime('now', ?))")
+            .bind(token_hash)
+            .bind(format!("+{} seconds", ttl_secs))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn auth_session_valid(&self, token_hash: &str) -> Result<bool> {
+        let row: Option<(i64,)> = sqlx::query_as("SELECT 1 FROM auth_sessions WHERE token_hash = ? AND expires_at > CURRENT_TIMESTAMP")
+            .bind(token_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.is_some())
+    }
+
+    pub async fn delete_auth_session(&self, token_hash: &str) -> Result<()> {
+        sqlx::query("DELETE FROM auth_sessions WHERE token_hash = ?")
+            .bind(token_hash)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn insert_api_token(&self, name: &str, token_hash: &str) -> Result<String> {
+        let id = uuid::Uuid::new_v4().to_string();
+        sqlx::query("INSERT INTO api_tokens (id, name, token_hash, created_at) VALUES (?, ?, ?, CURRENT_TIMESTAMP)")
+            .bind(&id)
+            .bind(name)
+            .bind(token_hash)
+            .execute(&self.pool)
+            .await?;
+        Ok(id)
+    }
+
+    pub async fn list_api_tokens(&self) -> Result<Vec<ApiTokenInfo>> {
+        let tokens = sqlx::query_as::<_, ApiTokenInfo>("SELECT id, name, created_at, last_used FROM api_tokens ORDER BY created_at, id")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(tokens)
+    }
+
+    // token 有效时顺便记下使用时间
+    pub async fn touch_api_token(&self, token_hash: &str) -> Result<bool> {
+        let res = sqlx::query("UPDATE api_tokens SET last_used = CURRENT_TIMESTAMP WHERE token_hash = ?")
+            .bind(token_hash)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn delete_api_token(&self, id: &str) -> Result<bool> {
+        let res = sqlx::query("DELETE FROM api_tokens WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    // 把备份合并进当前库：卡片按 id（其次按假名）合并，较新的 last_review 胜出，复习记录按 id 去重，设置直接覆盖。
+    // 整个过程在一个事务里，失败不会留下半份数据。
+    pub async fn merge_backup(
+        &self,
+        cards: &[Card],
+        reviews: &[ReviewEntry],
+        decks: &[BackupDeck],
+        settings: &BTreeMap<String, String>,
+    ) -> Result<MergeReport> {
+        let mut report = MergeReport::default();
+        let mut remapped_ids: HashMap<&str, String> = HashMap::new();
+        let mut tx = self.pool.begin().await?;
+
+        for card in cards {
+            let mut existing: Option<Card> = sqlx::query_as("SELECT * FROM cards WHERE id = ?")
+                .bind(&card.id)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+            // 新库初始化时种子卡片的 id 是随机生成的，按假名对上同一张卡
+            if existing.is_none() {
+                existing = sqlx::query_as("SELECT * FROM cards WHERE kana_char = ? LIMIT 1")
+                    .bind(&card.kana_char)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+                if let Some(found) = &existing {
+                    remapped_ids.insert(card.id.as_str(), found.id.clone());
+                }
+            }
+
+            match existing {
+                None => {
+                    sqlx::query("INSERT INTO cards (id, kana_char, romaji, stability, difficulty, last_review, meaning, tags, suspended, lapses, buried_until, mnemonic) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+                        .bind(&card.id)
+                        .bind(&card.kana_char)
+                        .bind(&card.romaji)
+                        .bind(card.stability)
+                        .bind(card.difficulty)
+                        .bind(&card.last_review)
+                        .bind(&card.meaning)
+                        .bind(&card.tags)
+                        .bind(card.suspended)
+                        .bind(card.lapses)
+                        .bind(&card.buried_until)
+                        .bind(&card.mnemonic)
+                        .execute(&mut *tx)
+                        .await?;
+                    report.cards_added += 1;
+                }
+                Some(existing) => {
+                    // "YYYY-MM-DD HH:MM:SS" 可以直接按字符串比较
+                    let incoming_newer = card.last_review > existing.last_review;
+                    let (stability, difficulty, last_review) = if incoming_newer {
+                        (card.stability, card.difficulty, card.last_review.clone())
+                    } else {
+                        (existing.stability, existing.difficulty, existing.last_review.clone())
+                    };
+                    // 停用、埋卡状态也跟着较新的一边走，不然恢复到新装的库里停用的卡全都回来了；
+                    // 两边一样新（比如都没复习过）时哪边停用了就算停用，埋到较晚的那个时间
+                    let (suspended, buried_until) = match card.last_review.cmp(&existing.last_review) {
+                        Ordering::Greater => (card.suspended, card.buried_until.clone()),
+                        Ordering::Less => (existing.suspended, existing.buried_until.clone()),
+                        Ordering::Equal => (
+                            existing.suspended || card.suspended,
+                            existing.buried_until.clone().max(card.buried_until.clone()),
+                        ),
+                    };
+                    // 遗忘次数取大的，和保留下来的 leech 标签对得上
+                    let lapses = existing.lapses.max(card.lapses);
+
+                    let mut tags: Vec<&str> = existing.tags.split_whitespace().collect();
+                    for tag in card.tags.split_whitespace() {
+                        if !tags.contains(&tag) {
+                            tags.push(tag);
+                        }
+                    }
+                    let tags = tags.join(" ");
+                    let meaning = existing.meaning.clone().or_else(|| card.meaning.clone());
+                    let mnemonic = existing.mnemonic.clone().or_else(|| card.mnemonic.clone());
+
+                    let changed = incoming_newer
+                        || suspended != existing.suspended
+                        || buried_until != existing.buried_until
+                        || lapses != existing.lapses
+                        || tags != existing.tags
+                        || meaning != existing.meaning
+                        || mnemonic != existing.mnemonic;
+                    if changed {
+                        sqlx::query("UPDATE cards SET stability = ?, difficulty = ?, last_review = ?, suspended = ?, lapses = ?, buried_until = ?, meaning = ?, tags = ?, mnemonic = ? WHERE id = ?")
+                            .bind(stability)
+                            .bind(difficulty)
+                            .bind(last_review)
+                            .bind(suspended)
+                            .bind(lapses)
+                            .bind(buried_until)
+                            .bind(meaning)
+                            .bind(tags)
+                            .bind(mnemonic)
+                            .bind(&existing.id)
+                            .execute(&mut *tx)
+                            .await?;
+                        report.cards_updated += 1;
+                    }
+                }
+            }
+        }
+
+        for entry in reviews {
+            let card_id = remapped_ids.get(entry.card_id.as_str()).unwrap_or(&entry.card_id);
+            let res = sqlx::query("INSERT OR IGNORE INTO review_log (id, card_id, reviewed_at, correct, source, response_ms) VALUES (?, ?, ?, ?, ?, ?)")
+                .bind(&entry.id)
+                .bind(card_id)
+                .bind(&entry.reviewed_at)
+                .bind(entry.correct)
+                .bind(&entry.source)
+                .bind(entry.response_ms)
+                .execute(&mut *tx)
+                .await?;
+            report.reviews_added += res.rows_affected() as usize;
+        }
+
+        // 同 id 的卡组沿用本地那个，不然按名字对上；成员只增不减
+        for deck in decks {
+            let mut existing: Option<(String,)> = sqlx::query_as("SELECT id FROM decks WHERE id = ?")
+                .bind(&deck.id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            if existing.is_none() {
+                existing = sqlx::query_as("SELECT id FROM decks WHERE name = ?")
+                    .bind(&deck.name)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+            }
+            let deck_id = match existing {
+                Some((id,)) => id,
+                None => {
+                    sqlx::query("INSERT INTO decks (id, name, created_at) VALUES (?, ?, CURRENT_TIMESTAMP)")
+                        .bind(&deck.id)
+                        .bind(&deck.name)
+                        .execute(&mut *tx)
+                        .await?;
+                    deck.id.clone()
+                }
+            };
+            for card_id in &deck.card_ids {
+                let card_id = remapped_ids.get(card_id.as_str()).unwrap_or(card_id);
+                sqlx::query("INSERT OR IGNORE INTO deck_cards (deck_id, card_id) VALUES (?, ?)")
+                    .bind(&deck_id)
+                    .bind(card_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            report.decks_merged += 1;
+        }
+
+        for (key, value) in settings {
+            sqlx::query("INSERT INTO settings (key, value) VALUES (?, ?) ON CONFLICT(key) DO UPDATE SET value = excluded.value")
+                .bind(key)
+                .bind(value)
                 .execute(&mut *tx)
                 .await?;
+            report.settings_updated += 1;
         }
 
         tx.commit().await?;
-        Ok(0)
+        Ok(report)
+    }
+
+    pub async fn stats(&self) -> Result<DbStats> {
+        let (total_cards, reviewed_cards): (i64, i64) =
+            sqlx::query_as("SELECT COUNT(*), COUNT(last_review) FROM cards")
+                .fetch_one(&self.pool)
+                .await?;
+        let (total_reviews, correct_reviews, last_review): (i64, Option<i64>, Option<String>) =
+            sqlx::query_as("SELECT COUNT(*), SUM(correct), MAX(reviewed_at) FROM review_log")
+                .fetch_one(&self.pool)
+                .await?;
+        let hardest: Vec<(String, String, f64)> = sqlx::query_as(
+            "SELECT kana_char, romaji, difficulty FROM cards WHERE last_review IS NOT NULL ORDER BY difficulty DESC LIMIT 10"
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        let slowest = self.latency_by_kana().await?.into_iter().take(10).collect();
+
+        Ok(DbStats {
+            total_cards: total_cards as usize,
+            reviewed_cards: reviewed_cards as usize,
+            total_reviews: total_reviews as usize,
+            correct_reviews: correct_reviews.unwrap_or(0) as usize,
+            last_review,
+            hardest,
+            slowest,
+        })
     }
-}
\ No newline at end of file
+
+    // 只看答对的记录，按中位数从慢到快排
+    pub async fn latency_by_kana(&self) -> Result<Vec<KanaLatency>> {
+        let rows: Vec<(String, String, i64)> = sqlx::query_as(
+            "SELECT c.kana_char, c.romaji, r.response_ms FROM review_log r JOIN cards c ON c.id = r.card_id \
+             WHERE r.correct = 1 AND r.response_ms IS NOT NULL ORDER BY c.kana_char, r.response_ms"
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut latencies: Vec<KanaLatency> = Vec::new();
+        let mut samples: Vec<i64> = Vec::new();
+        let mut rows = rows.into_iter().peekable();
+        while let Some((kana_char, romaji, ms)) = rows.next() {
+            samples.push(ms);
+            if rows.peek().map(|r| &r.0) == Some(&kana_char) {
+                continue;
+            }
+            // 已经按毫秒排好序
+            let mid = samples.len() / 2;
+            let median_ms = if samples.len().is_multiple_of(2) { (samples[mid - 1] + samples[mid]) / 2 } else { samples[mid] };
+            latencies.push(KanaLatency { kana_char, romaji, median_ms, samples: samples.len() });
+            samples.clear();
+        }
+
+        latencies.sort_by_key(|l| std::cmp::Reverse(l.median_ms));
+        Ok(latencies)
+    }
+
+    // 清空学习进度；reseed 为 true 时连卡片一起删掉，重新灌入默认假名
+    pub async fn reset(&self, reseed: bool) -> Result<()> {
+        // 中途失败时不能留下删了复习记录却没重置卡片，或者卡片删了没写回去的半截状态
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM review_log").execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM settings WHERE key = ?")
+            .bind(crate::recent::RECENT_SETTING_KEY)
+            .execute(&mut *tx)
+            .await?;
+
+        if reseed {
+            sqlx::query("DELETE FROM deck_cards").execute(&mut *tx).await?;
+            sqlx::query("DELETE FROM cards").execute(&mut *tx).await?;
+            Self::insert_seed_cards(&mut tx).await?;
+        } else {
+            sqlx::query("UPDATE cards SET stability = 0.0, difficulty = 0.0, last_review = NULL, lapses = 0, buried_until = NULL")
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn optimize(&self) -> Result<()> {
+        sqlx::query("PRAGMA optimize").execute(&self.pool).await?;
+        sqlx::query("ANALYZE").execute(&self.pool).await?;
+        sqlx::query("VACUUM").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    // 返回发现的问题列表，空表示数据库健康
+    pub async fn check(&self) -> Result<Vec<String>> {
+        let mut problems = Vec::new();
+
+        let integrity: Vec<(String,)> = sqlx::query_as("PRAGMA integrity_check")
+            .fetch_all(&self.pool)
+            .await?;
+        for (line,) in integrity {
+            if line != "ok" {
+                problems.push(format!("integrity: {}", line));
+            }
+        }
+
+        let orphans: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM review_log WHERE card_id NOT IN (SELECT id FROM cards)")
+            .fetch_one(&self.pool)
+            .await?;
+        if orphans.0 > 0 {
+            problems.push(format!("{} review log entries reference missing cards", orphans.0));
+        }
+
+        let empty: Vec<(String,)> = sqlx::query_as("SELECT id FROM cards WHERE TRIM(kana_char) = '' OR TRIM(romaji) = ''")
+            .fetch_all(&self.pool)
+            .await?;
+        for (id,) in empty {
+            problems.push(format!("card {} has an empty kana_char or romaji", id));
+        }
+
+        let duplicates: Vec<(String, i64)> = sqlx::query_as("SELECT kana_char, COUNT(*) FROM cards GROUP BY kana_char HAVING COUNT(*) > 1")
+            .fetch_all(&self.pool)
+            .await?;
+        for (kana, n) in duplicates {
+            problems.push(format!("kana '{}' appears on {} cards", kana, n));
+        }
+
+        let non_finite: (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM cards WHERE stability IS NULL OR difficulty IS NULL OR ABS(stability) > 1e300 OR ABS(difficulty) > 1e300"
+        )
+            .fetch_one(&self.pool)
+            .await?;
+        if non_finite.0 > 0 {
+            problems.push(format!("{} cards have invalid scheduling state", non_finite.0));
+        }
+
+        Ok(problems)
+    }
+}
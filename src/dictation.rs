@@ -0,0 +1,100 @@
+use kana_types::DictationSegment;
+
+use crate::reading::{align, sounds_like};
+
+// 听写按拍判分，每一拍按读音比，假名和罗马音（包括 di、n' 这类其他写法）都收。
+// 题目里有字不是内置假名时返回 Err(那个字)
+pub fn grade(kana: &str, answer: &str) -> Result<Vec<DictationSegment>, char> {
+    align(kana, answer, sounds_like)
+}
+
+// verdict 里的说明：哪几拍听错了
+pub fn explain(segments: &[DictationSegment]) -> String {
+    let wrong: Vec<String> = segments
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| !s.correct)
+        .map(|(i, s)| {
+            if s.answer.is_empty() {
+                format!("第 {} 拍 {}（{}）漏写了", i + 1, s.kana, s.expected)
+            } else {
+                format!("第 {} 拍 {}（{}）写成了 '{}'", i + 1, s.kana, s.expected, s.answer)
+            }
+        })
+        .collect();
+    format!("{}。", wrong.join("；"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answers(segments: &[DictationSegment]) -> Vec<(&str, bool)> {
+        segments.iter().map(|s| (s.answer.as_str(), s.correct)).collect()
+    }
+
+    #[test]
+    fn every_mora_right() {
+        for answer in ["kakiku", "ka ki ku", "かきく", "カキク", "kaきku"] {
+            let segments = grade("かきく", answer).unwrap();
+            assert!(segments.iter().all(|s| s.correct), "{}: {:?}", answer, segments);
+        }
+        let segments = grade("きゃりゅ", "kyaryu").unwrap();
+        assert_eq!(answers(&segments), vec![("kya", true), ("ryu", true)]);
+    }
+
+    #[test]
+    fn missing_mora_is_aligned_to_nothing() {
+        let segments = grade("かきく", "kaku").unwrap();
+        assert_eq!(answers(&segments), vec![("ka", true), ("", false), ("ku", true)]);
+        assert_eq!(explain(&segments), "第 2 拍 き（ki）漏写了。");
+
+        let segments = grade("かきく", "").unwrap();
+        assert_eq!(answers(&segments), vec![("", false), ("", false), ("", false)]);
+    }
+
+    #[test]
+    fn extra_letters_go_to_the_last_mora() {
+        let segments = grade("かきく", "kakikuu").unwrap();
+        assert_eq!(answers(&segments), vec![("ka", true), ("ki", true), ("kuu", false)]);
+    }
+
+    #[test]
+    fn wrong_mora_keeps_the_others() {
+        let segments = grade("かきく", "kakeku").unwrap();
+        assert_eq!(answers(&segments), vec![("ka", true), ("ke", false), ("ku", true)]);
+        assert_eq!(explain(&segments), "第 2 拍 き（ki）写成了 'ke'。");
+    }
+
+    #[test]
+    fn homophones_count_either_way() {
+        for answer in ["chijimi", "chidimi", "ちじみ", "ちぢみ", "tizimi"] {
+            let segments = grade("ちぢみ", answer).unwrap();
+            assert!(segments.iter().all(|s| s.correct), "{}: {:?}", answer, segments);
+        }
+        for answer in ["okashi", "wokashi", "おかし"] {
+            let segments = grade("をかし", answer).unwrap();
+            assert!(segments.iter().all(|s| s.correct), "{}: {:?}", answer, segments);
+        }
+        for answer in ["tsuzuku", "tsuduku", "つずく"] {
+            let segments = grade("つづく", answer).unwrap();
+            assert!(segments.iter().all(|s| s.correct), "{}: {:?}", answer, segments);
+        }
+        // 读音不同的不能算
+        let segments = grade("ちぢみ", "chichimi").unwrap();
+        assert_eq!(answers(&segments), vec![("chi", true), ("chi", false), ("mi", true)]);
+    }
+
+    #[test]
+    fn same_answer_same_result() {
+        let first = grade("しゃつを", "shatsu o").unwrap();
+        for _ in 0..5 {
+            assert_eq!(grade("しゃつを", "shatsu o").unwrap(), first);
+        }
+    }
+
+    #[test]
+    fn unknown_character() {
+        assert_eq!(grade("か漢", "kakan"), Err('漢'));
+    }
+}
@@ -0,0 +1,145 @@
+use std::fmt;
+
+use axum::{
+    extract::{
+        multipart::{MultipartError, MultipartRejection},
+        rejection::{JsonRejection, QueryRejection},
+        FromRequest,
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+pub use kana_types::ErrorBody;
+
+// 所有接口统一的错误类型，响应体为 {"code": "...", "message": "..."}
+#[derive(Debug)]
+pub enum ApiError {
+    // 401：没登录、密码错误或 token 无效
+    Unauthorized(String),
+    // 403：请求来源（Origin）不被允许
+    Forbidden(String),
+    // 404：卡片不存在
+    NotFound(String),
+    // 400：请求体、参数或上传的文件有问题
+    BadRequest(String),
+    // 409：票据过期，客户端应该重新取当前卡
+    StaleSession(String),
+    // 500：服务端自己的问题
+    Internal(anyhow::Error),
+}
+
+impl ApiError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Unauthorized(_) => "unauthorized",
+            Self::Forbidden(_) => "forbidden",
+            Self::NotFound(_) => "not_found",
+            Self::BadRequest(_) => "bad_request",
+            Self::StaleSession(_) => "stale_session",
+            Self::Internal(_) => "internal",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::StaleSession(_) => StatusCode::CONFLICT,
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn body(&self) -> ErrorBody {
+        ErrorBody { code: self.code().to_string(), message: self.to_string() }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unauthorized(msg) | Self::Forbidden(msg) | Self::NotFound(msg) | Self::BadRequest(msg) | Self::StaleSession(msg) => f.write_str(msg),
+            Self::Internal(e) => write!(f, "{:#}", e),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        if let Self::Internal(e) = &self {
+            log::error!("internal error: {:#}", e);
+        }
+        (self.status(), Json(self.body())).into_response()
+    }
+}
+
+// 数据库层用 CardNotFound 表示卡片不存在，导入和恢复用 InvalidData 表示文件本身有问题，其余错误都算 500
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        if let Some(not_found) = e.downcast_ref::<CardNotFound>() {
+            return Self::NotFound(not_found.to_string());
+        }
+        match e.downcast_ref::<InvalidData>() {
+            Some(invalid) => Self::BadRequest(invalid.to_string()),
+            None => Self::Internal(e),
+        }
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(e: JsonRejection) -> Self {
+        Self::BadRequest(e.body_text())
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(e: QueryRejection) -> Self {
+        Self::BadRequest(e.body_text())
+    }
+}
+
+impl From<MultipartError> for ApiError {
+    fn from(e: MultipartError) -> Self {
+        Self::BadRequest(e.body_text())
+    }
+}
+
+impl From<MultipartRejection> for ApiError {
+    fn from(e: MultipartRejection) -> Self {
+        Self::BadRequest(e.body_text())
+    }
+}
+
+#[derive(Debug)]
+pub struct CardNotFound(pub String);
+
+impl fmt::Display for CardNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "card {} not found", self.0)
+    }
+}
+
+impl std::error::Error for CardNotFound {}
+
+// 导入的文件或要恢复的备份内容不合法：解析不了、校验不过
+#[derive(Debug)]
+pub struct InvalidData(pub String);
+
+impl fmt::Display for InvalidData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for InvalidData {}
+
+// 和 axum::Json 一样，只是解析失败时返回 ApiError 的 JSON 格式
+#[derive(FromRequest)]
+#[from_request(via(axum::Json), rejection(ApiError))]
+pub struct ApiJson<T>(pub T);
+
+#[derive(axum::extract::FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(ApiError))]
+pub struct ApiQuery<T>(pub T);
@@ -1,27 +1,63 @@
-use crate::data::KANA_DATA;
+use crate::data::{kana_with_reading, KANA_DATA};
 
-#[allow(unused)]
 pub struct FeedbackGenerator;
 
-#[allow(unused)]
 impl FeedbackGenerator {
     pub fn generate_explanation(correct_kana: &str, correct_romaji: &str, user_input: &str) -> String {
         let trimmed_input = user_input.trim();
 
-        // Find if user input matches another kana
-        let confused_kana = KANA_DATA.iter().find(|k| k.romaji == trimmed_input);
-
         let mut msg = format!(
             "正确答案是 {} ({})。 你输入了: '{}'。 请继续加油！",
             correct_kana, correct_romaji, trimmed_input
         );
 
-        if let Some(confused) = confused_kana {
-            if confused.kana != correct_kana {
-                msg.push_str(&format!("\n你输入的 '{}' 对应的假名是 '{}'。", trimmed_input, confused.kana));
+        // 同一个读音可能对应好几个假名（ji 是 じ 也是 ぢ），都列出来
+        let confused: Vec<&str> = kana_with_reading(trimmed_input)
+            .into_iter()
+            .map(|k| k.kana)
+            .filter(|k| *k != correct_kana)
+            .collect();
+        if !confused.is_empty() {
+            msg.push_str(&format!("\n你输入的 '{}' 对应的假名是 {}。", trimmed_input, confused.join("、")));
+        }
+
+        // 单个假名有其他写法时提一句，免得以为只能写标准写法
+        if let Some(pair) = KANA_DATA.iter().find(|k| k.kana == correct_kana) {
+            let alternates: Vec<&str> = pair.readings().skip(1).collect();
+            if !alternates.is_empty() {
+                msg.push_str(&format!("\n{} 也可以写成 {}。", correct_kana, alternates.join(" / ")));
             }
         }
 
         msg
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ambiguous_input_lists_every_kana() {
+        let msg = FeedbackGenerator::generate_explanation("し", "shi", "ji");
+        let line = msg.lines().find(|l| l.contains("对应的假名")).unwrap();
+        assert!(line.contains('じ') && line.contains('ぢ'), "{}", msg);
+
+        // 答对的那个不再列出来
+        let msg = FeedbackGenerator::generate_explanation("ぢ", "ji", "ji");
+        let line = msg.lines().find(|l| l.contains("对应的假名")).unwrap();
+        assert!(line.contains('じ') && !line.contains('ぢ'), "{}", msg);
+    }
+
+    #[test]
+    fn alternate_spellings_are_mentioned() {
+        let msg = FeedbackGenerator::generate_explanation("ぢ", "ji", "zi");
+        assert!(msg.contains("ぢ 也可以写成 di"), "{}", msg);
+
+        let msg = FeedbackGenerator::generate_explanation("ん", "n", "m");
+        assert!(msg.contains("nn / n'"), "{}", msg);
+
+        let msg = FeedbackGenerator::generate_explanation("か", "ka", "ga");
+        assert!(!msg.contains("也可以写成"), "{}", msg);
+    }
+}
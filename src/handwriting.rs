@@ -0,0 +1,338 @@
+use kana_types::{DrawnStroke, HandwritingScore, StrokeProblem};
+
+use crate::strokes::{parse_path, stroke_data};
+
+type Point = (f32, f32);
+
+// 每笔按弧长重采样成这么多点再逐点比
+const SAMPLES: usize = 16;
+// 曲线段拆成几段折线
+const CURVE_STEPS: usize = 12;
+// 整个字缩放到单位方框后，一笔平均每个点偏出去这么多就算写歪了
+const SHAPE_TOLERANCE: f32 = 0.2;
+// 单个点偏出去这么多也算写错，ね / れ / わ 只差收笔那一小段，平均下来看不出来。
+// tests/handwriting 里正确笔迹的最大偏差在 0.11 左右，把 れ 当 ね 写时收笔那段偏 0.3，取中间
+const POINT_TOLERANCE: f32 = 0.2;
+// 比这短的笔画（点、浊点）方向看不准，不检查
+const MIN_DIRECTED_LENGTH: f32 = 0.12;
+// 和另一笔的距离要明显更近才算顺序或方向错了，而不只是写得潦草
+const CLEARLY_BETTER: f32 = 0.6;
+// 多字卡片每个字占一格，格宽和笔顺数据一样是 109
+const CELL: f32 = 109.0;
+
+// 把参考笔画的 M / L / Q / C 展开成折线
+fn flatten(path: &str) -> Vec<Point> {
+    let mut points: Vec<Point> = Vec::new();
+    for (cmd, args) in parse_path(path) {
+        let mut current = points.last().copied().unwrap_or((0.0, 0.0));
+        let steps = |n: usize| (1..=n).map(move |i| i as f32 / n as f32);
+        match cmd {
+            'M' | 'L' => points.extend(args),
+            'Q' => {
+                for pair in args.chunks_exact(2) {
+                    let (c, end) = (pair[0], pair[1]);
+                    points.extend(steps(CURVE_STEPS).map(|t| {
+                        let u = 1.0 - t;
+                        (u * u * current.0 + 2.0 * u * t * c.0 + t * t * end.0, u * u * current.1 + 2.0 * u * t * c.1 + t * t * end.1)
+                    }));
+                    current = end;
+                }
+            }
+            'C' => {
+                for triple in args.chunks_exact(3) {
+                    let (c1, c2, end) = (triple[0], triple[1], triple[2]);
+                    points.extend(steps(CURVE_STEPS).map(|t| {
+                        let u = 1.0 - t;
+                        let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+                        (
+                            a * current.0 + b * c1.0 + c * c2.0 + d * end.0,
+                            a * current.1 + b * c1.1 + c * c2.1 + d * end.1,
+                        )
+                    }));
+                    current = end;
+                }
+            }
+            _ => {}
+        }
+    }
+    points
+}
+
+fn dist(a: Point, b: Point) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+fn length(points: &[Point]) -> f32 {
+    points.windows(2).map(|w| dist(w[0], w[1])).sum()
+}
+
+// 按弧长等距取 SAMPLES 个点，画得快慢、点的疏密就不影响比较了
+fn resample(points: &[Point]) -> Vec<Point> {
+    let total = length(points);
+    if points.len() < 2 || total <= f32::EPSILON {
+        return vec![points.first().copied().unwrap_or((0.0, 0.0)); SAMPLES];
+    }
+
+    let step = total / (SAMPLES - 1) as f32;
+    let mut out = vec![points[0]];
+    let mut walked = 0.0;
+    let mut target = step;
+    for w in points.windows(2) {
+        let seg = dist(w[0], w[1]);
+        while seg > 0.0 && walked + seg >= target && out.len() < SAMPLES {
+            let t = (target - walked) / seg;
+            out.push((w[0].0 + (w[1].0 - w[0].0) * t, w[0].1 + (w[1].1 - w[0].1) * t));
+            target += step;
+        }
+        walked += seg;
+    }
+    while out.len() < SAMPLES {
+        out.push(points[points.len() - 1]);
+    }
+    out
+}
+
+// 整个字一起平移缩放到以原点为中心的单位方框，不改变长宽比；
+// 这样写大写小、偏左偏右都没关系，比的只是笔画之间的相对位置和形状
+fn normalize(strokes: &mut [Vec<Point>]) {
+    let all = strokes.iter().flatten();
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+    for &(x, y) in all {
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    }
+    let scale = (max_x - min_x).max(max_y - min_y).max(1e-3);
+    let (cx, cy) = ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+    for point in strokes.iter_mut().flatten() {
+        *point = ((point.0 - cx) / scale, (point.1 - cy) / scale);
+    }
+}
+
+fn deviation(a: &[Point], b: &[Point]) -> f32 {
+    a.iter().zip(b).map(|(p, q)| dist(*p, *q)).sum::<f32>() / a.len() as f32
+}
+
+fn max_deviation(a: &[Point], b: &[Point]) -> f32 {
+    a.iter().zip(b).map(|(p, q)| dist(*p, *q)).fold(0.0, f32::max)
+}
+
+fn reversed(points: &[Point]) -> Vec<Point> {
+    points.iter().rev().copied().collect()
+}
+
+// 和笔顺数据逐笔比：先看笔数，再看每一笔是不是别的笔（顺序）、是不是反着写（方向）、偏得多不多（形状）。
+// 卡片里有字没有笔顺数据时返回 Err(那个字)
+pub fn check(kana: &str, drawn: &[DrawnStroke]) -> Result<HandwritingScore, char> {
+    let data = stroke_data(kana)?;
+    let mut reference: Vec<Vec<Point>> = data
+        .glyphs
+        .iter()
+        .enumerate()
+        .flat_map(|(i, glyph)| {
+            glyph.strokes.iter().map(move |path| {
+                flatten(path).into_iter().map(|(x, y)| (x + i as f32 * CELL, y)).collect()
+            })
+        })
+        .collect();
+    let mut drawn: Vec<Vec<Point>> = drawn
+        .iter()
+        .filter(|stroke| !stroke.is_empty())
+        .map(|stroke| stroke.iter().map(|p| (p[0], p[1])).collect())
+        .collect();
+
+    let (expected_strokes, drawn_strokes) = (reference.len(), drawn.len());
+    if expected_strokes != drawn_strokes {
+        return Ok(HandwritingScore {
+            correct: false,
+            score: 0,
+            expected_strokes,
+            drawn_strokes,
+            problems: vec![StrokeProblem::Count { expected: expected_strokes, drawn: drawn_strokes }],
+        });
+    }
+
+    normalize(&mut reference);
+    normalize(&mut drawn);
+    let reference: Vec<Vec<Point>> = reference.iter().map(|s| resample(s)).collect();
+    let drawn: Vec<Vec<Point>> = drawn.iter().map(|s| resample(s)).collect();
+
+    let mut problems = Vec::new();
+    let mut total = 0.0;
+    for (i, stroke) in drawn.iter().enumerate() {
+        let forward = deviation(stroke, &reference[i]);
+        let backward = deviation(stroke, &reversed(&reference[i]));
+        let own = forward.min(backward);
+        // 分数按书写方向算，反着写的那笔会拉低分数
+        total += forward;
+
+        // 和其他笔比，取最像的那一笔（正反都算）
+        let closest = reference
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(j, other)| (j, deviation(stroke, other).min(deviation(stroke, &reversed(other)))))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        match closest {
+            // 本来就写得挺像的不算（两个浊点离得很近，互相也像）
+            Some((j, other)) if own > SHAPE_TOLERANCE / 2.0 && other < SHAPE_TOLERANCE && other < own * CLEARLY_BETTER => {
+                problems.push(StrokeProblem::Order { stroke: i + 1, expected: j + 1 });
+            }
+            _ if length(&reference[i]) >= MIN_DIRECTED_LENGTH && backward < forward * CLEARLY_BETTER => {
+                problems.push(StrokeProblem::Direction { stroke: i + 1 });
+            }
+            _ if forward > SHAPE_TOLERANCE || max_deviation(stroke, &reference[i]) > POINT_TOLERANCE => {
+                problems.push(StrokeProblem::Shape { stroke: i + 1 });
+            }
+            _ => {}
+        }
+    }
+
+    let mean = total / drawn_strokes.max(1) as f32;
+    let score = ((1.0 - mean / (2.0 * SHAPE_TOLERANCE)) * 100.0).clamp(0.0, 100.0).round() as u8;
+    Ok(HandwritingScore { correct: problems.is_empty(), score, expected_strokes, drawn_strokes, problems })
+}
+
+// verdict 里给学习者看的说明
+pub fn explain(score: &HandwritingScore) -> String {
+    let problems: Vec<String> = score.problems.iter().map(|p| p.to_string()).collect();
+    format!("相似度 {} 分。{}", score.score, problems.join("；"))
+}
+
+#[cfg(test)]
+mod tests {
+    use kana_types::HandwritingCheck;
+
+    use super::*;
+
+    // tests/handwriting 下的笔迹是照参考笔画模拟出来的手写：写得比格子小、偏右下、略斜，点距不均匀，带手抖。
+    // 格式和 POST /api/v1/handwriting/check 的请求一样，录到真实笔迹可以直接放进去
+    fn fixture(name: &str) -> HandwritingCheck {
+        let json = match name {
+            "a" => include_str!("../tests/handwriting/a.json"),
+            "o" => include_str!("../tests/handwriting/o.json"),
+            "ne" => include_str!("../tests/handwriting/ne.json"),
+            "re" => include_str!("../tests/handwriting/re.json"),
+            "ki" => include_str!("../tests/handwriting/ki.json"),
+            "pa" => include_str!("../tests/handwriting/pa.json"),
+            "shi_katakana" => include_str!("../tests/handwriting/shi_katakana.json"),
+            "tsu_katakana" => include_str!("../tests/handwriting/tsu_katakana.json"),
+            _ => panic!("no fixture {}", name),
+        };
+        serde_json::from_str(json).unwrap()
+    }
+
+    const FIXTURES: &[&str] = &["a", "o", "ne", "re", "ki", "pa", "shi_katakana", "tsu_katakana"];
+
+    // 参考笔画原样当作手写
+    fn reference_strokes(kana: &str) -> Vec<DrawnStroke> {
+        stroke_data(kana)
+            .unwrap()
+            .glyphs
+            .iter()
+            .enumerate()
+            .flat_map(|(i, glyph)| {
+                glyph.strokes.iter().map(move |path| flatten(path).into_iter().map(|(x, y)| [x + i as f32 * CELL, y]).collect())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reference_strokes_score_full_marks() {
+        for kana in ["あ", "お", "ね", "れ", "き", "ぱ", "シ", "ツ", "きゃ", "ヂ"] {
+            let score = check(kana, &reference_strokes(kana)).unwrap();
+            assert!(score.correct, "{}: {:?}", kana, score);
+            assert!(score.score >= 98, "{}: {:?}", kana, score);
+        }
+    }
+
+    #[test]
+    fn recorded_strokes_are_correct() {
+        for name in FIXTURES {
+            let f = fixture(name);
+            let score = check(&f.kana_char, &f.strokes).unwrap();
+            assert!(score.correct, "{}: {:?}", name, score);
+            assert!(score.score >= 80, "{}: {:?}", name, score);
+        }
+    }
+
+    #[test]
+    fn swapped_strokes_are_out_of_order() {
+        for name in FIXTURES {
+            let f = fixture(name);
+            // 长短差不多的笔互换了也要认出来，所以每一对都试
+            for (i, j) in (0..f.strokes.len()).flat_map(|i| (i + 1..f.strokes.len()).map(move |j| (i, j))) {
+                let mut strokes = f.strokes.clone();
+                strokes.swap(i, j);
+                let score = check(&f.kana_char, &strokes).unwrap();
+                assert!(!score.correct, "{} swap {} {}: {:?}", name, i + 1, j + 1, score);
+                assert!(
+                    score.problems.iter().any(|p| matches!(p, StrokeProblem::Order { .. })),
+                    "{} swap {} {}: {:?}",
+                    name,
+                    i + 1,
+                    j + 1,
+                    score
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn reversed_stroke_is_reported() {
+        for name in FIXTURES {
+            let f = fixture(name);
+            let reference = reference_strokes(&f.kana_char);
+            for i in 0..f.strokes.len() {
+                // 点和浊点太短（不到 MIN_DIRECTED_LENGTH），不检查方向
+                let points: Vec<Point> = reference[i].iter().map(|p| (p[0], p[1])).collect();
+                if length(&points) < 14.0 {
+                    continue;
+                }
+                let mut strokes = f.strokes.clone();
+                strokes[i].reverse();
+                let score = check(&f.kana_char, &strokes).unwrap();
+                assert!(!score.correct, "{} reverse {}: {:?}", name, i + 1, score);
+                assert!(
+                    score.problems.contains(&StrokeProblem::Direction { stroke: i + 1 }),
+                    "{} reverse {}: {:?}",
+                    name,
+                    i + 1,
+                    score
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn wrong_stroke_count() {
+        let f = fixture("a");
+        let mut missing = f.strokes.clone();
+        missing.pop();
+        let score = check("あ", &missing).unwrap();
+        assert_eq!(score.problems, vec![StrokeProblem::Count { expected: 3, drawn: 2 }]);
+        assert!(!score.correct);
+
+        let mut extra = f.strokes.clone();
+        extra.push(vec![[50.0, 50.0], [60.0, 60.0]]);
+        let score = check("あ", &extra).unwrap();
+        assert_eq!(score.problems, vec![StrokeProblem::Count { expected: 3, drawn: 4 }]);
+    }
+
+    #[test]
+    fn another_kana_with_the_same_stroke_count_is_wrong() {
+        // 笔数一样、长得也像的几对
+        for (name, kana) in [("o", "あ"), ("a", "お"), ("re", "ね"), ("ne", "れ"), ("shi_katakana", "ツ"), ("tsu_katakana", "シ")] {
+            let f = fixture(name);
+            let score = check(kana, &f.strokes).unwrap();
+            assert!(!score.correct, "{} as {}: {:?}", name, kana, score);
+        }
+    }
+
+    #[test]
+    fn no_stroke_data() {
+        assert_eq!(check("漢", &[]), Err('漢'));
+    }
+}
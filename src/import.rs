@@ -0,0 +1,396 @@
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use utoipa::ToSchema;
+
+use crate::db::{Db, NewCard, ReviewEntry};
+use crate::error::InvalidData;
+use crate::mnemonic::default_mnemonic;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    Apkg,
+    Csv,
+    Tsv,
+}
+
+impl ImportFormat {
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        let ext = Path::new(name).extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "apkg" | "colpkg" => Some(Self::Apkg),
+            "csv" => Some(Self::Csv),
+            // Anki 导出的 "Notes in Plain Text" 是 .txt + Tab 分隔
+            "tsv" | "txt" => Some(Self::Tsv),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    // None 时按文件扩展名判断
+    pub format: Option<ImportFormat>,
+    // 字段下标从 0 开始：front -> kana_char, back -> romaji
+    pub front_field: usize,
+    pub back_field: usize,
+    pub meaning_field: Option<usize>,
+    // 仅 CSV/TSV：哪一列是空格分隔的标签
+    pub tags_field: Option<usize>,
+    // 额外给每张导入的卡片打上的标签
+    pub tags: Vec<String>,
+    // 是否带上 Anki 的复习记录和调度状态
+    pub with_history: bool,
+    pub skip_header: bool,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            format: None,
+            front_field: 0,
+            back_field: 1,
+            meaning_field: None,
+            tags_field: None,
+            tags: Vec::new(),
+            with_history: false,
+            skip_header: false,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct ImportReport {
+    pub cards_added: usize,
+    // 已存在相同假名的卡片，只合并标签和历史
+    pub cards_merged: usize,
+    pub cards_skipped: usize,
+    pub reviews_imported: usize,
+}
+
+struct ImportedCard {
+    front: String,
+    back: String,
+    meaning: Option<String>,
+    tags: Vec<String>,
+    // (stability, difficulty, last_review)
+    scheduling: Option<(f64, f64, Option<String>)>,
+    history: Vec<ReviewEntry>,
+}
+
+pub async fn import_file(db: &Db, path: &Path, opts: &ImportOptions) -> Result<ImportReport> {
+    let bytes = std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    import_bytes(db, file_name, &bytes, opts).await
+}
+
+pub async fn import_bytes(db: &Db, file_name: &str, bytes: &[u8], opts: &ImportOptions) -> Result<ImportReport> {
+    let format = opts
+        .format
+        .or_else(|| ImportFormat::from_file_name(file_name))
+        .ok_or_else(|| InvalidData(format!("cannot detect import format of '{}', pass it explicitly", file_name)))?;
+
+    let (cards, skipped) = match format {
+        ImportFormat::Apkg => read_apkg(bytes, opts).await?,
+        ImportFormat::Csv => read_delimited(bytes, b',', opts)?,
+        ImportFormat::Tsv => read_delimited(bytes, b'\t', opts)?,
+    };
+
+    let mut report = store(db, cards, opts).await?;
+    report.cards_skipped += skipped;
+    Ok(report)
+}
+
+fn read_delimited(bytes: &[u8], delimiter: u8, opts: &ImportOptions) -> Result<(Vec<ImportedCard>, usize)> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(opts.skip_header)
+        .flexible(true)
+        .from_reader(strip_anki_header(bytes));
+
+    let mut cards = Vec::new();
+    let mut skipped = 0;
+
+    for record in reader.records() {
+        let record = record.map_err(|e| InvalidData(format!("malformed row: {}", e)))?;
+        let field = |idx: usize| record.get(idx).map(clean_field).unwrap_or_default();
+
+        let front = field(opts.front_field);
+        let back = field(opts.back_field);
+        if front.is_empty() || back.is_empty() {
+            skipped += 1;
+            continue;
+        }
+
+        cards.push(ImportedCard {
+            front,
+            back,
+            meaning: opts.meaning_field.map(field).filter(|m| !m.is_empty()),
+            tags: opts.tags_field.map(|idx| split_tags(&field(idx))).unwrap_or_default(),
+            scheduling: None,
+            history: Vec::new(),
+        });
+    }
+
+    Ok((cards, skipped))
+}
+
+// Anki 导出文件开头有 "#separator:tab"、"#html:true" 之类的头部行。
+// 只跳过开头这几行，正文里以 # 开头的字段照常导入
+fn strip_anki_header(bytes: &[u8]) -> &[u8] {
+    let mut rest = bytes;
+    while rest.starts_with(b"#") {
+        let end = rest.iter().position(|&b| b == b'\n').map_or(rest.len(), |i| i + 1);
+        let line = String::from_utf8_lossy(&rest[..end]);
+        let is_header = line
+            .trim_end()
+            .strip_prefix('#')
+            .and_then(|l| l.split_once(':'))
+            .is_some_and(|(key, _)| !key.is_empty() && key.chars().all(|c| c.is_ascii_alphabetic() || c == ' '));
+        if !is_header {
+            break;
+        }
+        rest = &rest[end..];
+    }
+    rest
+}
+
+// 解压出来的临时 SQLite 文件，离开作用域时删除
+struct TempFile(PathBuf);
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+async fn read_apkg(bytes: &[u8], opts: &ImportOptions) -> Result<(Vec<ImportedCard>, usize)> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| InvalidData(format!("not a valid .apkg (zip) file: {}", e)))?;
+
+    // 新版 Anki 同时放一个占位的 collection.anki2 和 zstd 压缩的 collection.anki21b
+    let entry = if archive.by_name("collection.anki21").is_ok() {
+        "collection.anki21"
+    } else if archive.by_name("collection.anki21b").is_ok() {
+        return Err(InvalidData(
+            "collection.anki21b is zstd-compressed; re-export from Anki with \"Support older Anki versions\" enabled".to_string(),
+        )
+        .into());
+    } else if archive.by_name("collection.anki2").is_ok() {
+        "collection.anki2"
+    } else {
+        return Err(InvalidData("no Anki collection found inside the package".to_string()).into());
+    };
+
+    let mut collection = Vec::new();
+    let corrupt = |e: String| InvalidData(format!("failed to unpack {}: {}", entry, e));
+    let mut file = archive.by_name(entry).map_err(|e| corrupt(e.to_string()))?;
+    file.read_to_end(&mut collection).map_err(|e| corrupt(e.to_string()))?;
+
+    let tmp = TempFile(std::env::temp_dir().join(format!("kana-import-{}.anki2", uuid::Uuid::new_v4())));
+    std::fs::write(&tmp.0, &collection)?;
+
+    // 解压出来的不是 Anki 的库（或者缺表）算文件的问题
+    let not_a_collection = |e: sqlx::Error| InvalidData(format!("not a valid Anki collection: {}", e));
+    let options = SqliteConnectOptions::from_str(&format!("sqlite:{}", tmp.0.display()))?.read_only(true);
+    let pool = SqlitePoolOptions::new().max_connections(1).connect_with(options).await.map_err(not_a_collection)?;
+
+    let notes: Vec<(i64, String, String)> = sqlx::query_as("SELECT id, flds, tags FROM notes ORDER BY id")
+        .fetch_all(&pool)
+        .await
+        .map_err(not_a_collection)?;
+
+    // 一个 note 可能有正反两张卡，只取 ord 最小的那张的调度状态
+    let anki_cards: Vec<(i64, i64, i64, i64)> = sqlx::query_as("SELECT id, nid, ivl, factor FROM cards ORDER BY nid, ord")
+        .fetch_all(&pool)
+        .await
+        .map_err(not_a_collection)?;
+    let mut card_by_note: HashMap<i64, (i64, i64, i64)> = HashMap::new();
+    for (cid, nid, ivl, factor) in anki_cards {
+        card_by_note.entry(nid).or_insert((cid, ivl, factor));
+    }
+
+    let mut history_by_card: HashMap<i64, Vec<ReviewEntry>> = HashMap::new();
+    if opts.with_history {
+        // type 4 是手动改期，ease 0 也不是真正的作答
+        let revlog: Vec<(i64, i64, i64, i64)> = sqlx::query_as("SELECT id, cid, ease, time FROM revlog WHERE type != 4 AND ease > 0 ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .map_err(not_a_collection)?;
+        for (rid, cid, ease, time) in revlog {
+            let Some(reviewed_at) = DateTime::from_timestamp_millis(rid) else { continue };
+            history_by_card.entry(cid).or_default().push(ReviewEntry {
+                id: format!("anki-{}", rid),
+                card_id: String::new(),
+                reviewed_at: reviewed_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+                correct: ease > 1,
+                source: "anki".to_string(),
+                // Anki 记录的是答题用时（毫秒）
+                response_ms: (time > 0).then_some(time),
+            });
+        }
+    }
+
+    pool.close().await;
+
+    let mut cards = Vec::new();
+    let mut skipped = 0;
+
+    for (nid, flds, tags) in notes {
+        let fields: Vec<&str> = flds.split('\u{1f}').collect();
+        let field = |idx: usize| fields.get(idx).map(|f| clean_field(f)).unwrap_or_default();
+
+        let front = field(opts.front_field);
+        let back = field(opts.back_field);
+        if front.is_empty() || back.is_empty() {
+            skipped += 1;
+            continue;
+        }
+
+        let mut scheduling = None;
+        let mut history = Vec::new();
+        if let Some(&(cid, ivl, factor)) = card_by_note.get(&nid) {
+            history = history_by_card.remove(&cid).unwrap_or_default();
+            if opts.with_history {
+                // ivl > 0 是天数，负数表示仍在学习阶段（秒）
+                let stability = if ivl > 0 { ivl as f64 } else { 0.0 };
+                // Anki 默认 ease 250%，越低说明越难；比默认还容易的按 0 算
+                let difficulty = if factor > 0 { ((2500 - factor) as f64 / 1000.0).max(0.0) } else { 0.0 };
+                let last_review = history.last().map(|r| r.reviewed_at.clone());
+                scheduling = Some((stability, difficulty, last_review));
+            }
+        }
+
+        cards.push(ImportedCard {
+            front,
+            back,
+            meaning: opts.meaning_field.map(field).filter(|m| !m.is_empty()),
+            tags: split_tags(&tags),
+            scheduling,
+            history,
+        });
+    }
+
+    Ok((cards, skipped))
+}
+
+async fn store(db: &Db, cards: Vec<ImportedCard>, opts: &ImportOptions) -> Result<ImportReport> {
+    let mut report = ImportReport::default();
+    // 和恢复备份一样，中途出错就整批回滚，不留下导入了一半的卡片
+    let mut tx = db.begin().await?;
+
+    for card in cards {
+        let mut tags = opts.tags.clone();
+        for tag in card.tags {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+
+        let card_id = match Db::card_by_kana(&mut tx, &card.front).await? {
+            Some(existing) => {
+                Db::add_tags(&mut tx, &existing.id, &tags).await?;
+                // 本地从没复习过的卡片直接沿用 Anki 的调度状态
+                if let (Some((stability, difficulty, last_review)), None) = (&card.scheduling, &existing.last_review) {
+                    Db::set_scheduling(&mut tx, &existing.id, *stability, *difficulty, last_review.as_deref()).await?;
+                }
+                report.cards_merged += 1;
+                existing.id
+            }
+            None => {
+                let (stability, difficulty, last_review) = card.scheduling.unwrap_or((0.0, 0.0, None));
+                let id = Db::insert_new_card(
+                    &mut tx,
+                    &NewCard {
+                        mnemonic: default_mnemonic(&card.front),
+                        kana_char: card.front,
+                        romaji: card.back,
+                        meaning: card.meaning,
+                        tags: tags.join(" "),
+                        stability,
+                        difficulty,
+                        last_review,
+                    },
+                )
+                .await?;
+                report.cards_added += 1;
+                id
+            }
+        };
+
+        if opts.with_history {
+            for mut entry in card.history {
+                entry.card_id = card_id.clone();
+                if Db::insert_review(&mut tx, &entry).await? {
+                    report.reviews_imported += 1;
+                }
+            }
+        }
+    }
+
+    tx.commit().await?;
+    Ok(report)
+}
+
+pub fn split_tags(raw: &str) -> Vec<String> {
+    raw.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_string())
+        .collect()
+}
+
+// Anki 字段里是 HTML，去掉标签、[sound:...] 和常见实体
+fn clean_field(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut in_tag = false;
+    let mut rest = raw;
+
+    while let Some(c) = rest.chars().next() {
+        if !in_tag && rest.starts_with("[sound:") {
+            match rest.find(']') {
+                Some(end) => {
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+                None => break,
+            }
+        }
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+
+    out.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_leading_anki_header_lines_are_skipped() {
+        let data = "#separator:tab\n#html:false\n#tags column:3\nあ\ta\n#\tsharp\nい\ti\n";
+        let (cards, skipped) = read_delimited(data.as_bytes(), b'\t', &ImportOptions::default()).unwrap();
+
+        let fronts: Vec<&str> = cards.iter().map(|c| c.front.as_str()).collect();
+        assert_eq!(fronts, ["あ", "#", "い"]);
+        assert_eq!(skipped, 0);
+    }
+}
@@ -2,44 +2,282 @@ use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use dotenvy::dotenv;
+use clap::Parser;
 // 【新增引用】
 use tower_http::services::ServeDir;
 
 mod api;
 mod app;
+mod audio;
+mod auth;
+mod backup;
+mod cards;
+mod cli;
+mod combo;
+mod config;
 mod data;
 mod db;
+mod dictation;
+mod error;
 mod feedback;
-// mod ui; // Removed
+mod handwriting;
+mod import;
+mod media;
+mod mnemonic;
+mod openapi;
+mod reading;
+mod recent;
+mod sampler;
+mod session;
+mod strokes;
+mod ui;
+mod ws;
 
 use crate::app::App;
 use crate::api::{app_router, ApiState};
+use crate::cli::{Cli, Command, ExportFormat, ServeArgs, TokenCommand};
+use crate::config::Config;
+use crate::db::Db;
+use crate::media::MediaStore;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenv().ok();
-    env_logger::init();
 
-    // Initialize App (which inits DB)
-    let app_logic = App::new().await?;
+    let cli = Cli::parse();
+    let command = cli.command.unwrap_or(Command::Serve(ServeArgs::default()));
+
+    // 默认值 < 配置文件 < 环境变量 < 命令行参数，合并完再统一校验
+    let mut config = Config::load(cli.config.as_deref())?;
+    config.apply_env()?;
+    if let Some(url) = cli.database {
+        config.database.url = url;
+    }
+    if let Command::Serve(args) = &command {
+        if let Some(bind) = args.bind {
+            config.server.bind = bind;
+        }
+        if let Some(port) = args.port {
+            config.server.port = port;
+        }
+        if let Some(dir) = &args.static_dir {
+            config.server.static_dir = dir.clone();
+        }
+    }
+    config.validate()?;
+
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(&config.logging.level)).init();
+
+    if let Command::Config = command {
+        print!("{}", toml::to_string_pretty(&config)?);
+        return Ok(());
+    }
+    // 合成发音用不着数据库
+    if let Command::Say { kana, output } = &command {
+        let wav = audio::speech(kana).map_err(|c| anyhow::anyhow!("no audio for '{}'", c))?;
+        std::fs::write(output, wav)?;
+        println!("Wrote {}", output.display());
+        return Ok(());
+    }
+
+    let db = Db::connect(&config.database.url).await?.with_scheduler(config.scheduler.clone());
+
+    match command {
+        Command::Serve(_) => serve(db, &config).await,
+        Command::Config | Command::Say { .. } => Ok(()),
+        Command::Tui(args) => {
+            let mode = args.mode();
+            mode.validate().map_err(anyhow::Error::msg)?;
+            let mut app = App::new(Arc::new(db), config.session.clone()).await?;
+            app.start_session(mode).await?;
+            ui::run(&mut app).await
+        }
+        Command::Init => {
+            let stats = db.stats().await?;
+            println!("Database ready at {} ({} cards).", config.database.url, stats.total_cards);
+            Ok(())
+        }
+        Command::Stats => print_stats(&db).await,
+        Command::Import(args) => {
+            let report = import::import_file(&db, &args.path, &args.to_options()).await?;
+            println!(
+                "Imported {} new cards, merged {}, skipped {}, {} reviews.",
+                report.cards_added, report.cards_merged, report.cards_skipped, report.reviews_imported
+            );
+            Ok(())
+        }
+        Command::Export(args) => {
+            let content = match args.format {
+                ExportFormat::Json => serde_json::to_string_pretty(&backup::export(&db).await?)?,
+                ExportFormat::Csv => backup::export_review_log_csv(&db).await?,
+            };
+            match args.output {
+                Some(path) => {
+                    std::fs::write(&path, content)?;
+                    println!("Exported to {}", path.display());
+                }
+                None => print!("{}", content),
+            }
+            Ok(())
+        }
+        Command::Restore { path } => {
+            let data: backup::Backup = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+            let report = backup::restore(&db, &data).await?;
+            println!(
+                "Restored: {} cards added, {} updated, {} reviews added, {} decks, {} settings.",
+                report.cards_added, report.cards_updated, report.reviews_added, report.decks_merged, report.settings_updated
+            );
+            Ok(())
+        }
+        Command::Reset { cards, yes } => {
+            if !yes {
+                anyhow::bail!("reset wipes all review history; re-run with --yes to confirm");
+            }
+            db.reset(cards).await?;
+            println!("Progress reset{}.", if cards { " and cards re-seeded" } else { "" });
+            Ok(())
+        }
+        Command::Optimize => {
+            db.optimize().await?;
+            println!("Database optimized.");
+            Ok(())
+        }
+        Command::Passwd { stdin } => {
+            let password = read_new_password(stdin)?;
+            db.set_password_hash(&auth::hash_password(&password)?).await?;
+            println!("Password updated; all browser sessions have been logged out.");
+            Ok(())
+        }
+        Command::Token(TokenCommand::Create { name }) => {
+            let token = auth::create_api_token(&db, name.trim()).await?;
+            println!("Created token {} ({}). It will not be shown again:", token.id, token.name);
+            println!("{}", token.token);
+            Ok(())
+        }
+        Command::Token(TokenCommand::List) => {
+            let tokens = db.list_api_tokens().await?;
+            if tokens.is_empty() {
+                println!("No API tokens.");
+            }
+            for t in &tokens {
+                println!("{}  {}  created {}  last used {}", t.id, t.name, t.created_at, t.last_used.as_deref().unwrap_or("-"));
+            }
+            Ok(())
+        }
+        Command::Token(TokenCommand::Revoke { id }) => {
+            if !db.delete_api_token(&id).await? {
+                anyhow::bail!("no token with id {}", id);
+            }
+            println!("Token {} revoked.", id);
+            Ok(())
+        }
+        Command::CheckDb => {
+            let problems = db.check().await?;
+            if problems.is_empty() {
+                println!("Database OK.");
+                return Ok(());
+            }
+            for problem in &problems {
+                println!("- {}", problem);
+            }
+            anyhow::bail!("{} problem(s) found", problems.len())
+        }
+    }
+}
+
+// 终端里不回显，输两遍防止手滑
+fn read_new_password(from_stdin: bool) -> anyhow::Result<String> {
+    let password = if from_stdin {
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+        line.trim_end_matches(['\r', '\n']).to_string()
+    } else {
+        let first = rpassword::prompt_password("New password: ")?;
+        let second = rpassword::prompt_password("Repeat password: ")?;
+        if first != second {
+            anyhow::bail!("passwords do not match");
+        }
+        first
+    };
+
+    if password.chars().count() < 8 {
+        anyhow::bail!("password must be at least 8 characters");
+    }
+    Ok(password)
+}
+
+async fn print_stats(db: &Db) -> anyhow::Result<()> {
+    let stats = db.stats().await?;
+    let accuracy = if stats.total_reviews > 0 {
+        stats.correct_reviews as f64 * 100.0 / stats.total_reviews as f64
+    } else {
+        0.0
+    };
+
+    println!("Cards:     {} ({} reviewed)", stats.total_cards, stats.reviewed_cards);
+    println!("Reviews:   {} ({:.1}% correct)", stats.total_reviews, accuracy);
+    println!("Last seen: {}", stats.last_review.as_deref().unwrap_or("-"));
+    if !stats.hardest.is_empty() {
+        println!("Hardest:");
+        for (kana, romaji, difficulty) in &stats.hardest {
+            println!("  {} ({}) {:.2}", kana, romaji, difficulty);
+        }
+    }
+    if !stats.slowest.is_empty() {
+        println!("Slowest (median):");
+        for l in &stats.slowest {
+            println!("  {} ({}) {} ms, {} samples", l.kana_char, l.romaji, l.median_ms, l.samples);
+        }
+    }
+    Ok(())
+}
+
+async fn serve(db: Db, config: &Config) -> anyhow::Result<()> {
+    // Initialize App
+    let db = Arc::new(db);
+    let app_logic = App::new(db.clone(), config.session.clone()).await?;
     let app_state = Arc::new(Mutex::new(app_logic));
 
+    warn_if_exposed(&db, config).await?;
+
     let api_state = ApiState {
         app: app_state,
+        db,
+        auth: config.auth.clone(),
+        allowed_origins: config.server.allowed_origins.clone().into(),
+        media: MediaStore::new(config.media.dir.clone(), config.media.max_image_kb),
     };
 
-    // 【修改这里】
-    // 原来的代码：let app = app_router(api_state);
-    // 现在的代码：把 API 路由和静态文件服务连起来
+    // 把 API 路由和静态文件服务连起来，找不到的路径都去 frontend 找
     let app = app_router(api_state)
-        .fallback_service(ServeDir::new("frontend/dist")); // <--- 关键！找不到的路径都去 frontend 找
+        .fallback_service(ServeDir::new(&config.server.static_dir));
+
+    if !config.server.static_dir.is_dir() {
+        log::warn!("static dir {} does not exist, only the API will be served", config.server.static_dir.display());
+    }
 
-    let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
-    // let addr = SocketAddr::from(([192, 168, 25, 76], 3000));
+    let addr = SocketAddr::new(config.server.bind, config.server.port);
     println!("Listening on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    // 认证要知道请求是不是来自本机
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
+
+    Ok(())
+}
 
+async fn warn_if_exposed(db: &Db, config: &Config) -> anyhow::Result<()> {
+    let exposed = !config.server.bind.is_loopback();
+    if !config.auth.enabled {
+        if exposed {
+            log::warn!("authentication is disabled while listening on {}; anyone on the network can change your progress", config.server.bind);
+        }
+    } else if db.get_password_hash().await?.is_none() {
+        if !config.auth.trust_loopback {
+            log::warn!("no password set; every API request is rejected until you run `kana-tutor passwd`");
+        } else if exposed {
+            log::warn!("no password set; only requests from this machine are accepted until you run `kana-tutor passwd`");
+        }
+    }
     Ok(())
-}
\ No newline at end of file
+}
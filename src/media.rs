@@ -0,0 +1,87 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use rand::RngCore;
+
+// 上传的文件只按内容识别类型，不信任文件名和 Content-Type；不收 SVG，免得夹带脚本
+pub fn image_type(bytes: &[u8]) -> Option<(&'static str, &'static str)> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(("png", "image/png"))
+    } else if bytes.starts_with(b"\xff\xd8\xff") {
+        Some(("jpg", "image/jpeg"))
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some(("gif", "image/gif"))
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some(("webp", "image/webp"))
+    } else {
+        None
+    }
+}
+
+// 数据库里只存文件名，文件本身放在 media.dir 下
+#[derive(Debug, Clone)]
+pub struct MediaStore {
+    dir: PathBuf,
+    max_image_bytes: usize,
+}
+
+impl MediaStore {
+    pub fn new(dir: PathBuf, max_image_kb: usize) -> Self {
+        Self { dir, max_image_bytes: max_image_kb * 1024 }
+    }
+
+    fn mnemonic_path(&self, file: &str) -> Result<PathBuf> {
+        // 文件名是我们自己生成的，出现别的字符说明数据库被改过，不能拿去拼路径
+        if file.is_empty() || !file.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.') || file.starts_with('.') {
+            bail!("invalid media file name '{}'", file);
+        }
+        Ok(self.dir.join("mnemonics").join(file))
+    }
+
+    // 每次上传都换一个文件名，返回新文件名
+    pub async fn save_mnemonic_image(&self, card_id: &str, bytes: &[u8]) -> Result<String> {
+        if bytes.len() > self.max_image_bytes {
+            bail!("image is {} KB, the limit is {} KB", bytes.len() / 1024, self.max_image_bytes / 1024);
+        }
+        let Some((ext, _)) = image_type(bytes) else {
+            bail!("unsupported image, expected PNG, JPEG, GIF or WebP");
+        };
+
+        let mut suffix = [0u8; 4];
+        rand::thread_rng().fill_bytes(&mut suffix);
+        let suffix: String = suffix.iter().map(|b| format!("{:02x}", b)).collect();
+        let card_part: String = card_id.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '-').collect();
+        let file = format!("{}-{}.{}", card_part, suffix, ext);
+
+        let path = self.mnemonic_path(&file)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, bytes).await?;
+        Ok(file)
+    }
+
+    pub async fn read_mnemonic_image(&self, file: &str) -> Result<Option<(Vec<u8>, &'static str)>> {
+        let path = self.mnemonic_path(file)?;
+        let bytes = match tokio::fs::read(&path).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let mime = image_type(&bytes).map(|(_, mime)| mime).unwrap_or("application/octet-stream");
+        Ok(Some((bytes, mime)))
+    }
+
+    // 文件已经不在了也算成功
+    pub async fn remove_mnemonic_image(&self, file: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.mnemonic_path(file)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn max_image_bytes(&self) -> usize {
+        self.max_image_bytes
+    }
+}
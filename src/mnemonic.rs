@@ -0,0 +1,172 @@
+use kana_types::{Card, MnemonicNote};
+
+use crate::data::{unvoiced, KANA_DATA};
+
+pub struct KanaMnemonic {
+    pub kana: &'static str,
+    pub text: &'static str,
+}
+
+// 清音的默认助记：平假名来自汉字草书，片假名来自汉字的一部分，再配一句字形联想。
+// 浊音、半浊音和拗音由 default_mnemonic 按清音拼出来，不单独列
+pub const BASE_MNEMONICS: &[KanaMnemonic] = &[
+    // --- HIRAGANA ---
+    KanaMnemonic { kana: "あ", text: "来自「安」的草书。十字下面绕一个大圈，像张大嘴喊「啊」。" },
+    KanaMnemonic { kana: "い", text: "来自「以」的草书。两根并排的竖线，像两个人站在一起说「一」。" },
+    KanaMnemonic { kana: "う", text: "来自「宇」的草书。头上一点是宝盖，下面弯腰像被打了一拳「呜」。" },
+    KanaMnemonic { kana: "え", text: "来自「衣」的草书。上面一点，下面像一个人跳舞，「诶」地一声扭腰。" },
+    KanaMnemonic { kana: "お", text: "来自「於」的草书。和 あ 很像，但右上多一点，像发现什么「哦」了一声。" },
+    KanaMnemonic { kana: "か", text: "来自「加」的草书。左边就是「力」，右边一点，用力「咔」地掰断。" },
+    KanaMnemonic { kana: "き", text: "来自「幾」的草书。两横一竖像一把钥匙（key），下面一勾。" },
+    KanaMnemonic { kana: "く", text: "来自「久」的草书。一个尖嘴，像布谷鸟张嘴「咕」。" },
+    KanaMnemonic { kana: "け", text: "来自「計」的草书。左边一竖，右边像一个小桶，读 ke。" },
+    KanaMnemonic { kana: "こ", text: "来自「己」的草书。上下两笔像两条平放的棍子，读 ko。" },
+    KanaMnemonic { kana: "さ", text: "来自「左」的草书。一横一竖再绕个弯；和 ち 左右相反，さ 的弯朝左下。" },
+    KanaMnemonic { kana: "し", text: "来自「之」的草书。一笔钩子，像鱼钩，鱼钩上「吸」住鱼。" },
+    KanaMnemonic { kana: "す", text: "来自「寸」的草书。一横一竖中间打个圈，像吸管打了个结「嘶」。" },
+    KanaMnemonic { kana: "せ", text: "来自「世」的草书。像「世」少了一竖，读 se。" },
+    KanaMnemonic { kana: "そ", text: "来自「曽」的草书。上面一个「Z」，下面一个「C」，读 so。" },
+    KanaMnemonic { kana: "た", text: "来自「太」的草书。左边「ナ」，右边一个小「こ」，读 ta。" },
+    KanaMnemonic { kana: "ち", text: "来自「知」的草书。像数字 5 反过来；弯朝右下，和 さ 相反。" },
+    KanaMnemonic { kana: "つ", text: "来自「川」的草书。一道横着的浪，浪打过来「呲」。" },
+    KanaMnemonic { kana: "て", text: "来自「天」的草书。一横再往下甩，像伸出的手（te 手）。" },
+    KanaMnemonic { kana: "と", text: "来自「止」的草书。一根刺扎在脚（to 脚趾）上。" },
+    KanaMnemonic { kana: "な", text: "来自「奈」的草书。左边十字，右边一个带圈的竖，读 na。" },
+    KanaMnemonic { kana: "に", text: "来自「仁」的草书。左边一竖，右边一个「二」（ni）。" },
+    KanaMnemonic { kana: "ぬ", text: "来自「奴」的草书。像一碗面条（nu-dle），尾巴打个圈；め 没有圈。" },
+    KanaMnemonic { kana: "ね", text: "来自「祢」的草书。左边一竖，右边绕一圈打个结，像猫（ne-ko）卷起的尾巴。" },
+    KanaMnemonic { kana: "の", text: "来自「乃」的草书。一个大圈，像禁止标志「No」。" },
+    KanaMnemonic { kana: "は", text: "来自「波」的草书。左边一竖，右边打个结；ほ 右边多一横。" },
+    KanaMnemonic { kana: "ひ", text: "来自「比」的草书。像一张咧开的笑脸「嘿」（hi）。" },
+    KanaMnemonic { kana: "ふ", text: "来自「不」的草书。四笔分开，像一张吹气的嘴「呼」（fu）。" },
+    KanaMnemonic { kana: "へ", text: "来自「部」的草书。一座小山，爬山爬得「嘿」（he）地喘气。平假名和片假名几乎一样。" },
+    KanaMnemonic { kana: "ほ", text: "来自「保」的草书。比 は 右边多一横，像多了一层楼，读 ho。" },
+    KanaMnemonic { kana: "ま", text: "来自「末」的草书。两横一竖，下面打个圈，读 ma。" },
+    KanaMnemonic { kana: "み", text: "来自「美」的草书。像数字 21，写起来弯弯绕绕，读 mi。" },
+    KanaMnemonic { kana: "む", text: "来自「武」的草书。一横一竖打个圈，右上一点，像奶牛「哞」（mu）。" },
+    KanaMnemonic { kana: "め", text: "来自「女」的草书。像一只眼睛（me 目）；和 ぬ 比，め 尾巴不打圈。" },
+    KanaMnemonic { kana: "も", text: "来自「毛」的草书。就是「毛」字的样子，鱼钩上挂两横，读 mo。" },
+    KanaMnemonic { kana: "や", text: "来自「也」的草书。像一头牦牛（ya-k）的犄角。" },
+    KanaMnemonic { kana: "ゆ", text: "来自「由」的草书。像一条鱼，竖线是鱼的中线，读 yu。" },
+    KanaMnemonic { kana: "よ", text: "来自「与」的草书。一横一竖下面打个结，像溜溜球（yo-yo）。" },
+    KanaMnemonic { kana: "ら", text: "来自「良」的草书。上面一点，下面像数字 5 的下半截，读 ra。" },
+    KanaMnemonic { kana: "り", text: "来自「利」的草书。两根竖线，右边长左边短，像「刂」，读 ri。" },
+    KanaMnemonic { kana: "る", text: "来自「留」的草书。像 ろ 的尾巴多卷了一个圈（loop = ru）。" },
+    KanaMnemonic { kana: "れ", text: "来自「礼」的草书。和 わ、ね 同一个左半边，右边是往外踢的一条腿，读 re。" },
+    KanaMnemonic { kana: "ろ", text: "来自「呂」的草书。像 る 但尾巴不卷，直直的路（ro-ad）。" },
+    KanaMnemonic { kana: "わ", text: "来自「和」的草书。左边一竖，右边圆圆的背，读 wa；れ 踢腿，ね 打结。" },
+    KanaMnemonic { kana: "を", text: "来自「遠」的草书。像一个人在冲浪，现在只当助词用，读 o（写作 wo）。" },
+    KanaMnemonic { kana: "ん", text: "来自「无」的草书。像小写的 n 拖了个尾巴，读 n。" },
+    // --- KATAKANA ---
+    KanaMnemonic { kana: "ア", text: "取自「阿」的左边「阝」。像一把斧头（a-xe）。" },
+    KanaMnemonic { kana: "イ", text: "取自「伊」的左边「亻」。一个单人旁，像一个人（i）。" },
+    KanaMnemonic { kana: "ウ", text: "取自「宇」的宝盖头。和平假名 う 一样带头顶那一点，读 u。" },
+    KanaMnemonic { kana: "エ", text: "取自「江」的右边「工」，读 e。" },
+    KanaMnemonic { kana: "オ", text: "取自「於」的左边「方」，像「才」字，读 o。" },
+    KanaMnemonic { kana: "カ", text: "取自「加」的左边「力」，和平假名 か 去掉一点一样，读 ka。" },
+    KanaMnemonic { kana: "キ", text: "取自「幾」的一部分，两横一竖像一把钥匙（key），读 ki。" },
+    KanaMnemonic { kana: "ク", text: "取自「久」的前两笔，像张开的鸟嘴「咕」，读 ku；比 ケ 少一横。" },
+    KanaMnemonic { kana: "ケ", text: "取自「介」的一部分，像字母 K 歪过来，读 ke。" },
+    KanaMnemonic { kana: "コ", text: "取自「己」的上半部分，一个缺口朝左的框，读 ko。" },
+    KanaMnemonic { kana: "サ", text: "取自「散」的左上角，像「艹」拖一笔，读 sa。" },
+    KanaMnemonic { kana: "シ", text: "取自「之」的草书。三点从下往上挑，像笑眯眯的脸「嘻」（shi）；ツ 的点在上面、往下写。" },
+    KanaMnemonic { kana: "ス", text: "取自「須」的右边，像一个人翘着腿坐着，读 su。" },
+    KanaMnemonic { kana: "セ", text: "取自「世」，和平假名 せ 一样，读 se。" },
+    KanaMnemonic { kana: "ソ", text: "取自「曽」的头两点。点在左上，长笔从上往下撇，读 so；ン 的长笔从下往上挑。" },
+    KanaMnemonic { kana: "タ", text: "取自「多」的上半部分，像「夕」，读 ta；比 ク 里多一点。" },
+    KanaMnemonic { kana: "チ", text: "取自「千」，像「千」字，读 chi。" },
+    KanaMnemonic { kana: "ツ", text: "取自「川」。三笔在上面并排往下写，像海啸（tsu-nami）打下来；シ 的点在左边。" },
+    KanaMnemonic { kana: "テ", text: "取自「天」，像一根天线（te-levision），读 te。" },
+    KanaMnemonic { kana: "ト", text: "取自「止」的上半部分，就是「卜」字，读 to。" },
+    KanaMnemonic { kana: "ナ", text: "取自「奈」的上半部分，像「十」字的竖往左撇，读 na。" },
+    KanaMnemonic { kana: "ニ", text: "取自「二」，就是数字二（ni）。" },
+    KanaMnemonic { kana: "ヌ", text: "取自「奴」的右边「又」，像筷子夹面条（nu-dle），读 nu。" },
+    KanaMnemonic { kana: "ネ", text: "取自「祢」的左边「礻」，读 ne。" },
+    KanaMnemonic { kana: "ノ", text: "取自「乃」的第一笔，就是一撇，读 no。" },
+    KanaMnemonic { kana: "ハ", text: "取自「八」，就是数字八，像笑声「哈」（ha）。" },
+    KanaMnemonic { kana: "ヒ", text: "取自「比」的右半边「匕」，读 hi。" },
+    KanaMnemonic { kana: "フ", text: "取自「不」的第一笔，一个横折，像 ふ 只剩最上面那笔，读 fu。" },
+    KanaMnemonic { kana: "ヘ", text: "取自「部」的右边，和平假名 へ 几乎一样，读 he。" },
+    KanaMnemonic { kana: "ホ", text: "取自「保」的右下部分，像「木」加两点，读 ho。" },
+    KanaMnemonic { kana: "マ", text: "取自「末」的上半部分，像一个倒过来的小三角，读 ma。" },
+    KanaMnemonic { kana: "ミ", text: "取自「三」，三笔斜着写，像数字三（mi-ttsu）。" },
+    KanaMnemonic { kana: "ム", text: "取自「牟」的上半部分「厶」，读 mu。" },
+    KanaMnemonic { kana: "メ", text: "取自「女」的上半部分，像一个叉「乂」，读 me。" },
+    KanaMnemonic { kana: "モ", text: "取自「毛」，比平假名 も 少了个钩，读 mo。" },
+    KanaMnemonic { kana: "ヤ", text: "取自「也」，和平假名 や 一样只是更方，读 ya。" },
+    KanaMnemonic { kana: "ユ", text: "取自「由」的下半部分，像一个 U 形的框（yu），读 yu。" },
+    KanaMnemonic { kana: "ヨ", text: "取自「与」，像反过来的「E」，读 yo。" },
+    KanaMnemonic { kana: "ラ", text: "取自「良」的上半部分，像 フ 头上多一横，读 ra。" },
+    KanaMnemonic { kana: "リ", text: "取自「利」的右边「刂」，和平假名 り 一样，读 ri。" },
+    KanaMnemonic { kana: "ル", text: "取自「流」的右下部分，像两条腿，一条往外踢，读 ru。" },
+    KanaMnemonic { kana: "レ", text: "取自「礼」的右边，一笔竖折上挑，像打勾，读 re。" },
+    KanaMnemonic { kana: "ロ", text: "取自「呂」的上半部分「口」，读 ro。" },
+    KanaMnemonic { kana: "ワ", text: "取自「和」的右边「口」的一部分，像 ウ 没有头上那点，读 wa。" },
+    KanaMnemonic { kana: "ヲ", text: "取自「乎」，像 ヨ 多一撇，只当助词用，读 o（写作 wo）。" },
+    KanaMnemonic { kana: "ン", text: "取自「尔」的一部分。点在左上，长笔从下往上挑，读 n；ソ 的长笔往下撇。" },
+];
+
+const SMALL_Y: [(char, &str); 6] = [('ゃ', "ya"), ('ゅ', "yu"), ('ょ', "yo"), ('ャ', "ya"), ('ュ', "yu"), ('ョ', "yo")];
+
+fn base_text(kana: &str) -> Option<&'static str> {
+    BASE_MNEMONICS.iter().find(|m| m.kana == kana).map(|m| m.text)
+}
+
+fn romaji_of(kana: &str) -> Option<&'static str> {
+    KANA_DATA.iter().find(|k| k.kana == kana).map(|k| k.romaji)
+}
+
+fn voiced_mnemonic(kana: char) -> Option<String> {
+    let (base, handakuten) = unvoiced(kana, |c| base_text(&c.to_string()).is_some())?;
+    let base = base.to_string();
+    let (base_romaji, romaji) = (romaji_of(&base)?, romaji_of(&kana.to_string())?);
+    let mark = if handakuten { "右上加个小圈（半浊点）" } else { "右上加两点（浊点）" };
+    let mut text = format!("{}{}：{} → {}。", base, mark, base_romaji, romaji);
+    if matches!(kana, 'ぢ' | 'づ' | 'ヂ' | 'ヅ') {
+        text.push_str(&format!("读音和 {} 一样，现代日语里很少用。", if romaji == "ji" { "じ" } else { "ず" }));
+    }
+    text.push_str(&format!("{}：{}", base, base_text(&base)?));
+    Some(text)
+}
+
+fn yoon_mnemonic(kana: &str) -> Option<String> {
+    let mut chars = kana.chars();
+    let (first, small) = (chars.next()?, chars.next()?);
+    if chars.next().is_some() {
+        return None;
+    }
+    let (_, small_romaji) = SMALL_Y.iter().find(|(c, _)| *c == small)?;
+    let first = first.to_string();
+    let (first_romaji, romaji) = (romaji_of(&first)?, romaji_of(kana)?);
+    Some(format!(
+        "{}（{}）+ 小写的 {}（{}），两拍并成一拍读 {}。小字只占半格，别读成 {}{}。",
+        first, first_romaji, small, small_romaji, romaji, first_romaji, small_romaji
+    ))
+}
+
+// KANA_DATA 里的每个假名都有默认助记，其他卡返回 None
+pub fn default_mnemonic(kana: &str) -> Option<String> {
+    if let Some(text) = base_text(kana) {
+        return Some(text.to_string());
+    }
+    let mut chars = kana.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => voiced_mnemonic(c),
+        _ => yoon_mnemonic(kana),
+    }
+}
+
+pub fn image_url(card_id: &str, file: &str) -> String {
+    // 文件名每次上传都会变，带上它浏览器就不会拿到旧图
+    format!("/api/v1/cards/{}/mnemonic/image?v={}", card_id, file)
+}
+
+// 答错后给前端看的助记；文字和图片都没有就不发
+pub fn note(card: &Card) -> Option<MnemonicNote> {
+    let text = card.mnemonic.clone().filter(|t| !t.trim().is_empty());
+    let image_url = card.mnemonic_image.as_deref().map(|file| image_url(&card.id, file));
+    if text.is_none() && image_url.is_none() {
+        return None;
+    }
+    Some(MnemonicNote { card_id: card.id.clone(), kana_char: card.kana_char.clone(), text, image_url })
+}
@@ -0,0 +1,91 @@
+use axum::Json;
+use kana_types::{ClientMessage, ServerMessage};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, Http, HttpAuthScheme, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::api;
+use crate::auth;
+use crate::cards;
+use crate::ws;
+
+// 文档直接从 Rust 类型和 handler 上的注解生成，改了接口不用再手动同步
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "kana-tutor API"),
+    modifiers(&SecurityAddon),
+    security(("session_cookie" = []), ("api_token" = [])),
+    paths(
+        auth::login,
+        auth::logout,
+        auth::status,
+        auth::list_tokens,
+        auth::create_token,
+        auth::revoke_token,
+        api::get_next_batch,
+        api::submit_answer,
+        api::check_handwriting,
+        ws::ws_handler,
+        api::start_session,
+        api::get_session_summary,
+        api::get_latency,
+        api::get_combo_strategy,
+        api::set_combo_strategy,
+        cards::get_session_deck,
+        cards::set_session_deck,
+        cards::list_cards,
+        cards::create_card,
+        cards::get_card,
+        cards::update_card,
+        cards::delete_card,
+        cards::suspend_card,
+        cards::unsuspend_card,
+        cards::bury_card,
+        cards::unbury_card,
+        cards::get_mnemonic_image,
+        cards::upload_mnemonic_image,
+        cards::delete_mnemonic_image,
+        cards::get_card_strokes,
+        cards::get_kana_strokes,
+        cards::get_card_audio,
+        cards::get_kana_audio,
+        cards::list_decks,
+        cards::create_deck,
+        cards::get_deck,
+        cards::rename_deck,
+        cards::delete_deck,
+        cards::set_deck_cards,
+        cards::add_deck_cards,
+        cards::remove_deck_card,
+        api::import_deck,
+        api::export_progress,
+        api::restore_backup,
+    ),
+    // WebSocket 消息不在任何 path 里出现，手动列出来
+    components(schemas(ClientMessage, ServerMessage)),
+    tags(
+        (name = "auth", description = "登录、登出和 API token"),
+        (name = "session", description = "取卡、提交和会话"),
+        (name = "cards", description = "卡片和卡组的增删改"),
+        (name = "stats", description = "统计"),
+        (name = "data", description = "导入、导出和恢复"),
+    )
+)]
+pub struct ApiDoc;
+
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+// 浏览器用登录后的 cookie，脚本用 Authorization: Bearer <token>
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "session_cookie",
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(auth::SESSION_COOKIE))),
+        );
+        components.add_security_scheme("api_token", SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)));
+    }
+}
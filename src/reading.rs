@@ -0,0 +1,129 @@
+use kana_types::{romaji_to_kana, same_romaji, DictationSegment};
+
+use crate::audio::heard;
+use crate::data::{split_kana, KanaPair};
+
+// 一拍最多写几个字（tsu、jya 之类），再长的一段不可能对，不用试
+const MAX_PIECE: usize = 6;
+
+// 把作答切成和题目一样多的段，让对上的拍尽量多；matches 决定一段算不算这一拍。
+// cost[i][j] = 前 i 拍用掉作答前 j 个字时最少错几拍；多写的字都算进最后一拍，漏写的拍对到空串。
+// 题目里有字不是内置假名时返回 Err(那个字)
+pub fn align(kana: &str, answer: &str, matches: impl Fn(&KanaPair, &str) -> bool) -> Result<Vec<DictationSegment>, char> {
+    let moras = split_kana(kana)?;
+    let typed: Vec<char> = answer.chars().filter(|c| !c.is_whitespace()).collect();
+    let (n, m) = (moras.len(), typed.len());
+    let piece = |from: usize, to: usize| -> String { typed[from..to].iter().collect() };
+
+    let mut cost = vec![vec![usize::MAX; m + 1]; n + 1];
+    let mut split = vec![vec![0; m + 1]; n + 1];
+    cost[0][0] = 0;
+    for i in 1..=n {
+        let ends = if i == n { m..=m } else { 0..=m };
+        for j in ends {
+            let starts = if i == n { 0 } else { j.saturating_sub(MAX_PIECE) };
+            for k in starts..=j {
+                if cost[i - 1][k] == usize::MAX {
+                    continue;
+                }
+                let total = cost[i - 1][k] + !matches(moras[i - 1], &piece(k, j)) as usize;
+                if total < cost[i][j] {
+                    cost[i][j] = total;
+                    split[i][j] = k;
+                }
+            }
+        }
+    }
+
+    let mut segments = Vec::with_capacity(n);
+    let mut end = m;
+    for i in (1..=n).rev() {
+        let start = split[i][end];
+        let answer = piece(start, end);
+        let pair = moras[i - 1];
+        segments.push(DictationSegment {
+            kana: pair.kana.to_string(),
+            expected: pair.romaji.to_string(),
+            correct: matches(pair, &answer),
+            answer,
+        });
+        end = start;
+    }
+    segments.reverse();
+    Ok(segments)
+}
+
+// 这一段听起来是不是这个假名：写的是假名就按读音比，写的是罗马音就看切出来的假名读音一样不一样
+pub fn sounds_like(pair: &KanaPair, piece: &str) -> bool {
+    !piece.trim().is_empty() && heard(piece) == heard(pair.kana)
+}
+
+// 看假名键入罗马音：作答切成假名后逐拍对上题目，每一拍可以是它任何一种写法切出来的假名（ぢ 写 di 或 ji、を 写 o）；
+// 对不上时再和卡片上的罗马音整串比，自己加的卡可能有特别的写法（こんにちは / konnichiwa）
+pub fn typed_matches(kana: &str, romaji: &str, answer: &str) -> bool {
+    if answer.trim().is_empty() {
+        return false;
+    }
+    let spelled = match (romaji_to_kana(answer), split_kana(kana)) {
+        (Some(typed), Ok(moras)) => {
+            let accepted: Vec<Vec<String>> = moras.iter().map(|k| k.readings().filter_map(romaji_to_kana).collect()).collect();
+            spells(&typed, &accepted)
+        }
+        _ => false,
+    };
+    spelled || same_romaji(answer, romaji)
+}
+
+// typed 能不能依次拆成每一拍接受的假名之一
+fn spells(typed: &str, moras: &[Vec<String>]) -> bool {
+    match moras.split_first() {
+        None => typed.is_empty(),
+        Some((first, rest)) => first.iter().any(|k| typed.strip_prefix(k.as_str()).is_some_and(|t| spells(t, rest))),
+    }
+}
+
+// 听音作答：作答和题目都换成平假名、同音的并成一个再比，假名也收
+pub fn heard_matches(kana: &str, romaji: &str, answer: &str) -> bool {
+    !answer.trim().is_empty() && (heard(answer) == heard(kana) || heard(answer) == heard(romaji))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_reading_of_a_mora_is_accepted() {
+        for (kana, romaji, answers) in [
+            ("ぢ", "ji", &["ji", "di"][..]),
+            ("づ", "zu", &["zu", "du"]),
+            ("を", "wo", &["wo", "o"]),
+            ("ん", "n", &["n", "nn", "n'"]),
+            ("ヂ", "ji", &["ji", "di"]),
+        ] {
+            for answer in answers {
+                assert!(typed_matches(kana, romaji, answer), "{} as {}", kana, answer);
+            }
+        }
+        assert!(!typed_matches("を", "wo", "a"));
+        assert!(!typed_matches("ん", "n", " "));
+    }
+
+    #[test]
+    fn readings_mix_per_mora_in_a_word() {
+        for answer in ["hanaji", "hanadi"] {
+            assert!(typed_matches("はなぢ", "hanaji", answer), "{}", answer);
+        }
+        for answer in ["tsuzuki", "tsuduki"] {
+            assert!(typed_matches("つづき", "tsuzuki", answer), "{}", answer);
+        }
+        for answer in ["hon", "honn", "hon'"] {
+            assert!(typed_matches("ほん", "hon", answer), "{}", answer);
+        }
+        // 卡片上的写法整串比也算
+        for answer in ["konnichiwa", "konnichiha", "kon'nichiha"] {
+            assert!(typed_matches("こんにちは", "konnichiwa", answer), "{}", answer);
+        }
+        assert!(!typed_matches("はなぢ", "hanaji", "hanachi"));
+        assert!(!typed_matches("つづき", "tsuzuki", "tsuki"));
+    }
+}
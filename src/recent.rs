@@ -0,0 +1,111 @@
+use std::collections::{HashSet, VecDeque};
+
+use anyhow::Result;
+
+use crate::db::Db;
+
+// 存在 settings 表里，重启后继续生效
+pub const RECENT_SETTING_KEY: &str = "recent_cards";
+
+// 最近展示过的卡片，满了就挤掉最早的。
+// 选卡时跳过这里面的卡，但最多只排除卡池的一半：排除太多的话剩下能抽的卡寥寥无几，
+// 加权抽牌就形同虚设了。
+#[derive(Debug, Clone, Default)]
+pub struct RecentBuffer {
+    capacity: usize,
+    // 实际生效的只有最近的这么多张，见 limit_to_pool
+    active: usize,
+    order: VecDeque<String>,
+    members: HashSet<String>,
+}
+
+impl RecentBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            active: capacity,
+            order: VecDeque::with_capacity(capacity),
+            members: HashSet::with_capacity(capacity),
+        }
+    }
+
+    pub async fn load(db: &Db, capacity: usize) -> Result<Self> {
+        let mut buffer = Self::new(capacity);
+        if let Some(raw) = db.get_setting(RECENT_SETTING_KEY).await? {
+            // 坏数据不影响启动，当作空缓冲
+            let ids: Vec<String> = serde_json::from_str(&raw).unwrap_or_default();
+            for id in ids {
+                buffer.push(id);
+            }
+        }
+        Ok(buffer)
+    }
+
+    pub async fn save(&self, db: &Db) -> Result<()> {
+        db.set_setting(RECENT_SETTING_KEY, &serde_json::to_string(&self.order)?).await
+    }
+
+    // 已经在里面的卡挪到最新的位置
+    pub fn push(&mut self, id: String) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.members.contains(&id) {
+            self.order.retain(|x| *x != id);
+        } else {
+            self.members.insert(id.clone());
+        }
+        self.order.push_back(id);
+
+        while self.order.len() > self.capacity {
+            if let Some(old) = self.order.pop_front() {
+                self.members.remove(&old);
+            }
+        }
+    }
+
+    // 按这一轮能抽到的卡数收紧，最多排除一半；记录本身不丢，换回大卡组时照样生效
+    pub fn limit_to_pool(&mut self, pool_size: usize) {
+        self.active = self.capacity.min(pool_size / 2);
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.position(id).is_some()
+    }
+
+    // 越早见过越小；不在缓冲里返回 None
+    pub fn position(&self, id: &str) -> Option<usize> {
+        if !self.members.contains(id) {
+            return None;
+        }
+        let skip = self.order.len().saturating_sub(self.active);
+        self.order.iter().skip(skip).position(|x| x == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn excludes_at_most_half_the_pool() {
+        let mut buffer = RecentBuffer::new(200);
+        for i in 0..200 {
+            buffer.push(i.to_string());
+        }
+        assert_eq!(buffer.position("0"), Some(0));
+
+        buffer.limit_to_pool(208);
+        // 只排除最近见过的 104 张
+        assert!(buffer.contains("199") && buffer.contains("96"));
+        assert!(!buffer.contains("95"));
+        assert_eq!(buffer.position("96"), Some(0));
+
+        buffer.limit_to_pool(1);
+        assert!(!buffer.contains("199"));
+
+        // 记录没丢，卡池变大又恢复
+        buffer.limit_to_pool(1000);
+        assert_eq!(buffer.position("0"), Some(0));
+    }
+}
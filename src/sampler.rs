@@ -0,0 +1,181 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+
+use crate::combo::MAX_COMBO_LENGTH;
+use crate::db::Card;
+
+// 按“掌握程度”加权抽卡：stability 低、difficulty 高的卡更容易被抽到。
+//
+// 每张卡的权重是 exp(weakness / temperature)，temperature 越高越接近均匀；
+// 最终概率再和均匀分布按 coverage_floor 混合：
+//     p = coverage_floor / n + (1 - coverage_floor) * w / Σw
+// 所以每张卡每轮有 coverage_floor 次的期望出现次数。期望值挡不住连着好几轮都抽不到，
+// 所以还记着每张卡连续几轮没进牌堆，满 ceil(1 / coverage_floor) - 1 轮的下一轮一定放进去，
+// 再熟的卡也不会被饿死（默认 0.2 时至少每 5 轮出现一次）。
+#[derive(Debug, Clone)]
+pub struct WeightedSampler {
+    pub temperature: f64,
+    pub coverage_floor: f64,
+    // 卡片 id → 连续几轮没进牌堆，只在内存里，重启后从头算
+    missed_rounds: HashMap<String, usize>,
+}
+
+impl WeightedSampler {
+    pub fn new(temperature: f64, coverage_floor: f64) -> Self {
+        Self { temperature, coverage_floor, missed_rounds: HashMap::new() }
+    }
+
+    // 连续这么多轮没抽到的卡下一轮必须出现
+    fn max_missed_rounds(&self) -> usize {
+        ((1.0 / self.coverage_floor).ceil() as usize).saturating_sub(1)
+    }
+
+    // 0 表示完全掌握；新卡 (stability = 0) 为 1，再加上 difficulty
+    pub fn weakness(card: &Card) -> f64 {
+        1.0 / (1.0 + card.stability.max(0.0)) + card.difficulty.max(0.0)
+    }
+
+    pub fn probabilities(&self, cards: &[Card]) -> Vec<f64> {
+        let n = cards.len() as f64;
+        let raw: Vec<f64> = cards
+            .iter()
+            // 指数封顶，避免 temperature 很小时溢出成 inf
+            .map(|c| (Self::weakness(c) / self.temperature).min(500.0).exp())
+            .collect();
+        let total: f64 = raw.iter().sum();
+
+        raw.iter()
+            .map(|w| self.coverage_floor / n + (1.0 - self.coverage_floor) * w / total)
+            .collect()
+    }
+
+    // 抽出一轮牌堆：长度等于卡片数，可重复；相邻 MAX_COMBO_LENGTH 张内尽量不重复，避免一张缝合卡里出现同一个假名。
+    // 太久没抽到的卡先各占一个位置，剩下的位置再加权抽
+    pub fn build_deck<R: Rng>(&mut self, cards: &[Card], rng: &mut R) -> Vec<String> {
+        if cards.is_empty() {
+            return Vec::new();
+        }
+
+        let Ok(dist) = WeightedIndex::new(self.probabilities(cards)) else {
+            return cards.iter().map(|c| c.id.clone()).collect();
+        };
+
+        let max_missed = self.max_missed_rounds();
+        let forced: Vec<usize> = (0..cards.len())
+            .filter(|&i| self.missed_rounds.get(&cards[i].id).copied().unwrap_or(0) >= max_missed)
+            .collect();
+
+        let spacing = MAX_COMBO_LENGTH.min(cards.len() - 1);
+        let mut recent: VecDeque<usize> = VecDeque::with_capacity(spacing + 1);
+        let mut picks = Vec::with_capacity(cards.len());
+
+        for _ in forced.len()..cards.len() {
+            let mut idx = dist.sample(rng);
+            // 拒绝采样；极端权重下重抽多次仍撞上就接受
+            for _ in 0..32 {
+                if !recent.contains(&idx) {
+                    break;
+                }
+                idx = dist.sample(rng);
+            }
+
+            picks.push(idx);
+            recent.push_back(idx);
+            if recent.len() > spacing {
+                recent.pop_front();
+            }
+        }
+
+        // 必须出现的卡插到随机位置，尽量不挨着同一张卡
+        for idx in forced {
+            let near = |pos: usize, picks: &[usize]| {
+                picks[pos.saturating_sub(spacing)..(pos + spacing).min(picks.len())].contains(&idx)
+            };
+            let mut pos = rng.gen_range(0..=picks.len());
+            for _ in 0..32 {
+                if !near(pos, &picks) {
+                    break;
+                }
+                pos = rng.gen_range(0..=picks.len());
+            }
+            picks.insert(pos, idx);
+        }
+
+        let drawn: HashSet<usize> = picks.iter().copied().collect();
+        for (i, card) in cards.iter().enumerate() {
+            let missed = self.missed_rounds.entry(card.id.clone()).or_insert(0);
+            *missed = if drawn.contains(&i) { 0 } else { *missed + 1 };
+        }
+
+        picks.into_iter().map(|i| cards[i].id.clone()).collect()
+    }
+
+    // 从候选里抽一张，返回下标
+    pub fn pick_index<R: Rng>(&self, cards: &[Card], rng: &mut R) -> Option<usize> {
+        if cards.is_empty() {
+            return None;
+        }
+        WeightedIndex::new(self.probabilities(cards))
+            .map(|dist| dist.sample(rng))
+            .ok()
+            .or(Some(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn card(id: usize, stability: f64, difficulty: f64) -> Card {
+        Card {
+            id: id.to_string(),
+            kana_char: String::new(),
+            romaji: String::new(),
+            stability,
+            difficulty,
+            last_review: None,
+            meaning: None,
+            tags: String::new(),
+            suspended: false,
+            lapses: 0,
+            buried_until: None,
+            mnemonic: None,
+            mnemonic_image: None,
+        }
+    }
+
+    #[test]
+    fn every_card_shows_up_within_coverage_window() {
+        // 两张很弱的卡几乎占满所有位置，其余都是熟卡
+        let mut cards: Vec<Card> = (0..2).map(|i| card(i, 0.0, 5.0)).collect();
+        cards.extend((2..40).map(|i| card(i, 365.0, 0.0)));
+
+        for coverage_floor in [0.2, 0.1, 0.05] {
+            let mut sampler = WeightedSampler::new(0.1, coverage_floor);
+            let window = (1.0 / coverage_floor).ceil() as usize;
+            let mut rng = StdRng::seed_from_u64(7);
+            let mut last_seen: HashMap<String, usize> = HashMap::new();
+
+            for round in 1..=window * 4 {
+                for id in sampler.build_deck(&cards, &mut rng) {
+                    last_seen.insert(id, round);
+                }
+                for card in &cards {
+                    let seen = last_seen.get(&card.id).copied().unwrap_or(0);
+                    assert!(
+                        round - seen < window,
+                        "card {} missing for {} rounds (coverage_floor {})",
+                        card.id,
+                        round - seen,
+                        coverage_floor
+                    );
+                }
+            }
+        }
+    }
+}
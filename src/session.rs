@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+pub use kana_types::session::{SessionMode, SessionSummary};
+
+// 当前会话的进度；一张缝合卡算一次作答
+#[derive(Debug, Clone)]
+pub struct SessionProgress {
+    pub mode: SessionMode,
+    pub started_at: Instant,
+    pub answered: usize,
+    pub correct: usize,
+    finished_at: Option<Instant>,
+    // Due 模式：还没答对过的到期卡
+    pub due_pending: HashSet<String>,
+    due_total: usize,
+}
+
+impl SessionProgress {
+    pub fn new(mode: SessionMode, due_ids: Vec<String>) -> Self {
+        let due_pending: HashSet<String> = due_ids.into_iter().collect();
+        Self {
+            mode,
+            started_at: Instant::now(),
+            answered: 0,
+            correct: 0,
+            finished_at: None,
+            due_total: due_pending.len(),
+            due_pending,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished_at.is_some() || self.goal_reached(Instant::now())
+    }
+
+    fn goal_reached(&self, now: Instant) -> bool {
+        match self.mode {
+            SessionMode::Open => false,
+            SessionMode::Sprint { seconds } => now.duration_since(self.started_at) >= Duration::from_secs(seconds),
+            SessionMode::Count { cards } => self.answered >= cards,
+            SessionMode::Due => self.due_pending.is_empty(),
+        }
+    }
+
+    // 会话结束后的作答不计入统计；返回这次是否被计入。
+    // member_correct 和 card_ids 一一对应：整张卡全对才算答对，但 Due 模式里答对的组成卡各自算完成
+    pub fn record(&mut self, card_ids: &[String], member_correct: &[bool]) -> bool {
+        if self.is_finished() {
+            self.mark_finished();
+            return false;
+        }
+
+        self.answered += 1;
+        if member_correct.iter().all(|&ok| ok) {
+            self.correct += 1;
+        }
+        for (id, _) in card_ids.iter().zip(member_correct).filter(|(_, &ok)| ok) {
+            self.due_pending.remove(id);
+        }
+        if self.goal_reached(Instant::now()) {
+            self.mark_finished();
+        }
+        true
+    }
+
+    fn mark_finished(&mut self) {
+        if self.finished_at.is_some() {
+            return;
+        }
+        // 冲刺的结束时间按时限算，不按最后一次请求算
+        self.finished_at = Some(match self.mode {
+            SessionMode::Sprint { seconds } => self.started_at + Duration::from_secs(seconds),
+            _ => Instant::now(),
+        });
+    }
+
+    pub fn summary(&self) -> SessionSummary {
+        let finished = self.is_finished();
+        let mut elapsed = self.finished_at.unwrap_or_else(Instant::now).duration_since(self.started_at);
+        let mut remaining_secs = None;
+        if let SessionMode::Sprint { seconds } = self.mode {
+            let limit = Duration::from_secs(seconds);
+            elapsed = elapsed.min(limit);
+            remaining_secs = Some((limit - elapsed).as_secs_f64());
+        }
+
+        let elapsed_secs = elapsed.as_secs_f64();
+        let is_due = self.mode == SessionMode::Due;
+
+        SessionSummary {
+            mode: self.mode.clone(),
+            finished,
+            elapsed_secs,
+            remaining_secs,
+            answered: self.answered,
+            correct: self.correct,
+            accuracy: if self.answered > 0 { self.correct as f64 / self.answered as f64 } else { 0.0 },
+            cards_per_minute: if elapsed_secs > 0.0 { self.answered as f64 * 60.0 / elapsed_secs } else { 0.0 },
+            due_total: is_due.then_some(self.due_total),
+            due_remaining: is_due.then_some(self.due_pending.len()),
+        }
+    }
+}
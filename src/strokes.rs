@@ -0,0 +1,493 @@
+use kana_types::{GlyphStrokes, StrokeData, STROKE_VIEW_BOX};
+
+use crate::data::unvoiced;
+
+pub struct KanaStrokes {
+    pub kana: &'static str,
+    pub strokes: &'static [&'static str],
+}
+
+// 清音的笔顺，109 × 109 的格子里手绘的简化字形，只用绝对坐标的 M / L / Q / C。
+// 每笔从起笔写到收笔，笔数和顺序按教科书；浊音、半浊音和拗音由 stroke_data 拼出来
+pub const BASE_STROKES: &[KanaStrokes] = &[
+    // --- HIRAGANA ---
+    KanaStrokes { kana: "あ", strokes: &[
+        "M28,32 Q50,34 76,28",
+        "M48,16 Q46,50 54,90",
+        "M68,48 Q58,80 40,84 Q24,86 28,70 Q36,54 62,52 Q86,52 86,72 Q86,88 66,94",
+    ] },
+    KanaStrokes { kana: "い", strokes: &[
+        "M28,28 Q26,60 34,78 Q38,84 42,74",
+        "M70,34 Q82,48 84,66",
+    ] },
+    KanaStrokes { kana: "う", strokes: &[
+        "M42,16 Q54,20 64,24",
+        "M34,44 Q52,34 68,40 Q82,50 72,70 Q62,86 42,94",
+    ] },
+    KanaStrokes { kana: "え", strokes: &[
+        "M44,16 Q56,20 66,24",
+        "M30,44 L70,40 L30,88 Q44,70 52,74 Q58,82 64,86 L84,86",
+    ] },
+    KanaStrokes { kana: "お", strokes: &[
+        "M24,34 Q44,36 64,30",
+        "M42,14 L42,74 Q42,86 34,84 Q22,80 26,68 Q36,52 60,52 Q84,54 82,74 Q78,90 58,94",
+        "M74,24 Q82,28 88,36",
+    ] },
+    KanaStrokes { kana: "か", strokes: &[
+        "M20,42 Q46,36 62,38 Q72,42 68,62 Q64,80 54,86 Q48,84 44,78",
+        "M44,18 Q40,56 24,88",
+        "M78,32 Q88,44 92,58",
+    ] },
+    KanaStrokes { kana: "き", strokes: &[
+        "M30,30 Q50,32 70,26",
+        "M28,48 Q52,48 76,42",
+        "M44,14 Q58,44 74,66",
+        "M36,66 Q30,78 42,86 Q54,92 72,90",
+    ] },
+    KanaStrokes { kana: "く", strokes: &[
+        "M66,16 L32,52 L68,92",
+    ] },
+    KanaStrokes { kana: "け", strokes: &[
+        "M28,22 Q22,56 28,84",
+        "M46,40 Q64,42 86,38",
+        "M70,16 Q74,60 56,94",
+    ] },
+    KanaStrokes { kana: "こ", strokes: &[
+        "M34,30 Q54,34 70,28 Q66,40 60,44",
+        "M30,70 Q36,84 56,84 L80,82",
+    ] },
+    KanaStrokes { kana: "さ", strokes: &[
+        "M28,36 Q50,38 76,30",
+        "M44,14 Q58,44 72,62",
+        "M38,66 Q30,78 42,86 Q54,92 72,90",
+    ] },
+    KanaStrokes { kana: "し", strokes: &[
+        "M36,16 L36,70 Q38,90 58,88 Q72,84 82,70",
+    ] },
+    KanaStrokes { kana: "す", strokes: &[
+        "M18,36 Q54,34 92,32",
+        "M56,14 L56,58 Q56,70 46,70 Q36,68 38,56 Q42,46 52,48 Q58,54 58,66 Q54,84 40,96",
+    ] },
+    KanaStrokes { kana: "せ", strokes: &[
+        "M16,44 Q50,40 92,38",
+        "M70,18 L70,58 Q70,66 60,64",
+        "M38,18 L38,76 Q40,88 60,88 L82,86",
+    ] },
+    KanaStrokes { kana: "そ", strokes: &[
+        "M34,22 L66,18 L24,52 Q54,42 84,42 Q58,52 54,66 Q54,86 74,92",
+    ] },
+    KanaStrokes { kana: "た", strokes: &[
+        "M20,34 Q36,36 56,30",
+        "M42,14 Q38,52 22,86",
+        "M56,50 Q70,46 84,48",
+        "M54,72 Q58,84 74,84 L88,82",
+    ] },
+    KanaStrokes { kana: "ち", strokes: &[
+        "M24,34 Q50,36 80,30",
+        "M46,14 L36,62 Q56,50 72,56 Q86,66 78,80 Q68,92 46,94",
+    ] },
+    KanaStrokes { kana: "つ", strokes: &[
+        "M16,46 Q54,28 80,40 Q96,56 80,74 Q68,84 46,88",
+    ] },
+    KanaStrokes { kana: "て", strokes: &[
+        "M18,32 Q56,30 90,24 Q56,40 54,66 Q56,86 76,92",
+    ] },
+    KanaStrokes { kana: "と", strokes: &[
+        "M36,16 Q42,36 50,48",
+        "M76,36 Q44,46 36,62 Q32,80 54,86 L82,86",
+    ] },
+    KanaStrokes { kana: "な", strokes: &[
+        "M18,34 Q34,36 52,30",
+        "M38,14 Q34,50 18,76",
+        "M68,36 Q80,42 86,50",
+        "M64,54 L64,84 Q62,94 48,92 Q36,88 44,80 Q58,74 86,92",
+    ] },
+    KanaStrokes { kana: "に", strokes: &[
+        "M26,22 Q20,56 28,86",
+        "M50,34 Q64,36 78,32",
+        "M50,66 Q54,80 68,80 L82,80",
+    ] },
+    KanaStrokes { kana: "ぬ", strokes: &[
+        "M24,26 Q30,56 40,80",
+        "M58,18 Q50,60 28,86 Q18,90 18,78 Q22,52 56,44 Q88,44 86,70 Q84,88 66,86 Q54,82 62,76 Q74,74 88,90",
+    ] },
+    KanaStrokes { kana: "ね", strokes: &[
+        "M36,14 L36,96",
+        "M16,38 L50,38 L20,84 Q44,52 66,48 Q88,50 86,72 Q84,88 68,86 Q56,82 64,76 Q76,74 92,90",
+    ] },
+    KanaStrokes { kana: "の", strokes: &[
+        "M52,32 Q48,66 32,84 Q18,90 18,68 Q22,34 58,30 Q90,34 88,64 Q84,86 60,92",
+    ] },
+    KanaStrokes { kana: "は", strokes: &[
+        "M24,20 Q18,56 26,88",
+        "M44,38 Q64,40 84,36",
+        "M66,16 L66,76 Q66,90 54,90 Q40,88 44,80 Q56,74 88,90",
+    ] },
+    KanaStrokes { kana: "ひ", strokes: &[
+        "M18,32 L40,30 Q28,56 32,76 Q40,90 56,84 Q70,72 72,30 Q76,60 92,68",
+    ] },
+    KanaStrokes { kana: "ふ", strokes: &[
+        "M44,16 Q54,22 58,30",
+        "M56,34 Q40,50 58,66 Q66,78 54,88 Q44,92 36,84",
+        "M24,62 Q18,74 16,84",
+        "M78,60 Q88,70 92,82",
+    ] },
+    KanaStrokes { kana: "へ", strokes: &[
+        "M14,62 L38,34 L94,76",
+    ] },
+    KanaStrokes { kana: "ほ", strokes: &[
+        "M22,20 Q16,56 24,88",
+        "M42,26 Q62,28 84,24",
+        "M42,50 Q62,52 84,48",
+        "M64,26 L64,80 Q64,92 52,90 Q40,88 44,80 Q56,74 88,90",
+    ] },
+    KanaStrokes { kana: "ま", strokes: &[
+        "M26,28 Q54,30 82,26",
+        "M30,50 Q54,52 80,48",
+        "M56,12 L56,78 Q56,92 44,90 Q32,88 36,80 Q48,74 84,90",
+    ] },
+    KanaStrokes { kana: "み", strokes: &[
+        "M24,30 L60,28 Q40,62 30,80 Q24,92 40,80 Q60,56 86,68",
+        "M72,44 Q72,74 58,94",
+    ] },
+    KanaStrokes { kana: "む", strokes: &[
+        "M20,34 Q36,36 54,30",
+        "M38,14 L38,60 Q38,72 28,70 Q18,66 24,56 Q34,50 38,64 L38,80 Q40,92 62,90 Q78,86 78,72",
+        "M76,30 Q84,36 88,44",
+    ] },
+    KanaStrokes { kana: "め", strokes: &[
+        "M26,28 Q30,54 40,76",
+        "M64,16 Q56,58 30,84 Q18,90 18,76 Q22,50 58,44 Q90,46 86,70 Q82,86 60,92",
+    ] },
+    KanaStrokes { kana: "も", strokes: &[
+        "M48,14 L38,70 Q38,92 58,90 Q78,86 80,62 Q80,46 72,40",
+        "M24,34 Q38,36 60,32",
+        "M22,56 Q40,58 62,54",
+    ] },
+    KanaStrokes { kana: "や", strokes: &[
+        "M20,44 Q54,30 76,36 Q90,44 80,58 Q72,64 62,60",
+        "M48,16 Q54,22 58,28",
+        "M34,22 L56,94",
+    ] },
+    KanaStrokes { kana: "ゆ", strokes: &[
+        "M22,30 Q18,64 28,76 Q34,44 56,40 Q82,40 82,62 Q80,80 56,74",
+        "M52,16 Q60,52 42,94",
+    ] },
+    KanaStrokes { kana: "よ", strokes: &[
+        "M52,38 Q62,40 78,36",
+        "M52,14 L52,76 Q52,90 40,88 Q28,86 32,78 Q44,72 84,90",
+    ] },
+    KanaStrokes { kana: "ら", strokes: &[
+        "M40,16 Q50,20 58,26",
+        "M32,34 Q26,56 30,68 Q50,50 70,58 Q84,72 70,86 Q58,94 42,92",
+    ] },
+    KanaStrokes { kana: "り", strokes: &[
+        "M34,18 Q28,44 32,56 Q36,62 40,52",
+        "M66,16 Q76,54 62,78 Q54,90 40,94",
+    ] },
+    KanaStrokes { kana: "る", strokes: &[
+        "M28,22 L68,20 L20,76 Q44,52 68,54 Q88,62 82,80 Q72,94 54,90 Q44,84 52,78 Q62,76 66,86",
+    ] },
+    KanaStrokes { kana: "れ", strokes: &[
+        "M36,14 L36,96",
+        "M16,38 L50,38 L20,84 Q46,52 64,46 Q70,50 66,70 Q64,86 74,88 Q84,88 92,78",
+    ] },
+    KanaStrokes { kana: "ろ", strokes: &[
+        "M28,22 L68,20 L20,76 Q44,52 68,54 Q88,62 82,80 Q72,94 44,92",
+    ] },
+    KanaStrokes { kana: "わ", strokes: &[
+        "M36,14 L36,96",
+        "M16,38 L50,38 L20,84 Q44,52 66,46 Q90,46 88,70 Q84,90 58,92",
+    ] },
+    KanaStrokes { kana: "を", strokes: &[
+        "M26,28 Q44,30 64,26",
+        "M44,14 Q38,36 24,56 Q46,42 56,48 Q60,56 52,64",
+        "M80,46 Q50,58 48,72 Q48,86 66,88 L84,86",
+    ] },
+    KanaStrokes { kana: "ん", strokes: &[
+        "M56,14 L18,92 Q34,56 48,56 Q58,58 56,76 Q56,88 68,88 Q82,86 90,68",
+    ] },
+    // --- KATAKANA ---
+    KanaStrokes { kana: "ア", strokes: &[
+        "M22,24 L84,24 Q78,40 64,50",
+        "M52,36 Q50,70 28,92",
+    ] },
+    KanaStrokes { kana: "イ", strokes: &[
+        "M70,14 Q52,46 18,64",
+        "M52,44 L52,96",
+    ] },
+    KanaStrokes { kana: "ウ", strokes: &[
+        "M54,12 L54,28",
+        "M24,32 L24,50",
+        "M24,32 L84,32 Q78,66 44,94",
+    ] },
+    KanaStrokes { kana: "エ", strokes: &[
+        "M26,26 L82,26",
+        "M54,26 L54,82",
+        "M16,84 L92,84",
+    ] },
+    KanaStrokes { kana: "オ", strokes: &[
+        "M16,40 L92,40",
+        "M62,14 L62,86 Q62,94 52,90",
+        "M60,42 Q44,70 18,86",
+    ] },
+    KanaStrokes { kana: "カ", strokes: &[
+        "M20,36 L80,36 Q80,74 64,88 Q58,90 52,84",
+        "M48,14 Q46,60 20,92",
+    ] },
+    KanaStrokes { kana: "キ", strokes: &[
+        "M20,36 L82,32",
+        "M14,60 L92,56",
+        "M46,14 L58,94",
+    ] },
+    KanaStrokes { kana: "ク", strokes: &[
+        "M44,14 Q36,34 18,48",
+        "M40,28 L80,28 Q70,70 30,94",
+    ] },
+    KanaStrokes { kana: "ケ", strokes: &[
+        "M40,14 Q32,34 16,50",
+        "M34,36 L90,36",
+        "M64,36 Q60,72 36,94",
+    ] },
+    KanaStrokes { kana: "コ", strokes: &[
+        "M24,28 L82,28 L82,80",
+        "M24,80 L84,80",
+    ] },
+    KanaStrokes { kana: "サ", strokes: &[
+        "M12,40 L96,40",
+        "M34,16 L34,58",
+        "M72,14 L72,56 Q68,82 42,96",
+    ] },
+    KanaStrokes { kana: "シ", strokes: &[
+        "M24,24 L40,34",
+        "M18,46 L36,56",
+        "M26,90 Q62,74 88,30",
+    ] },
+    KanaStrokes { kana: "ス", strokes: &[
+        "M24,26 L78,26 Q62,64 18,92",
+        "M58,60 L88,88",
+    ] },
+    KanaStrokes { kana: "セ", strokes: &[
+        "M14,50 L90,42 Q80,56 68,64",
+        "M36,16 L36,80 Q36,90 50,90 L86,90",
+    ] },
+    KanaStrokes { kana: "ソ", strokes: &[
+        "M22,26 L36,48",
+        "M82,22 Q74,64 32,94",
+    ] },
+    KanaStrokes { kana: "タ", strokes: &[
+        "M44,14 Q36,34 18,48",
+        "M40,28 L80,28 Q70,70 30,94",
+        "M34,50 L70,68",
+    ] },
+    KanaStrokes { kana: "チ", strokes: &[
+        "M76,14 Q54,24 28,28",
+        "M14,50 L94,50",
+        "M54,26 Q56,74 34,94",
+    ] },
+    KanaStrokes { kana: "ツ", strokes: &[
+        "M18,30 L30,48",
+        "M42,24 L52,42",
+        "M88,24 Q78,66 34,94",
+    ] },
+    KanaStrokes { kana: "テ", strokes: &[
+        "M28,22 L80,22",
+        "M16,44 L92,44",
+        "M54,44 Q52,76 34,94",
+    ] },
+    KanaStrokes { kana: "ト", strokes: &[
+        "M40,14 L40,94",
+        "M42,44 L80,62",
+    ] },
+    KanaStrokes { kana: "ナ", strokes: &[
+        "M14,40 L94,40",
+        "M56,14 Q56,70 30,94",
+    ] },
+    KanaStrokes { kana: "ニ", strokes: &[
+        "M26,30 L82,30",
+        "M16,80 L92,80",
+    ] },
+    KanaStrokes { kana: "ヌ", strokes: &[
+        "M24,26 L80,26 Q66,66 20,92",
+        "M38,46 Q64,62 84,88",
+    ] },
+    KanaStrokes { kana: "ネ", strokes: &[
+        "M52,12 L52,26",
+        "M24,30 L80,30 Q58,56 16,76",
+        "M52,52 L52,96",
+        "M62,58 L86,76",
+    ] },
+    KanaStrokes { kana: "ノ", strokes: &[
+        "M80,16 Q66,66 22,94",
+    ] },
+    KanaStrokes { kana: "ハ", strokes: &[
+        "M38,30 Q30,60 14,82",
+        "M66,28 Q80,56 94,80",
+    ] },
+    KanaStrokes { kana: "ヒ", strokes: &[
+        "M84,44 L32,58",
+        "M32,16 L32,82 Q32,90 44,90 L86,90",
+    ] },
+    KanaStrokes { kana: "フ", strokes: &[
+        "M20,24 L84,24 Q76,68 30,94",
+    ] },
+    KanaStrokes { kana: "ヘ", strokes: &[
+        "M14,62 L38,34 L94,76",
+    ] },
+    KanaStrokes { kana: "ホ", strokes: &[
+        "M16,36 L92,36",
+        "M54,14 L54,86 Q54,94 44,90",
+        "M34,52 Q30,68 18,80",
+        "M72,52 Q80,66 90,78",
+    ] },
+    KanaStrokes { kana: "マ", strokes: &[
+        "M18,28 L86,28 Q72,54 48,66",
+        "M38,50 L66,86",
+    ] },
+    KanaStrokes { kana: "ミ", strokes: &[
+        "M30,18 L76,30",
+        "M34,44 L72,54",
+        "M26,70 L80,88",
+    ] },
+    KanaStrokes { kana: "ム", strokes: &[
+        "M48,14 Q38,50 18,82 L84,76",
+        "M66,56 L88,90",
+    ] },
+    KanaStrokes { kana: "メ", strokes: &[
+        "M76,14 Q62,62 18,92",
+        "M32,40 Q62,58 84,84",
+    ] },
+    KanaStrokes { kana: "モ", strokes: &[
+        "M24,24 L82,24",
+        "M14,48 L92,48",
+        "M48,24 L48,80 Q48,90 60,90 L88,90",
+    ] },
+    KanaStrokes { kana: "ヤ", strokes: &[
+        "M14,42 L88,36 Q76,50 64,56",
+        "M34,16 L50,94",
+    ] },
+    KanaStrokes { kana: "ユ", strokes: &[
+        "M24,34 L74,34 L72,82",
+        "M12,84 L96,84",
+    ] },
+    KanaStrokes { kana: "ヨ", strokes: &[
+        "M24,24 L80,24 L80,86",
+        "M26,54 L80,54",
+        "M24,86 L82,86",
+    ] },
+    KanaStrokes { kana: "ラ", strokes: &[
+        "M28,18 L80,18",
+        "M20,40 L84,40 Q76,76 34,94",
+    ] },
+    KanaStrokes { kana: "リ", strokes: &[
+        "M32,20 L32,62",
+        "M74,14 L74,54 Q70,82 46,96",
+    ] },
+    KanaStrokes { kana: "ル", strokes: &[
+        "M38,20 Q38,66 14,90",
+        "M58,16 L58,84 Q72,76 92,58",
+    ] },
+    KanaStrokes { kana: "レ", strokes: &[
+        "M30,14 L30,86 Q60,76 90,50",
+    ] },
+    KanaStrokes { kana: "ロ", strokes: &[
+        "M24,24 L24,86",
+        "M24,24 L84,24 L84,86",
+        "M24,84 L84,84",
+    ] },
+    KanaStrokes { kana: "ワ", strokes: &[
+        "M22,24 L22,48",
+        "M22,26 L84,26 Q78,66 36,94",
+    ] },
+    KanaStrokes { kana: "ヲ", strokes: &[
+        "M22,24 L80,24",
+        "M20,48 L80,48",
+        "M80,24 Q74,68 32,94",
+    ] },
+    KanaStrokes { kana: "ン", strokes: &[
+        "M20,26 L40,40",
+        "M22,90 Q64,74 88,30",
+    ] },
+];
+
+// 浊点两笔、半浊点一个圈，都在格子右上角
+const DAKUTEN: [&str; 2] = ["M80,8 L86,22", "M92,6 L98,18"];
+const HANDAKUTEN: &str = "M92,7 C96,7 99,10 99,14 C99,18 96,21 92,21 C88,21 85,18 85,14 C85,10 88,7 92,7";
+
+// 小字 ゃ ゅ ょ 比大字的码位小 1；缩到六成放在格子左下
+const SMALL_KANA: &str = "ゃゅょャュョ";
+const SMALL_SCALE: f32 = 0.62;
+const SMALL_OFFSET: (f32, f32) = (20.0, 38.0);
+
+fn base_strokes(kana: char) -> Option<&'static [&'static str]> {
+    let mut buf = [0u8; 4];
+    let kana: &str = kana.encode_utf8(&mut buf);
+    BASE_STROKES.iter().find(|k| k.kana == kana).map(|k| k.strokes)
+}
+
+// 解析成 (命令, 坐标点)；只认这里用到的绝对坐标命令，数字两两一组就是 (x, y)
+pub fn parse_path(path: &str) -> Vec<(char, Vec<(f32, f32)>)> {
+    let mut segments: Vec<(char, Vec<f32>)> = Vec::new();
+    let mut number = String::new();
+    for c in path.chars().chain(std::iter::once(' ')) {
+        if c.is_ascii_digit() || c == '.' || c == '-' {
+            number.push(c);
+            continue;
+        }
+        if let (Ok(n), Some((_, numbers))) = (number.parse::<f32>(), segments.last_mut()) {
+            numbers.push(n);
+        }
+        number.clear();
+        if c.is_ascii_alphabetic() {
+            segments.push((c, Vec::new()));
+        }
+    }
+    segments
+        .into_iter()
+        .map(|(cmd, numbers)| (cmd, numbers.chunks_exact(2).map(|p| (p[0], p[1])).collect()))
+        .collect()
+}
+
+fn transform(path: &str, scale: f32, (dx, dy): (f32, f32)) -> String {
+    let round = |v: f32| (v * 10.0).round() / 10.0;
+    parse_path(path)
+        .into_iter()
+        .map(|(cmd, points)| {
+            let points: Vec<String> = points
+                .iter()
+                .map(|(x, y)| format!("{},{}", round(x * scale + dx), round(y * scale + dy)))
+                .collect();
+            format!("{}{}", cmd, points.join(" "))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn glyph(kana: char) -> Option<GlyphStrokes> {
+    let strokes: Vec<String> = if let Some(strokes) = base_strokes(kana) {
+        strokes.iter().map(|s| s.to_string()).collect()
+    } else if SMALL_KANA.contains(kana) {
+        let big = char::from_u32(kana as u32 + 1)?;
+        base_strokes(big)?.iter().map(|s| transform(s, SMALL_SCALE, SMALL_OFFSET)).collect()
+    } else {
+        let (base, handakuten) = unvoiced(kana, |c| base_strokes(c).is_some())?;
+        let mut strokes: Vec<String> = base_strokes(base)?.iter().map(|s| s.to_string()).collect();
+        if handakuten {
+            strokes.push(HANDAKUTEN.to_string());
+        } else {
+            strokes.extend(DAKUTEN.iter().map(|s| s.to_string()));
+        }
+        strokes
+    };
+    Some(GlyphStrokes { kana: kana.to_string(), strokes })
+}
+
+// 逐字拼笔顺，KANA_DATA 里的假名都有；有一个字没有数据就返回 Err(那个字)
+pub fn stroke_data(kana: &str) -> Result<StrokeData, char> {
+    let glyphs = kana
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| glyph(c).ok_or(c))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(StrokeData { kana_char: kana.to_string(), view_box: STROKE_VIEW_BOX.to_string(), glyphs })
+}
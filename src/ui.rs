@@ -0,0 +1,243 @@
+use std::io::{self, Write};
+use std::time::Instant;
+
+use crossterm::cursor::MoveTo;
+use crossterm::execute;
+use crossterm::style::Stylize;
+use crossterm::terminal::{self, Clear, ClearType};
+use unicode_width::UnicodeWidthStr;
+
+use crate::app::{App, Submission};
+use kana_types::MnemonicNote;
+use crate::db::{Card, Grade};
+use crate::feedback::FeedbackGenerator;
+use crate::reading;
+use crate::session::SessionMode;
+
+// 终端模式：和 Web 端共用同一个 App 状态机和 Db 调度，只是换了个界面。
+// 输入按行读取，tmux / SSH 下也能正常用输入法。
+
+#[derive(Default)]
+struct SessionStats {
+    answered: usize,
+    correct: usize,
+    streak: usize,
+    best_streak: usize,
+}
+
+impl SessionStats {
+    fn record(&mut self, correct: bool) {
+        self.answered += 1;
+        if correct {
+            self.correct += 1;
+            self.streak += 1;
+            self.best_streak = self.best_streak.max(self.streak);
+        } else {
+            self.streak = 0;
+        }
+    }
+
+    fn accuracy(&self) -> f64 {
+        if self.answered == 0 {
+            0.0
+        } else {
+            self.correct as f64 * 100.0 / self.answered as f64
+        }
+    }
+}
+
+pub async fn run(app: &mut App) -> anyhow::Result<()> {
+    let mut stdout = io::stdout();
+    let mut stats = SessionStats::default();
+
+    loop {
+        if app.progress.is_finished() {
+            break;
+        }
+        let Some(ticket) = app.current().await else {
+            if !app.progress.is_finished() {
+                println!("没有可以练习的卡片。");
+            }
+            break;
+        };
+        let card = ticket.card;
+
+        draw_card(&mut stdout, app, &card, &stats)?;
+        let shown_at = Instant::now();
+
+        let Some(input) = read_line().await? else { break };
+        let response_ms = shown_at.elapsed().as_millis() as u64;
+        let input = input.trim().to_string();
+        if is_quit(&input) {
+            break;
+        }
+
+        let correct = reading::typed_matches(&card.kana_char, &card.romaji, &input);
+        app.user_input = input.clone();
+        if correct {
+            app.current_feedback = Some("✓".to_string());
+            app.feedback_detail.clear();
+        } else {
+            app.current_feedback = Some(format!("✗ {}", card.romaji));
+            app.feedback_detail = FeedbackGenerator::generate_explanation(&card.kana_char, &card.romaji, &input);
+        }
+
+        // 先把反馈画出来，submit 会让状态机前进到下一张
+        draw_feedback(&mut stdout, app, correct)?;
+        stats.record(correct);
+        let Submission::Accepted(answered) = app.submit(&ticket.id, correct, Some(response_ms)).await? else {
+            anyhow::bail!("ticket for {} was rejected", card.kana_char);
+        };
+        draw_mnemonics(&answered.mnemonics)?;
+        if answered.outcome.grade == Grade::Hard {
+            println!("{}", centered(&format!("偏慢 ({:.1} 秒)，按 Hard 计", response_ms as f64 / 1000.0), term_width()).yellow());
+            println!();
+        }
+        if app.progress.is_finished() {
+            break;
+        }
+
+        print!("{}", "[Enter] 下一张  :q 退出".dark_grey());
+        stdout.flush()?;
+        match read_line().await? {
+            Some(line) if !is_quit(line.trim()) => {}
+            _ => break,
+        }
+    }
+
+    let summary = app.progress.summary();
+    println!();
+    if summary.finished {
+        println!("{}", "本轮目标完成！".green().bold());
+    }
+    println!(
+        "本次练习 {} 张，正确 {} 张 ({:.0}%)，最长连对 {}，用时 {:.0} 秒，每分钟 {:.1} 张。",
+        stats.answered,
+        stats.correct,
+        stats.accuracy(),
+        stats.best_streak,
+        summary.elapsed_secs,
+        summary.cards_per_minute
+    );
+    Ok(())
+}
+
+fn is_quit(input: &str) -> bool {
+    matches!(input, ":q" | ":quit" | ":exit")
+}
+
+// 阻塞读一行放到专门的线程里，不占 tokio 的工作线程；None 表示 EOF (Ctrl-D)
+async fn read_line() -> io::Result<Option<String>> {
+    tokio::task::spawn_blocking(|| {
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line)? {
+            0 => Ok(None),
+            _ => Ok(Some(line)),
+        }
+    })
+    .await
+    .map_err(io::Error::other)?
+}
+
+fn term_width() -> usize {
+    terminal::size().map(|(w, _)| w as usize).unwrap_or(80).max(20)
+}
+
+// 按显示宽度居中；假名是全角，一个字占两列
+fn centered(text: &str, width: usize) -> String {
+    let pad = width.saturating_sub(UnicodeWidthStr::width(text)) / 2;
+    format!("{}{}", " ".repeat(pad), text)
+}
+
+fn draw_card(out: &mut impl Write, app: &App, card: &Card, stats: &SessionStats) -> io::Result<()> {
+    let width = term_width();
+    execute!(out, Clear(ClearType::All), MoveTo(0, 0))?;
+
+    let progress = if app.is_review_effective() {
+        format!("复习错题 · 剩余 {}", app.cycle_mistakes.len())
+    } else {
+        format!(
+            "第 {}/{} 组 · 牌堆剩余 {} · 错题 {}",
+            app.batch_counter,
+            app.estimated_total_batches,
+            app.deck_queue.len(),
+            app.cycle_mistakes.len()
+        )
+    };
+    println!("{}", progress.dark_grey());
+    if let Some(goal) = goal_line(app) {
+        println!("{}", goal.cyan());
+    }
+    println!(
+        "{}",
+        format!(
+            "正确 {}/{} ({:.0}%) · 连对 {}",
+            stats.correct,
+            stats.answered,
+            stats.accuracy(),
+            stats.streak
+        )
+        .dark_grey()
+    );
+    println!();
+
+    // 卡片外框按内容的显示宽度画，保证全角字符对齐
+    let inner = UnicodeWidthStr::width(card.kana_char.as_str()) + 8;
+    let top = format!("┌{}┐", "─".repeat(inner));
+    let blank = format!("│{}│", " ".repeat(inner));
+    let body = format!("│    {}    │", card.kana_char);
+    let bottom = format!("└{}┘", "─".repeat(inner));
+
+    println!("{}", centered(&top, width));
+    println!("{}", centered(&blank, width));
+    println!("{}", centered(&body, width).bold());
+    println!("{}", centered(&blank, width));
+    println!("{}", centered(&bottom, width));
+    println!();
+
+    print!("{}", centered("> ", width / 2 + 1));
+    out.flush()
+}
+
+fn goal_line(app: &App) -> Option<String> {
+    let summary = app.progress.summary();
+    match summary.mode {
+        SessionMode::Open => None,
+        SessionMode::Sprint { .. } => Some(format!("冲刺 · 剩余 {:.0} 秒", summary.remaining_secs.unwrap_or_default())),
+        SessionMode::Count { cards } => Some(format!("目标 {}/{} 张", summary.answered, cards)),
+        SessionMode::Due => Some(format!("到期卡剩余 {}/{}", summary.due_remaining.unwrap_or_default(), summary.due_total.unwrap_or_default())),
+    }
+}
+
+// 终端里只显示文字助记，图片留给网页
+fn draw_mnemonics(notes: &[MnemonicNote]) -> io::Result<()> {
+    let width = term_width();
+    let notes: Vec<_> = notes.iter().filter_map(|n| n.text.as_ref().map(|t| (&n.kana_char, t))).collect();
+    if notes.is_empty() {
+        return Ok(());
+    }
+    for (kana, text) in notes {
+        for line in textwrap::wrap(&format!("{} {}", kana, text), width.saturating_sub(4)) {
+            println!("  {}", line.cyan());
+        }
+    }
+    println!();
+    io::stdout().flush()
+}
+
+fn draw_feedback(out: &mut impl Write, app: &App, correct: bool) -> io::Result<()> {
+    let width = term_width();
+    let verdict = app.current_feedback.clone().unwrap_or_default();
+
+    if correct {
+        println!("{}", centered(&verdict, width).green().bold());
+    } else {
+        println!("{}", centered(&verdict, width).red().bold());
+        println!();
+        for line in textwrap::wrap(&app.feedback_detail, width.saturating_sub(4)) {
+            println!("  {}", line.yellow());
+        }
+    }
+    println!();
+    out.flush()
+}
@@ -0,0 +1,199 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+};
+use kana_types::{ClientMessage, DictationSegment, Prompt, ServerMessage};
+
+use crate::api::ApiState;
+use crate::app::{Answered, App, Submission};
+use crate::audio;
+use crate::auth;
+use crate::dictation;
+use crate::error::{ApiError, ErrorBody};
+use crate::feedback::FeedbackGenerator;
+use crate::handwriting;
+use crate::reading;
+
+// 协议本身（ClientMessage / ServerMessage）定义在 kana-types 里，前端用的是同一份
+impl From<ApiError> for ServerMessage {
+    fn from(e: ApiError) -> Self {
+        if let ApiError::Internal(inner) = &e {
+            log::error!("internal error: {:#}", inner);
+        }
+        let body = e.body();
+        ServerMessage::Error { code: body.code, message: body.message }
+    }
+}
+
+// 消息格式见 components 里的 ClientMessage / ServerMessage
+#[utoipa::path(
+    get,
+    path = "/api/v1/ws",
+    tag = "session",
+    responses(
+        (status = 101, description = "升级为 WebSocket，之后收发 ClientMessage / ServerMessage"),
+        (status = 403, description = "Origin 不是本站也不在 server.allowed_origins 里", body = ErrorBody)
+    )
+)]
+pub async fn ws_handler(ws: WebSocketUpgrade, State(state): State<ApiState>, headers: HeaderMap) -> Response {
+    // 浏览器建 WebSocket 不受 CORS 限制，别的网页也能连上来，只能靠 Origin 把关
+    if !auth::origin_allowed(&state, &headers) {
+        return ApiError::Forbidden("cross-origin WebSocket rejected".to_string()).into_response();
+    }
+    ws.on_upgrade(move |socket| handle_socket(socket, state))
+}
+
+async fn handle_socket(mut socket: WebSocket, state: ApiState) {
+    while let Some(Ok(msg)) = socket.recv().await {
+        let text = match msg {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+
+        let replies = match serde_json::from_str::<ClientMessage>(&text) {
+            Ok(msg) => handle_message(&state, msg).await,
+            Err(e) => vec![ApiError::BadRequest(format!("invalid message: {}", e)).into()],
+        };
+
+        for reply in replies {
+            let Ok(json) = serde_json::to_string(&reply) else { continue };
+            if socket.send(Message::Text(json)).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+async fn handle_message(state: &ApiState, msg: ClientMessage) -> Vec<ServerMessage> {
+    let mut app = state.app.lock().await;
+
+    match msg {
+        ClientMessage::Next => vec![current_card(&mut app).await],
+        ClientMessage::StartSession { mode } => {
+            if let Err(message) = mode.validate() {
+                return vec![ApiError::BadRequest(message).into()];
+            }
+            if let Err(e) = app.start_session(mode).await {
+                return vec![ApiError::from(e).into()];
+            }
+            vec![current_card(&mut app).await]
+        }
+        ClientMessage::Submit { ticket, answer, response_ms, prompt: Prompt::Dictation } => {
+            let (correct, explanation, segments) = match app.ticket_card(&ticket).map(|card| (card.kana_char.clone(), dictation::grade(&card.kana_char, &answer))) {
+                Some((kana, Ok(segments))) => {
+                    let correct = segments.iter().all(|s| s.correct);
+                    let explanation = (!correct).then(|| match audio::homophone_note(&kana) {
+                        Some(note) => format!("{}\n{}", dictation::explain(&segments), note),
+                        None => dictation::explain(&segments),
+                    });
+                    (correct, explanation, segments)
+                }
+                Some((_, Err(c))) => return vec![ApiError::BadRequest(format!("no audio for '{}'", c)).into()],
+                None => (false, None, Vec::new()),
+            };
+
+            submit(&mut app, ticket, correct, explanation, segments, response_ms).await
+        }
+        ClientMessage::Submit { ticket, answer, response_ms, prompt } => {
+            let card = app.ticket_card(&ticket);
+            let correct = card
+                .map(|card| match prompt {
+                    Prompt::Text => reading::typed_matches(&card.kana_char, &card.romaji, &answer),
+                    _ => reading::heard_matches(&card.kana_char, &card.romaji, &answer),
+                })
+                .unwrap_or(false);
+            let explanation = card.filter(|_| !correct).map(|card| {
+                let text = FeedbackGenerator::generate_explanation(&card.kana_char, &card.romaji, &answer);
+                match audio::homophone_note(&card.kana_char).filter(|_| prompt == Prompt::Audio) {
+                    Some(note) => format!("{}\n{}", text, note),
+                    None => text,
+                }
+            });
+
+            submit(&mut app, ticket, correct, explanation, Vec::new(), response_ms).await
+        }
+        ClientMessage::SubmitStrokes { ticket, strokes, response_ms } => {
+            // 票不是当前卡时交给 submit 处理重复和过期
+            let (correct, explanation) = match app.ticket_card(&ticket).map(|card| handwriting::check(&card.kana_char, &strokes)) {
+                Some(Ok(score)) => (score.correct, (!score.correct).then(|| handwriting::explain(&score))),
+                Some(Err(c)) => return vec![ApiError::BadRequest(format!("no stroke data for '{}'", c)).into()],
+                None => (false, None),
+            };
+
+            submit(&mut app, ticket, correct, explanation, Vec::new(), response_ms).await
+        }
+    }
+}
+
+async fn submit(
+    app: &mut App,
+    ticket: String,
+    correct: bool,
+    explanation: Option<String>,
+    segments: Vec<DictationSegment>,
+    response_ms: Option<u64>,
+) -> Vec<ServerMessage> {
+    // 听写有逐拍结果，每张组成卡按自己那几拍记分
+    let result = if segments.is_empty() {
+        app.submit(&ticket, correct, response_ms).await
+    } else {
+        let moras: Vec<bool> = segments.iter().map(|s| s.correct).collect();
+        app.submit_moras(&ticket, &moras, response_ms).await
+    };
+    match result {
+        Ok(Submission::Accepted(answered)) => vec![
+            verdict(&answered, explanation, segments),
+            ServerMessage::Stats {
+                answered: 1,
+                correct: correct as usize,
+                cycle_mistakes_count: app.cycle_mistakes.len(),
+            },
+            current_card(app).await,
+        ],
+        Ok(Submission::Duplicate(answered)) => vec![verdict(&answered, None, Vec::new()), current_card(app).await],
+        Ok(Submission::Stale) => vec![ApiError::StaleSession(format!("ticket {} is not the current card", ticket)).into()],
+        Err(e) => match ApiError::from(e) {
+            // 卡片已被删除：服务端已经跳过它，顺带把下一张发过去
+            err @ ApiError::NotFound(_) => vec![err.into(), current_card(app).await],
+            err => vec![err.into()],
+        },
+    }
+}
+
+fn verdict(answered: &Answered, explanation: Option<String>, segments: Vec<DictationSegment>) -> ServerMessage {
+    ServerMessage::Verdict {
+        ticket: answered.ticket.clone(),
+        card_id: answered.card_id.clone(),
+        correct: answered.correct,
+        expected: answered.expected.clone(),
+        grade: answered.outcome.grade,
+        leech: answered.outcome.leech,
+        explanation,
+        mnemonics: answered.mnemonics.clone(),
+        segments,
+    }
+}
+
+async fn current_card(app: &mut App) -> ServerMessage {
+    let Some(ticket) = app.current().await else {
+        if app.progress.is_finished() {
+            return ServerMessage::SessionEnd { summary: app.progress.summary() };
+        }
+        return ApiError::NotFound("no cards available".to_string()).into();
+    };
+    let is_review = app.is_review_effective();
+
+    ServerMessage::Card {
+        ticket: ticket.id,
+        card: ticket.card,
+        batch_current: app.batch_counter,
+        batch_total: if is_review { app.batch_counter } else { app.estimated_total_batches },
+        remaining_in_deck: app.deck_queue.len(),
+        is_review,
+    }
+}